@@ -0,0 +1,239 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Threshold alarms on PIU housekeeping
+// Rules compare a telemetry field in engineering units (V, A, W, degC) with a
+// threshold, e.g. battery voltage below 7.2 V. check() returns an Alarm for
+// every rule whose condition holds, so FDIR logic sees an alarm for as long as
+// the condition lasts; raised is set only on the check where it started.
+// Feed the monitor from AlarmMonitor::poll() or with the samples of an HkPoller.
+
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+use crate::poller::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::cmp::Reverse;
+#[cfg(feature = "strum")]
+use strum_macros::{Display, EnumIter, EnumString};
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AlarmField {
+    // Internal board supply in V
+    BoardSupply,
+    // MCU temperature in degC
+    McuTemp,
+    // Input of the distribution part
+    DistVoltage,
+    DistCurrent,
+    DistPower,
+    // Input of the battery part
+    BattVoltage,
+    BattCurrent,
+    BattPower,
+    // Battery pack temperatures in degC
+    BattTemp2,
+    BattTemp3,
+    // Voltage of domain 0 - 2
+    DomainVoltage(u8),
+    ChannelVoltage(Channel),
+    ChannelCurrent(Channel),
+    ChannelPower(Channel),
+}
+
+impl AlarmField {
+    // Value of the field in engineering units, None for a domain not in the HK
+    pub fn value(&self, hk: &PIUHk) -> Option<f32> {
+        match self {
+            AlarmField::BoardSupply => Some(hk.volt_brdsup as f32 / 1000.0),
+            AlarmField::McuTemp => Some(hk.temp as f32 / 100.0),
+            AlarmField::DistVoltage => Some(hk.vip_dist_input.volts()),
+            AlarmField::DistCurrent => Some(hk.vip_dist_input.amps()),
+            AlarmField::DistPower => Some(hk.vip_dist_input.watts()),
+            AlarmField::BattVoltage => Some(hk.vip_batt_input.volts()),
+            AlarmField::BattCurrent => Some(hk.vip_batt_input.amps()),
+            AlarmField::BattPower => Some(hk.vip_batt_input.watts()),
+            AlarmField::BattTemp2 => Some(hk.batt_temp2 as f32 / 100.0),
+            AlarmField::BattTemp3 => Some(hk.batt_temp3 as f32 / 100.0),
+            AlarmField::DomainVoltage(vd) => match vd {
+                0 => Some(hk.volt_vd0 as f32 / 1000.0),
+                1 => Some(hk.volt_vd1 as f32 / 1000.0),
+                2 => Some(hk.volt_vd2 as f32 / 1000.0),
+                _ => None,
+            },
+            AlarmField::ChannelVoltage(ch) => hk.vip_cnt_ch(ch.index()).ok().map(|v| v.volts()),
+            AlarmField::ChannelCurrent(ch) => hk.vip_cnt_ch(ch.index()).ok().map(|v| v.amps()),
+            AlarmField::ChannelPower(ch) => hk.vip_cnt_ch(ch.index()).ok().map(|v| v.watts()),
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
+pub enum AlarmCondition {
+    Below,
+    Above,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AlarmRule {
+    pub name: String,
+    pub field: AlarmField,
+    pub condition: AlarmCondition,
+    // Threshold in the unit of the field, the condition is strict
+    pub threshold: f32,
+    pub severity: Severity,
+}
+
+impl AlarmRule {
+    // e.g. AlarmRule::below("battery low", AlarmField::BattVoltage, 7.2, Severity::Critical)
+    pub fn below(name: &str, field: AlarmField, threshold: f32, severity: Severity) -> Self {
+        AlarmRule {
+            name: name.to_string(),
+            field,
+            condition: AlarmCondition::Below,
+            threshold,
+            severity,
+        }
+    }
+
+    pub fn above(name: &str, field: AlarmField, threshold: f32, severity: Severity) -> Self {
+        AlarmRule {
+            name: name.to_string(),
+            field,
+            condition: AlarmCondition::Above,
+            threshold,
+            severity,
+        }
+    }
+
+    // Value of the field if the rule fires on it
+    pub fn evaluate(&self, hk: &PIUHk) -> Option<f32> {
+        let value = self.field.value(hk)?;
+        let fired = match self.condition {
+            AlarmCondition::Below => value < self.threshold,
+            AlarmCondition::Above => value > self.threshold,
+        };
+        if fired {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+// Returned on registration, to remove the rule again
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AlarmId(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Alarm {
+    pub id: AlarmId,
+    pub name: String,
+    pub field: AlarmField,
+    pub severity: Severity,
+    pub value: f32,
+    pub threshold: f32,
+    // The condition didn't hold on the previous check
+    pub raised: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AlarmMonitor {
+    // Rule and whether it fired on the previous check
+    rules: Vec<(AlarmId, AlarmRule, bool)>,
+    next_id: u64,
+}
+
+impl AlarmMonitor {
+    pub fn new() -> Self {
+        AlarmMonitor::default()
+    }
+
+    pub fn add(&mut self, rule: AlarmRule) -> AlarmId {
+        let id = AlarmId(self.next_id);
+        self.next_id += 1;
+        self.rules.push((id, rule, false));
+        id
+    }
+
+    // Returns false if the rule wasn't registered
+    pub fn remove(&mut self, id: AlarmId) -> bool {
+        let len = self.rules.len();
+        self.rules.retain(|(i, _, _)| *i != id);
+        self.rules.len() != len
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = (AlarmId, &AlarmRule)> {
+        self.rules.iter().map(|(id, rule, _)| (*id, rule))
+    }
+
+    // Alarms of the rules firing on the HK, most severe first
+    pub fn check(&mut self, hk: &PIUHk) -> Vec<Alarm> {
+        let mut alarms: Vec<Alarm> = self
+            .rules
+            .iter_mut()
+            .filter_map(|(id, rule, active)| {
+                let value = rule.evaluate(hk);
+                let raised = value.is_some() && !*active;
+                *active = value.is_some();
+                value.map(|value| Alarm {
+                    id: *id,
+                    name: rule.name.clone(),
+                    field: rule.field,
+                    severity: rule.severity,
+                    value,
+                    threshold: rule.threshold,
+                    raised,
+                })
+            })
+            .collect();
+        alarms.sort_by_key(|a| Reverse(a.severity));
+        alarms
+    }
+
+    // Evaluate a poller sample, other products than PIU HK give no alarms
+    pub fn observe(&mut self, data: &PollData) -> Vec<Alarm> {
+        match data {
+            PollData::Hk(HkData::Piu(hk)) => self.check(hk),
+            _ => Vec::new(),
+        }
+    }
+
+    // Read the PIU engineering HK and evaluate the rules on it
+    pub fn poll(&mut self, eps: &Eps) -> EpsResult<Vec<Alarm>> {
+        let hk = eps.piu_hk(PIUHkSel::PIUEngHK)?;
+        Ok(self.check(&hk))
+    }
+}
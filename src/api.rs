@@ -0,0 +1,113 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Service function table
+// The functions a CubeOS service exposes, listed once with their kind (query
+// or command), arguments and output. The EpsApi trait is generated from the
+// table, and service crates pass their own generator macro to eps_api! so the
+// service functions follow the API instead of being wrapped by hand.
+//
+// The callback is invoked with one entry per function:
+//     query: system_status() -> SystemStatus => Eps;
+//     command: mode_switch(mode: ModeSwitch) -> () => Eps;
+// where the last ident is the type or trait implementing the function on Eps.
+// Types are unqualified and expected in scope through `use isis_eps_api::*`.
+
+use crate::*;
+
+#[macro_export]
+macro_rules! eps_api {
+    ($callback:ident) => {
+        $callback! {
+            // System
+            query: eps_ping() -> () => Eps;
+            command: sys_reset(ret_key: u8) -> () => Eps;
+            command: shutdown_all() -> () => Eps;
+            command: watchdog_reset() -> () => Eps;
+            command: correct_time(time_correction: i32) -> () => Eps;
+            command: sync_time_from_host() -> i32 => Eps;
+            command: reset_all_counters() -> () => Eps;
+            query: system_status() -> SystemStatus => Eps;
+            query: reset_statistics() -> ResetStats => Eps;
+            query: firmware_info() -> FirmwareInfo => Eps;
+            // Outputs
+            command: set_group_outputs(typ_group: BusGroup, channels: Vec<Channel>) -> () => Eps;
+            command: set_group_state(typ_group: BusGroup, channels: BusChannelState) -> () => Eps;
+            command: set_single_output(typ_channel: BusChannel, channel: Channel) -> () => Eps;
+            command: ensure_channel(channel: Channel, desired: BusChannel) -> bool => Eps;
+            command: mode_switch(mode: ModeSwitch) -> () => Eps;
+            command: enter_safety(confirm: SafetyConfirm) -> () => Eps;
+            command: exit_safety() -> u32 => Eps;
+            query: overcurrent_state() -> OverCurrentFaultState => Eps;
+            query: channel_fault(ch: Channel) -> ChannelFault => Eps;
+            query: channel_states() -> ChannelStates => Eps;
+            query: abf_state() -> ABFState => Eps;
+            // Housekeeping
+            query: pdu_hk(mode: PDUHkSel) -> PDUHk => Eps;
+            query: pbu_hk(mode: PBUHkSel) -> PBUHk => Eps;
+            query: battery_soc() -> Vec<BatterySoc> => Eps;
+            query: power_summary() -> PowerSummary => Eps;
+            query: pcu_hk(mode: PCUHkSel) -> PCUHk => Eps;
+            query: piu_hk(mode: PIUHkSel) -> PIUHk => Eps;
+            query: piu_hk_short(mode: PIUHkSel) -> PIUHkShort => Eps;
+            // Configuration
+            query: get_config_para_write(param: ConfigParamWrite) -> Output => EpsConfig;
+            query: get_config_para_read(param: ConfigParamRead) -> Output => EpsConfig;
+            query: get_all_config() -> ConfigSnapshot => EpsConfig;
+            command: set_config_para_u32(param: ConfigParamWriteU32, input: u32) -> Output => EpsConfig;
+            command: set_config_para_u16(param: ConfigParamWriteU16, input: u16) -> Output => EpsConfig;
+            command: set_config_para_i16(param: ConfigParamWriteI16, input: i16) -> Output => EpsConfig;
+            command: set_config_para_u8(param: ConfigParamWriteU8, input: u8) -> Output => EpsConfig;
+            command: set_config_para_i8(param: ConfigParamWriteI8, input: i8) -> Output => EpsConfig;
+            command: set_config_para(param: ConfigParamWrite, value: Output) -> Output => EpsConfig;
+            command: set_config_para_verified(param: ConfigParamWrite, value: Output) -> Output => EpsConfig;
+            command: reset_param(param: ConfigParamWrite) -> Output => EpsConfig;
+            command: reset_all_conf() -> () => EpsConfig;
+            command: load_config() -> () => EpsConfig;
+            command: save_config() -> () => EpsConfig;
+            command: save_config_force() -> () => EpsConfig;
+            query: heater_thresholds(pack: u8) -> (f32, f32) => Eps;
+            command: set_heater_thresholds(pack: u8, lo_c: f32, hi_c: f32) -> () => Eps;
+            query: unbalance_thresholds(pack: u8) -> (i16, i16) => Eps;
+            command: set_unbalance_thresholds(pack: u8, lo_mv: i16, hi_mv: i16) -> () => Eps;
+        }
+    };
+}
+
+// EpsApi holds the functions implemented by Eps itself, the config functions
+// come in through the EpsConfig supertrait
+macro_rules! eps_api_trait {
+    (@acc [$($e:tt)*] $kind:ident: $name:ident($($arg:ident: $typ:ty),*) -> $out:ty => Eps; $($rest:tt)*) => {
+        eps_api_trait!(@acc [$($e)* [$name($($arg: $typ),*) -> $out]] $($rest)*);
+    };
+    (@acc [$($e:tt)*] $kind:ident: $name:ident($($arg:ident: $typ:ty),*) -> $out:ty => $via:ident; $($rest:tt)*) => {
+        eps_api_trait!(@acc [$($e)*] $($rest)*);
+    };
+    (@acc [$([$name:ident($($arg:ident: $typ:ty),*) -> $out:ty])*]) => {
+        pub trait EpsApi: EpsConfig {
+            $(fn $name(&self, $($arg: $typ),*) -> EpsResult<$out>;)*
+        }
+        impl EpsApi for Eps {
+            $(fn $name(&self, $($arg: $typ),*) -> EpsResult<$out> {
+                Eps::$name(self, $($arg),*)
+            })*
+        }
+    };
+    ($($t:tt)*) => {
+        eps_api_trait!(@acc [] $($t)*);
+    };
+}
+
+eps_api!(eps_api_trait);
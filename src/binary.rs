@@ -0,0 +1,157 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compact binary encoding of the telemetry and config types (feature "bincode")
+//!
+//! Used to pass EPS data between the EPS service and other CubeOS services.
+//! The layout is the bincode 1.x default and is fixed:
+//! - integers are little-endian with their full width (i16 = 2 bytes, u32 = 4 bytes, ...)
+//! - bool is one byte (0 or 1)
+//! - struct fields are written in declaration order without padding or names
+//! - enums are a u32 variant index (declaration order) followed by the variant's fields
+//! - Vec<T> is a u64 length followed by the elements
+//!
+//! Reordering fields or enum variants in objects.rs/config.rs changes the layout.
+
+use crate::crc16;
+use crate::error::*;
+use crate::objects::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+// Encode any telemetry or config type into its binary layout
+pub fn to_bytes<T: Serialize>(value: &T) -> EpsResult<Vec<u8>> {
+    Ok(bincode::serialize(value)?)
+}
+
+// Decode a telemetry or config type from its binary layout
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> EpsResult<T> {
+    Ok(bincode::deserialize(bytes)?)
+}
+
+// Fixed-size HK records for downlink frames.
+// A record is the binary layout above followed by a CRC-16/CCITT-FALSE
+// (poly 0x1021, init 0xFFFF) over those bytes, little-endian. The HK types
+// only hold fixed-width fields, so every record of a type has the same length
+// and field offsets follow from the declaration order in objects.rs, e.g.
+// PDUHk: volt_brdsup @0, temp @2, vip_input.volt @4, .curr @6, .pwr @8, ...
+// Enum fields (mode, reset cause, channel states) take 4 bytes, status
+// bitfields 2 bytes. The record length of a type is record_len() + 2, e.g. 206
+// bytes for PDUHk and 39 bytes for SystemStatus.
+// PBUHk has optional battery packs and is recorded as PbuRecord instead.
+pub trait HkRecord: Serialize + DeserializeOwned + Default {
+    // Record length without the CRC
+    fn record_len() -> usize {
+        serialized_len(&Self::default())
+    }
+
+    fn to_record(&self) -> EpsResult<Vec<u8>> {
+        Ok(seal(to_bytes(self)?))
+    }
+
+    // ResponseTooShort/InvalidInput on a wrong length, CrcMismatch on a wrong CRC
+    fn from_record(record: &[u8]) -> EpsResult<Self> {
+        from_bytes(unseal(record, Self::record_len())?)
+    }
+}
+
+fn serialized_len<T: Serialize>(value: &T) -> usize {
+    match bincode::serialized_size(value) {
+        Ok(n) => n as usize,
+        Err(_) => 0,
+    }
+}
+
+// Append the CRC
+fn seal(mut bytes: Vec<u8>) -> Vec<u8> {
+    let crc = crc16(&bytes);
+    bytes.extend_from_slice(&crc.to_le_bytes());
+    bytes
+}
+
+// Check length and CRC of a record of len bytes (without the CRC), returns the data.
+// A truncated record is ResponseTooShort, an overlong one InvalidInput.
+fn unseal(record: &[u8], len: usize) -> EpsResult<&[u8]> {
+    if record.len() < len + 2 {
+        return Err(EpsError::ResponseTooShort(len + 2, record.len()));
+    }
+    if record.len() > len + 2 {
+        return Err(EpsError::InvalidInput);
+    }
+    let crc = u16::from_le_bytes([record[len], record[len + 1]]);
+    if crc16(&record[..len]) != crc {
+        return Err(EpsError::CrcMismatch);
+    }
+    Ok(&record[..len])
+}
+
+impl HkRecord for SystemStatus {}
+impl HkRecord for OverCurrentFaultState {}
+impl HkRecord for ChannelStates {}
+impl HkRecord for ABFState {}
+impl HkRecord for PDUHk {}
+impl HkRecord for PCUHk {}
+impl HkRecord for PIUHk {}
+impl HkRecord for PIUHkShort {}
+
+// Record layout of PBUHk: all three packs are always written, absent ones as
+// default, and a byte flags which of bp2 (bit 0) and bp3 (bit 1) are present
+#[derive(Default, Serialize, Deserialize)]
+struct PbuRecord {
+    volt_brdsup: i16,
+    temp: i16,
+    vip_input: VIPData,
+    stat_bu: BattPackStatus,
+    present: u8,
+    bp: [BattPackData; 3],
+}
+
+impl HkRecord for PBUHk {
+    fn record_len() -> usize {
+        serialized_len(&PbuRecord::default())
+    }
+
+    fn to_record(&self) -> EpsResult<Vec<u8>> {
+        let hk = self.clone();
+        let present = hk.bp2.is_some() as u8 | (hk.bp3.is_some() as u8) << 1;
+        let record = PbuRecord {
+            volt_brdsup: hk.volt_brdsup,
+            temp: hk.temp,
+            vip_input: hk.vip_input,
+            stat_bu: hk.stat_bu,
+            present,
+            bp: [
+                hk.bp1,
+                hk.bp2.unwrap_or_default(),
+                hk.bp3.unwrap_or_default(),
+            ],
+        };
+        Ok(seal(to_bytes(&record)?))
+    }
+
+    fn from_record(record: &[u8]) -> EpsResult<Self> {
+        let record: PbuRecord = from_bytes(unseal(record, Self::record_len())?)?;
+        let [bp1, bp2, bp3] = record.bp;
+        Ok(PBUHk {
+            volt_brdsup: record.volt_brdsup,
+            temp: record.temp,
+            vip_input: record.vip_input,
+            stat_bu: record.stat_bu,
+            bp1,
+            bp2: (record.present & 0x01 != 0).then_some(bp2),
+            bp3: (record.present & 0x02 != 0).then_some(bp3),
+        })
+    }
+}
@@ -0,0 +1,223 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Eps construction with optional power-on self test
+// Eps::new() doesn't talk to the EPS, so a missing or wrong unit only shows at
+// the first command. With_post() makes build() check the unit first.
+
+use crate::eps::*;
+use crate::error::*;
+use crate::*;
+use std::fmt;
+
+// Step of the power-on self test
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PostStage {
+    // Opening the I2C device
+    Open,
+    // No-operation command (0x02)
+    Ping,
+    // Identifier read-back, the unit must speak the IVID of the crate
+    Identity,
+    // System status (0x40) read and decoded
+    Status,
+}
+impl fmt::Display for PostStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "thiserror", derive(thiserror::Error))]
+#[cfg_attr(
+    feature = "thiserror",
+    error("EPS self test failed at {stage}: {cause}")
+)]
+pub struct PostError {
+    pub stage: PostStage,
+    pub cause: EpsError,
+}
+#[cfg(not(feature = "thiserror"))]
+impl fmt::Display for PostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EPS self test failed at {}: {}", self.stage, self.cause)
+    }
+}
+#[cfg(not(feature = "thiserror"))]
+impl std::error::Error for PostError {}
+impl From<PostError> for EpsError {
+    fn from(e: PostError) -> EpsError {
+        e.cause
+    }
+}
+
+pub struct EpsBuilder {
+    i2c_path: String,
+    i2c_addr: u16,
+    target: StID,
+    ivid: u8,
+    bid: u8,
+    battery_packs: u8,
+    daughterboard: DaughterboardPresence,
+    soc: SocEstimator,
+    pacing: Option<PacingProfile>,
+    rate_limit: Option<RateLimit>,
+    retry: Option<RetryPolicy>,
+    response_mode: ResponseMode,
+    transfer_mode: TransferMode,
+    verify: bool,
+    strict: bool,
+    post: bool,
+}
+
+impl EpsBuilder {
+    pub fn new(i2c_path: String, i2c_addr: u16) -> Self {
+        EpsBuilder {
+            i2c_path,
+            i2c_addr,
+            target: StID::PiuStid,
+            ivid: ALL_IVID,
+            bid: OVERRIDE_BID,
+            battery_packs: 1,
+            daughterboard: DaughterboardPresence::Present,
+            soc: SocEstimator::default(),
+            pacing: None,
+            rate_limit: None,
+            retry: None,
+            response_mode: ResponseMode::Accept,
+            transfer_mode: TransferMode::Combined,
+            verify: false,
+            strict: false,
+            post: false,
+        }
+    }
+
+    // System addressed by the commands, see Eps::set_target()
+    pub fn target(mut self, target: StID) -> Self {
+        self.target = target;
+        self
+    }
+
+    // Interface version of the firmware, see Eps::set_ivid()
+    pub fn ivid(mut self, ivid: u8) -> Self {
+        self.ivid = ivid;
+        self
+    }
+
+    // Board ID of the unit, see Eps::set_bid()
+    pub fn bid(mut self, bid: u8) -> Self {
+        self.bid = bid;
+        self
+    }
+
+    // Battery packs connected to the PBU, see Eps::set_battery_packs()
+    pub fn battery_packs(mut self, packs: u8) -> Self {
+        self.battery_packs = packs;
+        self
+    }
+
+    // See Eps::set_daughterboard()
+    pub fn daughterboard(mut self, daughterboard: DaughterboardPresence) -> Self {
+        self.daughterboard = daughterboard;
+        self
+    }
+
+    // See Eps::set_soc_estimator()
+    pub fn soc_estimator(mut self, soc: SocEstimator) -> Self {
+        self.soc = soc;
+        self
+    }
+
+    pub fn pacing(mut self, pacing: PacingProfile) -> Self {
+        self.pacing = Some(pacing);
+        self
+    }
+
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    pub fn response_mode(mut self, mode: ResponseMode) -> Self {
+        self.response_mode = mode;
+        self
+    }
+
+    // Separate write and read for I2C masters without combined write-read
+    pub fn transfer_mode(mut self, mode: TransferMode) -> Self {
+        self.transfer_mode = mode;
+        self
+    }
+
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    // Run ping, identity and status checks in build()
+    pub fn with_post(mut self) -> Self {
+        self.post = true;
+        self
+    }
+
+    pub fn build(self) -> Result<Eps, PostError> {
+        let mut eps = stage(PostStage::Open, Eps::new(self.i2c_path, self.i2c_addr))?;
+        if let Some(pacing) = self.pacing {
+            stage(PostStage::Open, eps.set_pacing(pacing))?;
+        }
+        eps.set_target(self.target);
+        eps.set_ivid(self.ivid);
+        eps.set_bid(self.bid);
+        stage(PostStage::Open, eps.set_battery_packs(self.battery_packs))?;
+        eps.set_daughterboard(self.daughterboard);
+        eps.set_soc_estimator(self.soc);
+        eps.set_rate_limit(self.rate_limit);
+        eps.set_retry_policy(self.retry);
+        eps.set_response_mode(self.response_mode);
+        eps.set_transfer_mode(self.transfer_mode);
+        eps.set_verify(self.verify);
+        eps.set_strict(self.strict);
+        if self.post {
+            stage(PostStage::Ping, eps.eps_ping())?;
+            let info = stage(PostStage::Identity, eps.firmware_info())?;
+            if !info.ivid_matches() {
+                return Err(PostError {
+                    stage: PostStage::Identity,
+                    cause: EpsError::InvalidSystemType,
+                });
+            }
+            stage(PostStage::Status, eps.system_status())?;
+        }
+        Ok(eps)
+    }
+}
+
+fn stage<T>(stage: PostStage, result: EpsResult<T>) -> Result<T, PostError> {
+    match result {
+        Ok(x) => Ok(x),
+        Err(cause) => Err(PostError { stage, cause }),
+    }
+}
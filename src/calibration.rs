@@ -0,0 +1,57 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion of raw housekeeping ADC counts to engineering data
+// The coefficients are unit specific and have to be taken from the calibration
+// data of the unit, there is no default set.
+
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+// Linear calibration: eng = gain * raw + offset
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LinearCal {
+    pub gain: f32,
+    pub offset: f32,
+}
+impl LinearCal {
+    pub fn apply(&self, raw: i16) -> i16 {
+        (self.gain * raw as f32 + self.offset).round() as i16
+    }
+    // For results that don't fit the raw width, e.g. the power in mW
+    pub fn apply_wide(&self, raw: i16) -> i32 {
+        (self.gain * raw as f32 + self.offset).round() as i32
+    }
+}
+
+// Calibration of a VIP block, producing mV, mA and mW like VIPData
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VipCalibration {
+    pub volt: LinearCal,
+    pub curr: LinearCal,
+    pub pwr: LinearCal,
+}
+impl VipCalibration {
+    pub fn apply(&self, raw: &VIPRawData) -> VIPData {
+        VIPData {
+            volt: self.volt.apply(raw.volt_raw),
+            curr: self.curr.apply(raw.curr_raw),
+            pwr: self.pwr.apply_wide(raw.pwr_raw),
+        }
+    }
+}
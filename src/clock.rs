@@ -0,0 +1,256 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! EPS clock drift monitoring
+// Compares the EPS UNIX_TIME reported in the system status (0x40) with the OBC
+// clock, estimates the drift rate and optionally steps the EPS clock with the
+// correct time command (0xC4) once the offset exceeds a threshold.
+
+use crate::eps::*;
+use crate::error::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// One comparison of EPS time against OBC time, both in UNIX seconds
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClockSample {
+    pub obc_time: i64,
+    pub eps_time: u32,
+}
+impl ClockSample {
+    // EPS time minus OBC time in seconds, positive if the EPS runs ahead
+    pub fn offset(&self) -> i64 {
+        self.eps_time as i64 - self.obc_time
+    }
+}
+
+// A correction applied to the EPS clock
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeCorrectionEvent {
+    // OBC time the correction was sent at
+    pub obc_time: i64,
+    // Offset measured before the correction
+    pub offset: i64,
+    // Correction sent with 0xC4, in seconds
+    pub correction: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClockDriftMonitor {
+    // Offset (in seconds) above which the EPS clock is corrected
+    pub threshold: u32,
+    // Apply correct_time automatically in check()
+    pub auto_correct: bool,
+    // Number of samples kept for the drift estimate
+    pub window: usize,
+    samples: VecDeque<ClockSample>,
+    events: Vec<TimeCorrectionEvent>,
+}
+impl ClockDriftMonitor {
+    pub fn new(threshold: u32, auto_correct: bool, window: usize) -> Self {
+        ClockDriftMonitor {
+            threshold,
+            auto_correct,
+            window: window.max(2),
+            samples: VecDeque::new(),
+            events: Vec::new(),
+        }
+    }
+
+    // Add a sample, returns its offset
+    pub fn record(&mut self, obc_time: i64, eps_time: u32) -> i64 {
+        let sample = ClockSample { obc_time, eps_time };
+        let offset = sample.offset();
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        offset
+    }
+
+    // Offset of the latest sample
+    pub fn offset(&self) -> Option<i64> {
+        self.samples.back().map(|s| s.offset())
+    }
+
+    // Least-squares drift rate in seconds of offset per second of OBC time,
+    // None until at least two samples at different OBC times are available
+    pub fn drift_rate(&self) -> Option<f64> {
+        if self.samples.len() < 2 {
+            return None;
+        }
+        let n = self.samples.len() as f64;
+        let t0 = self.samples[0].obc_time;
+        let xs = self.samples.iter().map(|s| (s.obc_time - t0) as f64);
+        let x_mean = xs.clone().sum::<f64>() / n;
+        let y_mean = self.samples.iter().map(|s| s.offset() as f64).sum::<f64>() / n;
+        let mut sxy = 0.0;
+        let mut sxx = 0.0;
+        for (x, s) in xs.zip(self.samples.iter()) {
+            sxy += (x - x_mean) * (s.offset() as f64 - y_mean);
+            sxx += (x - x_mean) * (x - x_mean);
+        }
+        if sxx == 0.0 {
+            None
+        } else {
+            Some(sxy / sxx)
+        }
+    }
+
+    // Corrections applied so far, oldest first
+    pub fn events(&self) -> &[TimeCorrectionEvent] {
+        &self.events
+    }
+
+    // Read the EPS time, record it against the OBC clock and correct the
+    // EPS clock if auto_correct is set and the offset exceeds the threshold
+    pub fn check(&mut self, eps: &Eps) -> EpsResult<Option<TimeCorrectionEvent>> {
+        let eps_time = match eps.system_status() {
+            Ok(x) => x.unix_time(),
+            Err(e) => return Err(e),
+        };
+        let obc_time = obc_time();
+        let offset = self.record(obc_time, eps_time);
+
+        if !self.auto_correct || offset.unsigned_abs() <= self.threshold as u64 {
+            return Ok(None);
+        }
+
+        let correction = match i32::try_from(-offset) {
+            Ok(x) => x,
+            Err(_) => return Err(EpsError::InvalidInput),
+        };
+        eps.correct_time(correction)?;
+
+        let event = TimeCorrectionEvent {
+            obc_time,
+            offset,
+            correction,
+        };
+        #[cfg(feature = "debug")]
+        eps_log! {"EPS Time Correction {:?}",event};
+        self.events.push(event.clone());
+        // The offset has been stepped, earlier samples no longer apply
+        self.samples.clear();
+        Ok(Some(event))
+    }
+}
+
+// Periodic absolute time synchronisation of the EPS clock with the OBC clock
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeSync {
+    // Time between syncs, e.g. once per orbit
+    pub cadence: Duration,
+    // Offset (in seconds) below which a sync is skipped
+    pub tolerance: u32,
+}
+impl TimeSync {
+    pub fn new(cadence: Duration, tolerance: u32) -> Self {
+        TimeSync { cadence, tolerance }
+    }
+
+    // Start syncing in a background thread, the first sync is done immediately.
+    // Failed syncs are retried at the next cadence.
+    pub fn spawn(&self, eps: Arc<Mutex<Eps>>) -> TimeSyncHandle {
+        let (stop, stopped) = mpsc::channel();
+        let cadence = self.cadence;
+        let mut monitor = ClockDriftMonitor::new(self.tolerance, true, 2);
+        let thread = thread::spawn(move || {
+            loop {
+                let eps = eps.lock().unwrap_or_else(PoisonError::into_inner);
+                let _res = monitor.check(&eps);
+                #[cfg(feature = "debug")]
+                eps_log! {"Time Sync {:?}",_res};
+                drop(eps);
+                match stopped.recv_timeout(cadence) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    _ => break,
+                }
+            }
+            monitor
+        });
+        TimeSyncHandle {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+// Handle of a running TimeSync, the thread is stopped when the handle is dropped
+pub struct TimeSyncHandle {
+    stop: Sender<()>,
+    thread: Option<JoinHandle<ClockDriftMonitor>>,
+}
+impl TimeSyncHandle {
+    // Stop syncing, returns the corrections applied while running
+    pub fn stop(mut self) -> Vec<TimeCorrectionEvent> {
+        self.shutdown()
+            .map(|m| m.events().to_vec())
+            .unwrap_or_default()
+    }
+
+    fn shutdown(&mut self) -> Option<ClockDriftMonitor> {
+        let _ = self.stop.send(());
+        self.thread.take().and_then(|t| t.join().ok())
+    }
+}
+impl Drop for TimeSyncHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl Eps {
+    // Read the EPS time and correct it by its offset to the host clock in one go,
+    // returns the correction sent (0 if the clocks already agree)
+    pub fn sync_time_from_host(&self) -> EpsResult<i32> {
+        let eps_time = match self.system_status() {
+            Ok(x) => x.unix_time(),
+            Err(e) => return Err(e),
+        };
+        let sample = ClockSample {
+            obc_time: obc_time(),
+            eps_time,
+        };
+        let correction = match i32::try_from(-sample.offset()) {
+            Ok(x) => x,
+            Err(_) => return Err(EpsError::InvalidInput),
+        };
+        if correction != 0 {
+            self.correct_time(correction)?;
+        }
+        #[cfg(feature = "debug")]
+        eps_log! {"EPS Time Sync {:?}, correction {}",sample,correction};
+        Ok(correction)
+    }
+}
+
+// Current OBC time in UNIX seconds
+pub(crate) fn obc_time() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
@@ -1,15 +1,20 @@
 use crate::error::*;
 
 use crate::eps::*;
+use crate::icd::transfer_error;
 use crate::ConfigParamRead::*;
 use crate::ConfigParamWrite::*;
 use crate::*;
 use i2c_rs::Command;
+#[cfg(feature = "serde")]
 use serde::*;
-use std::time::Duration;
+#[cfg(feature = "strum")]
 use strum_macros::{Display, EnumIter, EnumString};
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, EnumIter, Display, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, Display))]
 pub enum Output {
     U32(u32),
     U16(u16),
@@ -18,19 +23,32 @@ pub enum Output {
     I8(i8),
 }
 
-#[derive(
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-    Hash,
-)]
+impl Output {
+    // Value widened to i64, for comparisons and reports independent of the parameter width
+    pub fn as_i64(&self) -> i64 {
+        match self {
+            Output::U32(x) => *x as i64,
+            Output::U16(x) => *x as i64,
+            Output::I16(x) => *x as i64,
+            Output::U8(x) => *x as i64,
+            Output::I8(x) => *x as i64,
+        }
+    }
+
+    // Width of the value in bytes, as ConfigParamWrite::get_len()
+    pub fn width(&self) -> usize {
+        match self {
+            Output::U32(_) => 4,
+            Output::U16(_) | Output::I16(_) => 2,
+            Output::U8(_) | Output::I8(_) => 1,
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum ConfigParamWriteU32 {
     #[default]
     ChStartupEnaBf,
@@ -39,19 +57,10 @@ pub enum ConfigParamWriteU32 {
     ChLatchoffKey,
 }
 
-#[derive(
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-    Hash,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum ConfigParamWriteU16 {
     #[default]
     TtcWdgTimeout,
@@ -62,78 +71,60 @@ pub enum ConfigParamWriteU16 {
     SafetyVoltHiThr,
 }
 
-#[derive(
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-    Hash,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum ConfigParamWriteI16 {
     #[default]
     LoThrBp1Heater,
-    // LoThrBp2Heater,
-    // LoThrBp3Heater,
+    LoThrBp2Heater,
+    LoThrBp3Heater,
     HiThrBp1Heater,
-    // HiThrBp2Heater,
-    // HiThrBp3Heater,
+    HiThrBp2Heater,
+    HiThrBp3Heater,
     LoThrBp1Unbal,
-    // LoThrBp2Unbal,
-    // LoThrBp3Unbal,
+    LoThrBp2Unbal,
+    LoThrBp3Unbal,
     HiThrBp1Unbal,
-    // HiThrBp2Unbal,
-    // HiThrBp3Unbal,
+    HiThrBp2Unbal,
+    HiThrBp3Unbal,
     McuTempBias,
     McuTempPremul,
     McuTempPosDiv,
     Bp1Temp1Bias,
     Bp1Temp2Bias,
     Bp1Temp3Bias,
-    // Bp2Temp1Bias,
-    // Bp2Temp2Bias,
-    // Bp2Temp3Bias,
-    // Bp3Temp1Bias,
-    // Bp3Temp2Bias,
-    // Bp3Temp3Bias,
+    Bp2Temp1Bias,
+    Bp2Temp2Bias,
+    Bp2Temp3Bias,
+    Bp3Temp1Bias,
+    Bp3Temp2Bias,
+    Bp3Temp3Bias,
     Bp1Temp1Premul,
     Bp1Temp2Premul,
     Bp1Temp3Premul,
-    // Bp2Temp1Premul,
-    // Bp2Temp2Premul,
-    // Bp2Temp3Premul,
-    // Bp3Temp1Premul,
-    // Bp3Temp2Premul,
-    // Bp3Temp3Premul,
+    Bp2Temp1Premul,
+    Bp2Temp2Premul,
+    Bp2Temp3Premul,
+    Bp3Temp1Premul,
+    Bp3Temp2Premul,
+    Bp3Temp3Premul,
     Bp1Temp1PosDiv,
     Bp1Temp2PosDiv,
     Bp1Temp3PosDiv,
-    // Bp2Temp1PosDiv,
-    // Bp2Temp2PosDiv,
-    // Bp2Temp3PosDiv,
-    // Bp3Temp1PosDiv,
-    // Bp3Temp2PosDiv,
-    // Bp3Temp3PosDiv,
+    Bp2Temp1PosDiv,
+    Bp2Temp2PosDiv,
+    Bp2Temp3PosDiv,
+    Bp3Temp1PosDiv,
+    Bp3Temp2PosDiv,
+    Bp3Temp3PosDiv,
 }
 
-#[derive(
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-    Hash,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum ConfigParamWriteU8 {
     #[default]
     BoardId,
@@ -141,27 +132,18 @@ pub enum ConfigParamWriteU8 {
     RavgStrengthP2,
 }
 
-#[derive(
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-    Hash,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum ConfigParamWriteI8 {
     #[default]
     AutoHeatEnaBP1,
-    // AutoHeatEnaBP2,
-    // AutoHeatEnaBP3,
+    AutoHeatEnaBP2,
+    AutoHeatEnaBP3,
     AutoBalEnaBP1,
-    // AutoBalEnaBP2,
-    // AutoBalEnaBP3,
+    AutoBalEnaBP2,
+    AutoBalEnaBP3,
     Vd1AlwaysEna,
     Vd2AlwaysEna,
     Vd3AlwaysEna,
@@ -176,19 +158,10 @@ pub enum ConfigParamWriteI8 {
     Vd6AlwaysDisa,
 }
 
-#[derive(
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-    Hash,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum ConfigParamWrite {
     ChStartupEnaBf,
     ChStartupKey,
@@ -202,56 +175,56 @@ pub enum ConfigParamWrite {
     SafetyVoltHiThr,
     LoThrBp1Heater,
     HiThrBp1Heater,
-    // LoThrBp2Heater,
-    // HiThrBp2Heater,
-    // LoThrBp3Heater,
-    // HiThrBp3Heater,
+    LoThrBp2Heater,
+    HiThrBp2Heater,
+    LoThrBp3Heater,
+    HiThrBp3Heater,
     LoThrBp1Unbal,
     HiThrBp1Unbal,
-    // LoThrBp2Unbal,
-    // HiThrBp2Unbal,
-    // LoThrBp3Unbal,
-    // HiThrBp3Unbal,
+    LoThrBp2Unbal,
+    HiThrBp2Unbal,
+    LoThrBp3Unbal,
+    HiThrBp3Unbal,
     McuTempBias,
     McuTempPremul,
     McuTempPosDiv,
     Bp1Temp1Bias,
     Bp1Temp2Bias,
     Bp1Temp3Bias,
-    // Bp2Temp1Bias,
-    // Bp2Temp2Bias,
-    // Bp2Temp3Bias,
-    // Bp3Temp1Bias,
-    // Bp3Temp2Bias,
-    // Bp3Temp3Bias,
+    Bp2Temp1Bias,
+    Bp2Temp2Bias,
+    Bp2Temp3Bias,
+    Bp3Temp1Bias,
+    Bp3Temp2Bias,
+    Bp3Temp3Bias,
     Bp1Temp1Premul,
     Bp1Temp2Premul,
     Bp1Temp3Premul,
-    // Bp2Temp1Premul,
-    // Bp2Temp2Premul,
-    // Bp2Temp3Premul,
-    // Bp3Temp1Premul,
-    // Bp3Temp2Premul,
-    // Bp3Temp3Premul,
+    Bp2Temp1Premul,
+    Bp2Temp2Premul,
+    Bp2Temp3Premul,
+    Bp3Temp1Premul,
+    Bp3Temp2Premul,
+    Bp3Temp3Premul,
     Bp1Temp1PosDiv,
     Bp1Temp2PosDiv,
     Bp1Temp3PosDiv,
-    // Bp2Temp1PosDiv,
-    // Bp2Temp2PosDiv,
-    // Bp2Temp3PosDiv,
-    // Bp3Temp1PosDiv,
-    // Bp3Temp2PosDiv,
-    // Bp3Temp3PosDiv,
+    Bp2Temp1PosDiv,
+    Bp2Temp2PosDiv,
+    Bp2Temp3PosDiv,
+    Bp3Temp1PosDiv,
+    Bp3Temp2PosDiv,
+    Bp3Temp3PosDiv,
     #[default]
     BoardId,
     BoardIdKey,
     RavgStrengthP2,
     AutoHeatEnaBP1,
-    // AutoHeatEnaBP2,
-    // AutoHeatEnaBP3,
+    AutoHeatEnaBP2,
+    AutoHeatEnaBP3,
     AutoBalEnaBP1,
-    // AutoBalEnaBP2,
-    // AutoBalEnaBP3,
+    AutoBalEnaBP2,
+    AutoBalEnaBP3,
     Vd1AlwaysEna,
     Vd2AlwaysEna,
     Vd3AlwaysEna,
@@ -265,8 +238,150 @@ pub enum ConfigParamWrite {
     Vd5AlwaysDisa,
     Vd6AlwaysDisa,
 }
+// Parameter ID lookup table for the writable configuration parameters, sorted by ID.
+// Both get_id() and from_id() are derived from this table, so the two can't drift apart.
+// ChStartupDelay/ChLatchoffDelay have one entry per channel 0 - 31, 0x4002 - 0x4021 and
+// 0x4022 - 0x4041, following TtcWdgTimeout(Key) at 0x4000/0x4001.
+const CONFIG_PARAM_WRITE_IDS: [(u16, ConfigParamWrite); 135] = [
+    (0x1001, AutoHeatEnaBP1),
+    (0x1002, AutoHeatEnaBP2),
+    (0x1003, AutoHeatEnaBP3),
+    (0x1004, AutoBalEnaBP1),
+    (0x1005, AutoBalEnaBP2),
+    (0x1006, AutoBalEnaBP3),
+    (0x1007, Vd1AlwaysEna),
+    (0x1008, Vd2AlwaysEna),
+    (0x1009, Vd3AlwaysEna),
+    (0x100A, Vd4AlwaysEna),
+    (0x100B, Vd5AlwaysEna),
+    (0x100C, Vd6AlwaysEna),
+    (0x100E, Vd1AlwaysDisa),
+    (0x100F, Vd2AlwaysDisa),
+    (0x1010, Vd3AlwaysDisa),
+    (0x1011, Vd4AlwaysDisa),
+    (0x1012, Vd5AlwaysDisa),
+    (0x1013, Vd6AlwaysDisa),
+    (0x2000, BoardId),
+    (0x2001, BoardIdKey),
+    (0x2002, RavgStrengthP2),
+    (0x3000, LoThrBp1Heater),
+    (0x3001, LoThrBp2Heater),
+    (0x3002, LoThrBp3Heater),
+    (0x3003, HiThrBp1Heater),
+    (0x3004, HiThrBp2Heater),
+    (0x3005, HiThrBp3Heater),
+    (0x3006, LoThrBp1Unbal),
+    (0x3007, LoThrBp2Unbal),
+    (0x3008, LoThrBp3Unbal),
+    (0x3009, HiThrBp1Unbal),
+    (0x300A, HiThrBp2Unbal),
+    (0x300B, HiThrBp3Unbal),
+    (0x300C, McuTempBias),
+    (0x300D, McuTempPremul),
+    (0x300E, McuTempPosDiv),
+    (0x300F, Bp1Temp1Bias),
+    (0x3010, Bp1Temp2Bias),
+    (0x3011, Bp1Temp3Bias),
+    (0x3012, Bp2Temp1Bias),
+    (0x3013, Bp2Temp2Bias),
+    (0x3014, Bp2Temp3Bias),
+    (0x3015, Bp3Temp1Bias),
+    (0x3016, Bp3Temp2Bias),
+    (0x3017, Bp3Temp3Bias),
+    (0x3018, Bp1Temp1Premul),
+    (0x3019, Bp1Temp2Premul),
+    (0x301A, Bp1Temp3Premul),
+    (0x301B, Bp2Temp1Premul),
+    (0x301C, Bp2Temp2Premul),
+    (0x301D, Bp2Temp3Premul),
+    (0x301E, Bp3Temp1Premul),
+    (0x301F, Bp3Temp2Premul),
+    (0x3020, Bp3Temp3Premul),
+    (0x3021, Bp1Temp1PosDiv),
+    (0x3022, Bp1Temp2PosDiv),
+    (0x3023, Bp1Temp3PosDiv),
+    (0x3024, Bp2Temp1PosDiv),
+    (0x3025, Bp2Temp2PosDiv),
+    (0x3026, Bp2Temp3PosDiv),
+    (0x3027, Bp3Temp1PosDiv),
+    (0x3028, Bp3Temp2PosDiv),
+    (0x3029, Bp3Temp3PosDiv),
+    (0x4000, TtcWdgTimeout),
+    (0x4001, TtcWdgTimeoutKey),
+    (0x4002, ChStartupDelay(0)),
+    (0x4003, ChStartupDelay(1)),
+    (0x4004, ChStartupDelay(2)),
+    (0x4005, ChStartupDelay(3)),
+    (0x4006, ChStartupDelay(4)),
+    (0x4007, ChStartupDelay(5)),
+    (0x4008, ChStartupDelay(6)),
+    (0x4009, ChStartupDelay(7)),
+    (0x400A, ChStartupDelay(8)),
+    (0x400B, ChStartupDelay(9)),
+    (0x400C, ChStartupDelay(10)),
+    (0x400D, ChStartupDelay(11)),
+    (0x400E, ChStartupDelay(12)),
+    (0x400F, ChStartupDelay(13)),
+    (0x4010, ChStartupDelay(14)),
+    (0x4011, ChStartupDelay(15)),
+    (0x4012, ChStartupDelay(16)),
+    (0x4013, ChStartupDelay(17)),
+    (0x4014, ChStartupDelay(18)),
+    (0x4015, ChStartupDelay(19)),
+    (0x4016, ChStartupDelay(20)),
+    (0x4017, ChStartupDelay(21)),
+    (0x4018, ChStartupDelay(22)),
+    (0x4019, ChStartupDelay(23)),
+    (0x401A, ChStartupDelay(24)),
+    (0x401B, ChStartupDelay(25)),
+    (0x401C, ChStartupDelay(26)),
+    (0x401D, ChStartupDelay(27)),
+    (0x401E, ChStartupDelay(28)),
+    (0x401F, ChStartupDelay(29)),
+    (0x4020, ChStartupDelay(30)),
+    (0x4021, ChStartupDelay(31)),
+    (0x4022, ChLatchoffDelay(0)),
+    (0x4023, ChLatchoffDelay(1)),
+    (0x4024, ChLatchoffDelay(2)),
+    (0x4025, ChLatchoffDelay(3)),
+    (0x4026, ChLatchoffDelay(4)),
+    (0x4027, ChLatchoffDelay(5)),
+    (0x4028, ChLatchoffDelay(6)),
+    (0x4029, ChLatchoffDelay(7)),
+    (0x402A, ChLatchoffDelay(8)),
+    (0x402B, ChLatchoffDelay(9)),
+    (0x402C, ChLatchoffDelay(10)),
+    (0x402D, ChLatchoffDelay(11)),
+    (0x402E, ChLatchoffDelay(12)),
+    (0x402F, ChLatchoffDelay(13)),
+    (0x4030, ChLatchoffDelay(14)),
+    (0x4031, ChLatchoffDelay(15)),
+    (0x4032, ChLatchoffDelay(16)),
+    (0x4033, ChLatchoffDelay(17)),
+    (0x4034, ChLatchoffDelay(18)),
+    (0x4035, ChLatchoffDelay(19)),
+    (0x4036, ChLatchoffDelay(20)),
+    (0x4037, ChLatchoffDelay(21)),
+    (0x4038, ChLatchoffDelay(22)),
+    (0x4039, ChLatchoffDelay(23)),
+    (0x403A, ChLatchoffDelay(24)),
+    (0x403B, ChLatchoffDelay(25)),
+    (0x403C, ChLatchoffDelay(26)),
+    (0x403D, ChLatchoffDelay(27)),
+    (0x403E, ChLatchoffDelay(28)),
+    (0x403F, ChLatchoffDelay(29)),
+    (0x4040, ChLatchoffDelay(30)),
+    (0x4041, ChLatchoffDelay(31)),
+    (0x4042, SafetyVoltLoThr),
+    (0x4043, SafetyVoltHiThr),
+    (0x6002, ChStartupEnaBf),
+    (0x6003, ChStartupKey),
+    (0x6004, ChLatchoffEnaBf),
+    (0x6005, ChLatchoffKey),
+];
+
 impl ConfigParamWrite {
-    pub fn get_id(&self) -> u16 {
+    pub const fn get_id(&self) -> u16 {
         match self {
             ChStartupEnaBf => 0x6002,
             ChStartupKey => 0x6003,
@@ -274,93 +389,61 @@ impl ConfigParamWrite {
             ChLatchoffKey => 0x6005,
             TtcWdgTimeout => 0x4000,
             TtcWdgTimeoutKey => 0x4001,
-            ChStartupDelay(0) => 0x4002,
-            ChStartupDelay(1) => 0x4003,
-            ChStartupDelay(2) => 0x4004,
-            ChStartupDelay(3) => 0x4005,
-            ChStartupDelay(4) => 0x4006,
-            ChStartupDelay(5) => 0x4007,
-            ChStartupDelay(6) => 0x4008,
-            ChStartupDelay(7) => 0x4009,
-            ChStartupDelay(8) => 0x400A,
-            ChStartupDelay(9) => 0x400B,
-            ChStartupDelay(10) => 0x400C,
-            ChStartupDelay(11) => 0x400D,
-            ChStartupDelay(12) => 0x400E,
-            ChStartupDelay(13) => 0x400F,
-            ChStartupDelay(14) => 0x4010,
-            ChStartupDelay(15) => 0x4011,
-            ChStartupDelay(16) => 0x4012,
-            ChStartupDelay(17) => 0x4013,
-            ChStartupDelay(18) => 0x4014,
-            ChStartupDelay(19) => 0x4015,
-            ChStartupDelay(20) => 0x4016,
-            ChStartupDelay(21) => 0x4017,
-            ChStartupDelay(22) => 0x4018,
-            ChStartupDelay(23) => 0x4019,
-            ChStartupDelay(24) => 0x401A,
-            ChStartupDelay(25) => 0x401B,
-            ChStartupDelay(26) => 0x401C,
-            ChStartupDelay(27) => 0x401D,
-            ChStartupDelay(28) => 0x401E,
-            ChStartupDelay(29) => 0x401F,
-            ChStartupDelay(30) => 0x4020,
-            ChStartupDelay(31) => 0x4021,
-            ChLatchoffDelay(0) => 0x4022,
-            ChLatchoffDelay(1) => 0x4023,
-            ChLatchoffDelay(2) => 0x4024,
-            ChLatchoffDelay(3) => 0x4025,
-            ChLatchoffDelay(4) => 0x4026,
-            ChLatchoffDelay(5) => 0x4027,
-            ChLatchoffDelay(6) => 0x4028,
-            ChLatchoffDelay(7) => 0x4029,
-            ChLatchoffDelay(8) => 0x402A,
-            ChLatchoffDelay(9) => 0x402B,
-            ChLatchoffDelay(10) => 0x402C,
-            ChLatchoffDelay(11) => 0x402D,
-            ChLatchoffDelay(12) => 0x402E,
-            ChLatchoffDelay(13) => 0x402F,
-            ChLatchoffDelay(14) => 0x4030,
-            ChLatchoffDelay(15) => 0x4031,
-            ChLatchoffDelay(16) => 0x4032,
-            ChLatchoffDelay(17) => 0x4033,
-            ChLatchoffDelay(18) => 0x4034,
-            ChLatchoffDelay(19) => 0x4035,
-            ChLatchoffDelay(20) => 0x4036,
-            ChLatchoffDelay(21) => 0x4037,
-            ChLatchoffDelay(22) => 0x4038,
-            ChLatchoffDelay(23) => 0x4039,
-            ChLatchoffDelay(24) => 0x403A,
-            ChLatchoffDelay(25) => 0x403B,
-            ChLatchoffDelay(26) => 0x403C,
-            ChLatchoffDelay(27) => 0x403D,
-            ChLatchoffDelay(28) => 0x403E,
-            ChLatchoffDelay(29) => 0x403F,
-            ChLatchoffDelay(30) => 0x4040,
-            ChLatchoffDelay(31) => 0x4041,
+            ChStartupDelay(n @ 0..=31) => 0x4002 + *n as u16,
+            ChLatchoffDelay(n @ 0..=31) => 0x4022 + *n as u16,
             SafetyVoltLoThr => 0x4042,
             SafetyVoltHiThr => 0x4043,
             LoThrBp1Heater => 0x3000,
             HiThrBp1Heater => 0x3003,
+            LoThrBp2Heater => 0x3001,
+            HiThrBp2Heater => 0x3004,
+            LoThrBp3Heater => 0x3002,
+            HiThrBp3Heater => 0x3005,
             LoThrBp1Unbal => 0x3006,
             HiThrBp1Unbal => 0x3009,
+            LoThrBp2Unbal => 0x3007,
+            HiThrBp2Unbal => 0x300A,
+            LoThrBp3Unbal => 0x3008,
+            HiThrBp3Unbal => 0x300B,
             McuTempBias => 0x300C,
             McuTempPremul => 0x300D,
             McuTempPosDiv => 0x300E,
             Bp1Temp1Bias => 0x300F,
             Bp1Temp2Bias => 0x3010,
             Bp1Temp3Bias => 0x3011,
+            Bp2Temp1Bias => 0x3012,
+            Bp2Temp2Bias => 0x3013,
+            Bp2Temp3Bias => 0x3014,
+            Bp3Temp1Bias => 0x3015,
+            Bp3Temp2Bias => 0x3016,
+            Bp3Temp3Bias => 0x3017,
             Bp1Temp1Premul => 0x3018,
             Bp1Temp2Premul => 0x3019,
             Bp1Temp3Premul => 0x301A,
+            Bp2Temp1Premul => 0x301B,
+            Bp2Temp2Premul => 0x301C,
+            Bp2Temp3Premul => 0x301D,
+            Bp3Temp1Premul => 0x301E,
+            Bp3Temp2Premul => 0x301F,
+            Bp3Temp3Premul => 0x3020,
             Bp1Temp1PosDiv => 0x3021,
             Bp1Temp2PosDiv => 0x3022,
             Bp1Temp3PosDiv => 0x3023,
+            Bp2Temp1PosDiv => 0x3024,
+            Bp2Temp2PosDiv => 0x3025,
+            Bp2Temp3PosDiv => 0x3026,
+            Bp3Temp1PosDiv => 0x3027,
+            Bp3Temp2PosDiv => 0x3028,
+            Bp3Temp3PosDiv => 0x3029,
             BoardId => 0x2000,
             BoardIdKey => 0x2001,
             RavgStrengthP2 => 0x2002,
             AutoHeatEnaBP1 => 0x1001,
+            AutoHeatEnaBP2 => 0x1002,
+            AutoHeatEnaBP3 => 0x1003,
             AutoBalEnaBP1 => 0x1004,
+            AutoBalEnaBP2 => 0x1005,
+            AutoBalEnaBP3 => 0x1006,
             Vd1AlwaysEna => 0x1007,
             Vd2AlwaysEna => 0x1008,
             Vd3AlwaysEna => 0x1009,
@@ -373,56 +456,22 @@ impl ConfigParamWrite {
             Vd4AlwaysDisa => 0x1011,
             Vd5AlwaysDisa => 0x1012,
             Vd6AlwaysDisa => 0x1013,
-            ChStartupDelay(_) => 0x4000,
-            ChLatchoffDelay(_) => 0x4000,
+            // Channel index outside of 0..=31, 0x0000 isn't a parameter ID
+            // so it can't alias another parameter
+            ChStartupDelay(_) | ChLatchoffDelay(_) => 0x0000,
+        }
+    }
+    // Parameter ID, InvalidInput for a channel index outside of 0..=31
+    pub fn try_id(&self) -> EpsResult<u16> {
+        match self.get_id() {
+            0x0000 => Err(EpsError::InvalidInput),
+            id => Ok(id),
         }
     }
     pub fn from_id(id: u16) -> Option<Self> {
-        match id {
-            0x1001 => Some(AutoHeatEnaBP1),
-            0x1004 => Some(AutoBalEnaBP1),
-            0x1007 => Some(Vd1AlwaysEna),
-            0x1008 => Some(Vd2AlwaysEna),
-            0x1009 => Some(Vd3AlwaysEna),
-            0x100A => Some(Vd4AlwaysEna),
-            0x100B => Some(Vd5AlwaysEna),
-            0x100C => Some(Vd6AlwaysEna),
-            0x100E => Some(Vd1AlwaysDisa),
-            0x100F => Some(Vd2AlwaysDisa),
-            0x1010 => Some(Vd3AlwaysDisa),
-            0x1011 => Some(Vd4AlwaysDisa),
-            0x1012 => Some(Vd5AlwaysDisa),
-            0x1013 => Some(Vd6AlwaysDisa),
-            0x2000 => Some(BoardId),
-            0x2001 => Some(BoardIdKey),
-            0x2002 => Some(RavgStrengthP2),
-            0x3000 => Some(LoThrBp1Heater),
-            0x3003 => Some(HiThrBp1Heater),
-            0x3006 => Some(LoThrBp1Unbal),
-            0x3009 => Some(HiThrBp1Unbal),
-            0x300C => Some(McuTempBias),
-            0x300D => Some(McuTempPremul),
-            0x300E => Some(McuTempPosDiv),
-            0x300F => Some(Bp1Temp1Bias),
-            0x3010 => Some(Bp1Temp2Bias),
-            0x3011 => Some(Bp1Temp3Bias),
-            0x3018 => Some(Bp1Temp1Premul),
-            0x3019 => Some(Bp1Temp2Premul),
-            0x301A => Some(Bp1Temp3Premul),
-            0x3021 => Some(Bp1Temp1PosDiv),
-            0x3022 => Some(Bp1Temp2PosDiv),
-            0x3023 => Some(Bp1Temp3PosDiv),
-            0x4000 => Some(TtcWdgTimeout),
-            0x4001 => Some(TtcWdgTimeoutKey),
-            0x4000..=0x401F => Some(ChStartupDelay(id.to_le_bytes()[0] - 0x00)),
-            0x4022..=0x403F => Some(ChLatchoffDelay(id.to_le_bytes()[0] - 0x22)),
-            0x4042 => Some(SafetyVoltLoThr),
-            0x4043 => Some(SafetyVoltHiThr),
-            0x6002 => Some(ChStartupEnaBf),
-            0x6003 => Some(ChStartupKey),
-            0x6004 => Some(ChLatchoffEnaBf),
-            0x6005 => Some(ChLatchoffKey),
-            _ => None,
+        match CONFIG_PARAM_WRITE_IDS.binary_search_by_key(&id, |(i, _)| *i) {
+            Ok(idx) => Some(CONFIG_PARAM_WRITE_IDS[idx].1.clone()),
+            Err(_) => None,
         }
     }
     pub fn get_len(&self) -> usize {
@@ -434,7 +483,29 @@ impl ConfigParamWrite {
         }
     }
     pub fn iter_id() -> impl Iterator<Item = u16> {
-        (0x0000..=0xFFFF).filter(|&id| ConfigParamWrite::from_id(id).is_some())
+        CONFIG_PARAM_WRITE_IDS.iter().map(|(id, _)| *id)
+    }
+    // Battery pack the parameter belongs to, 1 for battery pack 1 and the parameters of the unit
+    pub fn battery_pack(&self) -> u8 {
+        use ConfigParamWrite::*;
+        match self {
+            AutoHeatEnaBP2 | AutoBalEnaBP2 | LoThrBp2Heater | HiThrBp2Heater | LoThrBp2Unbal
+            | HiThrBp2Unbal | Bp2Temp1Bias | Bp2Temp2Bias | Bp2Temp3Bias | Bp2Temp1Premul
+            | Bp2Temp2Premul | Bp2Temp3Premul | Bp2Temp1PosDiv | Bp2Temp2PosDiv
+            | Bp2Temp3PosDiv => 2,
+            AutoHeatEnaBP3 | AutoBalEnaBP3 | LoThrBp3Heater | HiThrBp3Heater | LoThrBp3Unbal
+            | HiThrBp3Unbal | Bp3Temp1Bias | Bp3Temp2Bias | Bp3Temp3Bias | Bp3Temp1Premul
+            | Bp3Temp2Premul | Bp3Temp3Premul | Bp3Temp1PosDiv | Bp3Temp2PosDiv
+            | Bp3Temp3PosDiv => 3,
+            _ => 1,
+        }
+    }
+    // Parameters present on a unit with the given number of battery packs, in ID order
+    pub fn iter_for_packs(packs: u8) -> impl Iterator<Item = &'static ConfigParamWrite> {
+        CONFIG_PARAM_WRITE_IDS
+            .iter()
+            .map(|(_, param)| param)
+            .filter(move |param| param.battery_pack() <= packs)
     }
 }
 
@@ -481,6 +552,32 @@ impl From<ConfigParamWriteI16> for ConfigParamWrite {
             ConfigParamWriteI16::Bp1Temp1PosDiv => ConfigParamWrite::Bp1Temp1PosDiv,
             ConfigParamWriteI16::Bp1Temp2PosDiv => ConfigParamWrite::Bp1Temp2PosDiv,
             ConfigParamWriteI16::Bp1Temp3PosDiv => ConfigParamWrite::Bp1Temp3PosDiv,
+            ConfigParamWriteI16::LoThrBp2Heater => ConfigParamWrite::LoThrBp2Heater,
+            ConfigParamWriteI16::LoThrBp3Heater => ConfigParamWrite::LoThrBp3Heater,
+            ConfigParamWriteI16::HiThrBp2Heater => ConfigParamWrite::HiThrBp2Heater,
+            ConfigParamWriteI16::HiThrBp3Heater => ConfigParamWrite::HiThrBp3Heater,
+            ConfigParamWriteI16::LoThrBp2Unbal => ConfigParamWrite::LoThrBp2Unbal,
+            ConfigParamWriteI16::LoThrBp3Unbal => ConfigParamWrite::LoThrBp3Unbal,
+            ConfigParamWriteI16::HiThrBp2Unbal => ConfigParamWrite::HiThrBp2Unbal,
+            ConfigParamWriteI16::HiThrBp3Unbal => ConfigParamWrite::HiThrBp3Unbal,
+            ConfigParamWriteI16::Bp2Temp1Bias => ConfigParamWrite::Bp2Temp1Bias,
+            ConfigParamWriteI16::Bp2Temp2Bias => ConfigParamWrite::Bp2Temp2Bias,
+            ConfigParamWriteI16::Bp2Temp3Bias => ConfigParamWrite::Bp2Temp3Bias,
+            ConfigParamWriteI16::Bp3Temp1Bias => ConfigParamWrite::Bp3Temp1Bias,
+            ConfigParamWriteI16::Bp3Temp2Bias => ConfigParamWrite::Bp3Temp2Bias,
+            ConfigParamWriteI16::Bp3Temp3Bias => ConfigParamWrite::Bp3Temp3Bias,
+            ConfigParamWriteI16::Bp2Temp1Premul => ConfigParamWrite::Bp2Temp1Premul,
+            ConfigParamWriteI16::Bp2Temp2Premul => ConfigParamWrite::Bp2Temp2Premul,
+            ConfigParamWriteI16::Bp2Temp3Premul => ConfigParamWrite::Bp2Temp3Premul,
+            ConfigParamWriteI16::Bp3Temp1Premul => ConfigParamWrite::Bp3Temp1Premul,
+            ConfigParamWriteI16::Bp3Temp2Premul => ConfigParamWrite::Bp3Temp2Premul,
+            ConfigParamWriteI16::Bp3Temp3Premul => ConfigParamWrite::Bp3Temp3Premul,
+            ConfigParamWriteI16::Bp2Temp1PosDiv => ConfigParamWrite::Bp2Temp1PosDiv,
+            ConfigParamWriteI16::Bp2Temp2PosDiv => ConfigParamWrite::Bp2Temp2PosDiv,
+            ConfigParamWriteI16::Bp2Temp3PosDiv => ConfigParamWrite::Bp2Temp3PosDiv,
+            ConfigParamWriteI16::Bp3Temp1PosDiv => ConfigParamWrite::Bp3Temp1PosDiv,
+            ConfigParamWriteI16::Bp3Temp2PosDiv => ConfigParamWrite::Bp3Temp2PosDiv,
+            ConfigParamWriteI16::Bp3Temp3PosDiv => ConfigParamWrite::Bp3Temp3PosDiv,
         }
     }
 }
@@ -500,6 +597,10 @@ impl From<ConfigParamWriteI8> for ConfigParamWrite {
         match ci8 {
             ConfigParamWriteI8::AutoHeatEnaBP1 => ConfigParamWrite::AutoHeatEnaBP1,
             ConfigParamWriteI8::AutoBalEnaBP1 => ConfigParamWrite::AutoBalEnaBP1,
+            ConfigParamWriteI8::AutoHeatEnaBP2 => ConfigParamWrite::AutoHeatEnaBP2,
+            ConfigParamWriteI8::AutoHeatEnaBP3 => ConfigParamWrite::AutoHeatEnaBP3,
+            ConfigParamWriteI8::AutoBalEnaBP2 => ConfigParamWrite::AutoBalEnaBP2,
+            ConfigParamWriteI8::AutoBalEnaBP3 => ConfigParamWrite::AutoBalEnaBP3,
             ConfigParamWriteI8::Vd1AlwaysEna => ConfigParamWrite::Vd1AlwaysEna,
             ConfigParamWriteI8::Vd2AlwaysEna => ConfigParamWrite::Vd2AlwaysEna,
             ConfigParamWriteI8::Vd3AlwaysEna => ConfigParamWrite::Vd3AlwaysEna,
@@ -516,19 +617,10 @@ impl From<ConfigParamWriteI8> for ConfigParamWrite {
     }
 }
 
-#[derive(
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-    Hash,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum ConfigParamRead {
     ChForceEnaUseBf,
     ChStartUpEnaUseBf,
@@ -568,8 +660,117 @@ pub enum ConfigParamRead {
     BootResumeShort,
     ConfParamChanged,
 }
+// Parameter ID lookup table for the read-only configuration parameters, sorted by ID.
+const CONFIG_PARAM_READ_IDS: [(u16, ConfigParamRead); 36] = [
+    (0x1800, ConfParamChanged),
+    (0x2800, Stid),
+    (0x2801, Ivid),
+    (0x2802, BidUsed),
+    (0x2803, BootResumeShort),
+    (0x3800, AdcMcuTempV25T30),
+    (0x3801, AdcMcuTempV25T85),
+    (0x4800, TtcI2cSlaveAddr),
+    (0x4801, ConfNvmSaveCntr),
+    (0x4802, ConfNvmSaveChks),
+    (0x4803, RstCause),
+    (0x4804, RstCntrPwron),
+    (0x4805, RstCntrWdg),
+    (0x4806, RstCntrCmd),
+    (0x4807, RstCntrMcu),
+    (0x4808, RstCntrEmlopo),
+    (0x4809, RstCntrMcuRaw),
+    (0x480A, EmlopoVoltLoThr),
+    (0x480B, EmlopoVoltHiThr),
+    (0x480C, EmlopoPeriod),
+    (0x480D, SafetyVoltLoThrUsed),
+    (0x480E, SafetyVoltHiThrUsed),
+    (0x480F, SafetyLinger),
+    (0x4810, TtcWdgTimeoutUsed),
+    (0x4811, TtcPevCmdElapsed),
+    (0x6809, ChForceEnaUseBf),
+    (0x680A, ChStartUpEnaUseBf),
+    (0x680B, ChLatchoffEnaUseBf),
+    (0x680C, Vd1AllocChBf),
+    (0x680D, Vd2AllocChBf),
+    (0x680E, Vd3AllocChBf),
+    (0x680F, Vd4AllocChBf),
+    (0x6810, Vd5AllocChBf),
+    (0x6811, Vd6AllocChBf),
+    (0x6813, SwciChCmdEnaBf),
+    (0x6814, SwciChCmdDisaBf),
+];
+
+// Compile time checks of the lookup tables. Strictly ascending IDs are needed by the binary
+// search in from_id() and rule out two parameters sharing an ID, the channel indices of the
+// delay parameters have to match their ID so get_id() and from_id() round-trip.
+const fn ids_ascending<T>(table: &[(u16, T)]) -> bool {
+    let mut i = 1;
+    while i < table.len() {
+        if table[i - 1].0 >= table[i].0 {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+const fn delay_ids_match(table: &[(u16, ConfigParamWrite)]) -> bool {
+    let mut i = 0;
+    let mut delays = 0;
+    while i < table.len() {
+        match table[i].1 {
+            ChStartupDelay(n) | ChLatchoffDelay(n) if n > 31 => return false,
+            ChStartupDelay(n) if table[i].0 != 0x4002 + n as u16 => return false,
+            ChLatchoffDelay(n) if table[i].0 != 0x4022 + n as u16 => return false,
+            ChStartupDelay(_) | ChLatchoffDelay(_) => delays += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    delays == 64
+}
+const _: () = assert!(ids_ascending(&CONFIG_PARAM_WRITE_IDS));
+const _: () = assert!(ids_ascending(&CONFIG_PARAM_READ_IDS));
+const _: () = assert!(delay_ids_match(&CONFIG_PARAM_WRITE_IDS));
+
+// get_id() has to give the ID of the lookup tables searched by from_id(), the
+// exhaustive match in get_id() makes sure every variant has one
+const fn write_ids_match(table: &[(u16, ConfigParamWrite)]) -> bool {
+    let mut i = 0;
+    while i < table.len() {
+        if table[i].1.get_id() != table[i].0 {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+const fn read_ids_match(table: &[(u16, ConfigParamRead)]) -> bool {
+    let mut i = 0;
+    while i < table.len() {
+        if table[i].1.get_id() != table[i].0 {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+const _: () = assert!(write_ids_match(&CONFIG_PARAM_WRITE_IDS));
+const _: () = assert!(read_ids_match(&CONFIG_PARAM_READ_IDS));
+
+// Value width in bytes of a parameter ID, given by its type nibble (bits 12 - 15):
+// 1/2 = i8/u8, 3/4 = i16/u16, 5/6/7 = i32/u32/f32, 8/9/A = i64/u64/f64
+pub fn param_width(id: u16) -> Option<usize> {
+    match id >> 12 {
+        0x1 | 0x2 => Some(1),
+        0x3 | 0x4 => Some(2),
+        0x5..=0x7 => Some(4),
+        0x8..=0xA => Some(8),
+        _ => None,
+    }
+}
+
 impl ConfigParamRead {
-    pub fn get_id(&self) -> u16 {
+    pub const fn get_id(&self) -> u16 {
         match self {
             ChForceEnaUseBf => 0x6809,
             ChStartUpEnaUseBf => 0x680A,
@@ -609,6 +810,12 @@ impl ConfigParamRead {
             ConfParamChanged => 0x1800,
         }
     }
+    pub fn from_id(id: u16) -> Option<Self> {
+        match CONFIG_PARAM_READ_IDS.binary_search_by_key(&id, |(i, _)| *i) {
+            Ok(idx) => Some(CONFIG_PARAM_READ_IDS[idx].1.clone()),
+            Err(_) => None,
+        }
+    }
     pub fn get_len(&self) -> usize {
         match self.get_id() {
             0x6800..=0x6FFF => 4,
@@ -621,6 +828,246 @@ impl ConfigParamRead {
     }
 }
 
+// check_stat() of a config parameter response, which also has to be long
+// enough (rx_len) to carry the value read from the parameter ID onwards
+fn check_config_response(x: &[u8], rx_len: usize) -> EpsResult<()> {
+    check_stat(x)?;
+    check_len(x, rx_len)
+}
+
+// Temperature thresholds are in 0.01 degC per LSB
+const TEMP_RESOLUTION_C: f32 = 0.01;
+
+fn celsius_to_raw(c: f32) -> EpsResult<i16> {
+    let raw = (c / TEMP_RESOLUTION_C).round();
+    if !raw.is_finite() || raw < i16::MIN as f32 || raw > i16::MAX as f32 {
+        return Err(EpsError::InvalidInput);
+    }
+    Ok(raw as i16)
+}
+
+fn raw_to_celsius(o: Output) -> EpsResult<f32> {
+    match o {
+        Output::I16(x) => Ok(x as f32 * TEMP_RESOLUTION_C),
+        _ => Err(EpsError::InvalidInput),
+    }
+}
+
+// Heater threshold parameters of battery pack 1 - 3
+fn heater_params(pack: u8) -> EpsResult<(ConfigParamWriteI16, ConfigParamWriteI16)> {
+    match pack {
+        1 => Ok((
+            ConfigParamWriteI16::LoThrBp1Heater,
+            ConfigParamWriteI16::HiThrBp1Heater,
+        )),
+        2 => Ok((
+            ConfigParamWriteI16::LoThrBp2Heater,
+            ConfigParamWriteI16::HiThrBp2Heater,
+        )),
+        3 => Ok((
+            ConfigParamWriteI16::LoThrBp3Heater,
+            ConfigParamWriteI16::HiThrBp3Heater,
+        )),
+        _ => Err(EpsError::InvalidInput),
+    }
+}
+
+// Cell unbalance threshold parameters of battery pack 1 - 3
+fn unbalance_params(pack: u8) -> EpsResult<(ConfigParamWriteI16, ConfigParamWriteI16)> {
+    match pack {
+        1 => Ok((
+            ConfigParamWriteI16::LoThrBp1Unbal,
+            ConfigParamWriteI16::HiThrBp1Unbal,
+        )),
+        2 => Ok((
+            ConfigParamWriteI16::LoThrBp2Unbal,
+            ConfigParamWriteI16::HiThrBp2Unbal,
+        )),
+        3 => Ok((
+            ConfigParamWriteI16::LoThrBp3Unbal,
+            ConfigParamWriteI16::HiThrBp3Unbal,
+        )),
+        _ => Err(EpsError::InvalidInput),
+    }
+}
+
+// Cell imbalance of a battery pack compared to its unbalance thresholds
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UnbalanceLevel {
+    // Below the low threshold
+    Balanced,
+    // Between the thresholds
+    Marginal,
+    // Above the high threshold
+    Unbalanced,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnbalanceCheck {
+    // Highest minus lowest cell voltage in mV
+    pub imbalance_mv: i16,
+    pub lo_thr_mv: i16,
+    pub hi_thr_mv: i16,
+    pub level: UnbalanceLevel,
+}
+
+impl Eps {
+    // Low and high cell unbalance thresholds of a battery pack in mV
+    pub fn unbalance_thresholds(&self, pack: u8) -> EpsResult<(i16, i16)> {
+        let (lo, hi) = unbalance_params(pack)?;
+        match (
+            self.get_config_para_write(lo.into())?,
+            self.get_config_para_write(hi.into())?,
+        ) {
+            (Output::I16(lo_mv), Output::I16(hi_mv)) => Ok((lo_mv, hi_mv)),
+            _ => Err(EpsError::InvalidInput),
+        }
+    }
+
+    // Set the cell unbalance thresholds of a battery pack in mV, 0 <= lo_mv < hi_mv
+    pub fn set_unbalance_thresholds(&self, pack: u8, lo_mv: i16, hi_mv: i16) -> EpsResult<()> {
+        let (lo, hi) = unbalance_params(pack)?;
+        if lo_mv < 0 || lo_mv >= hi_mv {
+            return Err(EpsError::InvalidInput);
+        }
+        let (_, current_hi) = self.unbalance_thresholds(pack)?;
+        if lo_mv >= current_hi {
+            self.set_config_para_i16(hi, hi_mv)?;
+            self.set_config_para_i16(lo, lo_mv)?;
+        } else {
+            self.set_config_para_i16(lo, lo_mv)?;
+            self.set_config_para_i16(hi, hi_mv)?;
+        }
+        Ok(())
+    }
+
+    // Compare the current cell imbalance from the PBU engineering HK with the configured thresholds
+    pub fn check_unbalance(&self, pack: u8) -> EpsResult<UnbalanceCheck> {
+        let (lo_thr_mv, hi_thr_mv) = self.unbalance_thresholds(pack)?;
+        let bp = match self.pbu_hk(PBUHkSel::PBUEngHK)?.battery_pack(pack) {
+            Some(x) => x.clone(),
+            // Pack not present or not covered by Eps::battery_packs()
+            None => return Err(EpsError::InvalidInput),
+        };
+        let imbalance_mv = bp.cell_imbalance();
+        let level = if imbalance_mv > hi_thr_mv {
+            UnbalanceLevel::Unbalanced
+        } else if imbalance_mv >= lo_thr_mv {
+            UnbalanceLevel::Marginal
+        } else {
+            UnbalanceLevel::Balanced
+        };
+        Ok(UnbalanceCheck {
+            imbalance_mv,
+            lo_thr_mv,
+            hi_thr_mv,
+            level,
+        })
+    }
+
+    // BOOT_RESUME_SHORT (0x2803), true if the last boot was a short resume, where the
+    // EPS kept its output state across the reset instead of going through the startup
+    // sequence (startup delays and CH_STARTUP_ENA_BF). The parameter is read-only,
+    // it is set by the EPS itself and can't be written.
+    pub fn boot_resume_short(&self) -> EpsResult<bool> {
+        match self.get_config_para_read(ConfigParamRead::BootResumeShort)? {
+            Output::U8(x) => Ok(x != 0),
+            _ => Err(EpsError::InvalidInput),
+        }
+    }
+
+    // Heater on (lo) and off (hi) thresholds of a battery pack in degC
+    pub fn heater_thresholds(&self, pack: u8) -> EpsResult<(f32, f32)> {
+        let (lo, hi) = heater_params(pack)?;
+        let lo_c = raw_to_celsius(self.get_config_para_write(lo.into())?)?;
+        let hi_c = raw_to_celsius(self.get_config_para_write(hi.into())?)?;
+        Ok((lo_c, hi_c))
+    }
+
+    // Set the heater thresholds of a battery pack in degC, lo_c has to be below hi_c.
+    // The thresholds are written in the order that keeps lo below hi on the EPS.
+    pub fn set_heater_thresholds(&self, pack: u8, lo_c: f32, hi_c: f32) -> EpsResult<()> {
+        let (lo, hi) = heater_params(pack)?;
+        let lo_raw = celsius_to_raw(lo_c)?;
+        let hi_raw = celsius_to_raw(hi_c)?;
+        if lo_raw >= hi_raw {
+            return Err(EpsError::InvalidInput);
+        }
+        let current_hi = match self.get_config_para_write(hi.clone().into())? {
+            Output::I16(x) => x,
+            _ => return Err(EpsError::InvalidInput),
+        };
+        if lo_raw >= current_hi {
+            self.set_config_para_i16(hi, hi_raw)?;
+            self.set_config_para_i16(lo, lo_raw)?;
+        } else {
+            self.set_config_para_i16(lo, lo_raw)?;
+            self.set_config_para_i16(hi, hi_raw)?;
+        }
+        Ok(())
+    }
+
+    // Write a config parameter with the width given by the Output variant, returns the new value
+    pub(crate) fn set_config_para_output(
+        &self,
+        param: &ConfigParamWrite,
+        input: &Output,
+    ) -> EpsResult<Output> {
+        self.check_lockout(LockoutScope::Config)?;
+        param.info().check(input.as_i64())?;
+
+        let cmd: u8 = self.stid();
+
+        let id = param.try_id()?.to_le_bytes();
+        let value = match input {
+            Output::U32(v) => v.to_le_bytes().to_vec(),
+            Output::U16(v) => v.to_le_bytes().to_vec(),
+            Output::I16(v) => v.to_le_bytes().to_vec(),
+            Output::U8(v) => v.to_le_bytes().to_vec(),
+            Output::I8(v) => v.to_le_bytes().to_vec(),
+        };
+        let data: Vec<u8> = [
+            &self.header(SET_CONFIG_PARA_HEADER)[..],
+            &id[..],
+            &value[..],
+        ]
+        .concat();
+
+        let command = Command { cmd, data };
+
+        let delay = self.pacing().response_delay;
+
+        #[cfg(feature = "debug")]
+        eps_log! {"System Config Cmd{:?}",command};
+        let rx_len = config_response_len(value.len());
+        let result = match self.transfer(command, rx_len, delay) {
+            Ok(x) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"System Config Response {:?}",x};
+                match check_config_response(&x, rx_len) {
+                    Ok(()) => Ok(match input {
+                        Output::U32(_) => {
+                            Output::U32(u32::from_le_bytes([x[8], x[9], x[10], x[11]]))
+                        }
+                        Output::U16(_) => Output::U16(u16::from_le_bytes([x[8], x[9]])),
+                        Output::I16(_) => Output::I16(i16::from_le_bytes([x[8], x[9]])),
+                        Output::U8(_) => Output::U8(x[8]),
+                        Output::I8(_) => Output::I8(x[8] as i8),
+                    }),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(transfer_error(e)),
+        };
+        self.verify_config(param.clone(), &result, input.clone())?;
+        result
+    }
+}
+
 pub trait EpsConfig {
     fn get_config_para_write(&self, param: ConfigParamWrite) -> EpsResult<Output>;
     fn get_config_para_read(&self, param: ConfigParamRead) -> EpsResult<Output>;
@@ -629,6 +1076,9 @@ pub trait EpsConfig {
     fn set_config_para_i16(&self, param: ConfigParamWriteI16, input: i16) -> EpsResult<Output>;
     fn set_config_para_u8(&self, param: ConfigParamWriteU8, input: u8) -> EpsResult<Output>;
     fn set_config_para_i8(&self, param: ConfigParamWriteI8, input: i8) -> EpsResult<Output>;
+    fn set_config_para(&self, param: ConfigParamWrite, value: Output) -> EpsResult<Output>;
+    fn set_config_para_verified(&self, param: ConfigParamWrite, value: Output)
+        -> EpsResult<Output>;
     fn reset_param(&self, param: ConfigParamWrite) -> EpsResult<Output>;
     fn reset_all_conf(&self) -> EpsResult<()>;
     fn load_config(&self) -> EpsResult<()>;
@@ -636,92 +1086,95 @@ pub trait EpsConfig {
     fn save_config(&self) -> EpsResult<()>;
     fn calculate_checksum(&self) -> EpsResult<u16>;
     fn get_config_data(&self) -> EpsResult<Vec<u8>>;
+    fn get_all_config(&self) -> EpsResult<ConfigSnapshot>;
+    fn get_config_param_by_id(&self, id: u16) -> EpsResult<Vec<u8>>;
+    fn set_config_param_by_id(&self, id: u16, bytes: &[u8]) -> EpsResult<Vec<u8>>;
 }
 impl EpsConfig for Eps {
     fn get_config_para_write(&self, param: ConfigParamWrite) -> EpsResult<Output> {
-        let cmd: u8 = PIU_STID;
+        let cmd: u8 = self.stid();
 
         let id = param.get_id().to_le_bytes();
-        let data: Vec<u8> = [ALL_IVID, GET_CONFIG_PARA, OVERRIDE_BID, id[0], id[1]].to_vec();
+        let data: Vec<u8> = [&self.header(GET_CONFIG_PARA_HEADER)[..], &id[..]].concat();
 
         let command = Command { cmd, data };
 
-        let delay = Duration::from_millis(50);
+        let delay = self.pacing().response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"System Config Cmd{:?}",command};
+        eps_log! {"System Config Cmd{:?}",command};
 
         match param.get_id() {
             0x6000..=0x60FF => {
-                let rx_len = 12;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(4);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => {
                                 Ok(Output::U32(u32::from_le_bytes([x[8], x[9], x[10], x[11]])))
                             }
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x4000..=0x40FF => {
-                let rx_len = 10;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(2);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::U16(u16::from_le_bytes([x[8], x[9]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x3000..=0x30FF => {
-                let rx_len = 10;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(2);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::I16(i16::from_le_bytes([x[8], x[9]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x2000..=0x20FF => {
-                let rx_len = 9;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(1);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::U8(u8::from_le_bytes([x[8]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x1000..=0x10FF => {
-                let rx_len = 9;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(1);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::I8(i8::from_le_bytes([x[8]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             _ => Err(EpsError::InvalidInput),
@@ -729,89 +1182,89 @@ impl EpsConfig for Eps {
     }
 
     fn get_config_para_read(&self, param: ConfigParamRead) -> EpsResult<Output> {
-        let cmd: u8 = PIU_STID;
+        let cmd: u8 = self.stid();
 
         let id = param.get_id().to_le_bytes();
-        let data: Vec<u8> = [ALL_IVID, GET_CONFIG_PARA, OVERRIDE_BID, id[0], id[1]].to_vec();
+        let data: Vec<u8> = [&self.header(GET_CONFIG_PARA_HEADER)[..], &id[..]].concat();
 
         let command = Command { cmd, data };
 
-        let delay = Duration::from_millis(50);
+        let delay = self.pacing().response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"System Config Cmd{:?}",command};
+        eps_log! {"System Config Cmd{:?}",command};
 
         match param.get_id() {
             0x6800..=0x68FF => {
-                let rx_len = 12;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(4);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => {
                                 Ok(Output::U32(u32::from_le_bytes([x[8], x[9], x[10], x[11]])))
                             }
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x4800..=0x48FF => {
-                let rx_len = 10;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(2);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::U16(u16::from_le_bytes([x[8], x[9]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x3800..=0x38FF => {
-                let rx_len = 10;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(2);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::I16(i16::from_le_bytes([x[8], x[9]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x2800..=0x28FF => {
-                let rx_len = 8;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(1);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::U8(u8::from_le_bytes([x[8]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x1800..=0x18FF => {
-                let rx_len = 8;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(1);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::I8(i8::from_le_bytes([x[8]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             _ => Err(EpsError::InvalidInput),
@@ -819,229 +1272,318 @@ impl EpsConfig for Eps {
     }
 
     fn set_config_para_u32(&self, param: ConfigParamWriteU32, input: u32) -> EpsResult<Output> {
-        let cmd: u8 = PIU_STID;
-
-        let id = ConfigParamWrite::from(param).get_id().to_le_bytes();
-        let mut data: Vec<u8> = [ALL_IVID, SET_CONFIG_PARA, OVERRIDE_BID, id[0], id[1]].to_vec();
-
-        data.append(&mut input.to_le_bytes().to_vec());
+        self.check_lockout(LockoutScope::Config)?;
+        ConfigParamWrite::from(param.clone())
+            .info()
+            .check(input as i64)?;
+
+        let cmd: u8 = self.stid();
+
+        let id = ConfigParamWrite::from(param.clone())
+            .try_id()?
+            .to_le_bytes();
+        let data: Vec<u8> = [
+            &self.header(SET_CONFIG_PARA_HEADER)[..],
+            &id[..],
+            &input.to_le_bytes()[..],
+        ]
+        .concat();
 
         let command = Command { cmd, data };
 
-        let delay = Duration::from_millis(50);
+        let delay = self.pacing().response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"System Config Cmd{:?}",command};
-        let rx_len = 12;
-        match self.i2c.transfer(command, rx_len, delay) {
+        eps_log! {"System Config Cmd{:?}",command};
+        let rx_len = config_response_len(4);
+        let result = match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"System Config Response {:?}",x};
-                match match_stat(x[4]) {
+                eps_log! {"System Config Response {:?}",x};
+                match check_config_response(&x, rx_len) {
                     Ok(()) => Ok(Output::U32(u32::from_le_bytes([x[8], x[9], x[10], x[11]]))),
                     Err(e) => Err(e),
                 }
             }
-            Err(_e) => Err(EpsError::TransferError),
-        }
+            Err(e) => Err(transfer_error(e)),
+        };
+        self.verify_config(param.into(), &result, Output::U32(input))?;
+        result
     }
 
     fn set_config_para_u16(&self, param: ConfigParamWriteU16, input: u16) -> EpsResult<Output> {
-        let cmd: u8 = PIU_STID;
-
-        let id = ConfigParamWrite::from(param).get_id().to_le_bytes();
-        let mut data: Vec<u8> = [ALL_IVID, SET_CONFIG_PARA, OVERRIDE_BID, id[0], id[1]].to_vec();
-
-        data.append(&mut input.to_le_bytes().to_vec());
+        self.check_lockout(LockoutScope::Config)?;
+        ConfigParamWrite::from(param.clone())
+            .info()
+            .check(input as i64)?;
+
+        let cmd: u8 = self.stid();
+
+        let id = ConfigParamWrite::from(param.clone())
+            .try_id()?
+            .to_le_bytes();
+        let data: Vec<u8> = [
+            &self.header(SET_CONFIG_PARA_HEADER)[..],
+            &id[..],
+            &input.to_le_bytes()[..],
+        ]
+        .concat();
 
         let command = Command { cmd, data };
 
-        let delay = Duration::from_millis(50);
+        let delay = self.pacing().response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"System Config Cmd{:?}",command};
-        let rx_len = 10;
-        match self.i2c.transfer(command, rx_len, delay) {
+        eps_log! {"System Config Cmd{:?}",command};
+        let rx_len = config_response_len(2);
+        let result = match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"System Config Response {:?}",x};
-                match match_stat(x[4]) {
+                eps_log! {"System Config Response {:?}",x};
+                match check_config_response(&x, rx_len) {
                     Ok(()) => Ok(Output::U16(u16::from_le_bytes([x[8], x[9]]))),
                     Err(e) => Err(e),
                 }
             }
-            Err(_e) => Err(EpsError::TransferError),
-        }
+            Err(e) => Err(transfer_error(e)),
+        };
+        self.verify_config(param.into(), &result, Output::U16(input))?;
+        result
     }
 
     fn set_config_para_i16(&self, param: ConfigParamWriteI16, input: i16) -> EpsResult<Output> {
-        let cmd: u8 = PIU_STID;
-
-        let id = ConfigParamWrite::from(param).get_id().to_le_bytes();
-        let mut data: Vec<u8> = [ALL_IVID, SET_CONFIG_PARA, OVERRIDE_BID, id[0], id[1]].to_vec();
-
-        data.append(&mut input.to_le_bytes().to_vec());
+        self.check_lockout(LockoutScope::Config)?;
+        ConfigParamWrite::from(param.clone())
+            .info()
+            .check(input as i64)?;
+
+        let cmd: u8 = self.stid();
+
+        let id = ConfigParamWrite::from(param.clone())
+            .try_id()?
+            .to_le_bytes();
+        let data: Vec<u8> = [
+            &self.header(SET_CONFIG_PARA_HEADER)[..],
+            &id[..],
+            &input.to_le_bytes()[..],
+        ]
+        .concat();
 
         let command = Command { cmd, data };
 
-        let delay = Duration::from_millis(50);
+        let delay = self.pacing().response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"System Config Cmd{:?}",command};
-        let rx_len = 10;
-        match self.i2c.transfer(command, rx_len, delay) {
+        eps_log! {"System Config Cmd{:?}",command};
+        let rx_len = config_response_len(2);
+        let result = match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"System Config Response {:?}",x};
-                match match_stat(x[4]) {
+                eps_log! {"System Config Response {:?}",x};
+                match check_config_response(&x, rx_len) {
                     Ok(()) => Ok(Output::I16(i16::from_le_bytes([x[8], x[9]]))),
                     Err(e) => Err(e),
                 }
             }
-            Err(_e) => Err(EpsError::TransferError),
-        }
+            Err(e) => Err(transfer_error(e)),
+        };
+        self.verify_config(param.into(), &result, Output::I16(input))?;
+        result
     }
 
     fn set_config_para_u8(&self, param: ConfigParamWriteU8, input: u8) -> EpsResult<Output> {
-        let cmd: u8 = PIU_STID;
-
-        let id = ConfigParamWrite::from(param).get_id().to_le_bytes();
-        let mut data: Vec<u8> = [ALL_IVID, SET_CONFIG_PARA, OVERRIDE_BID, id[0], id[1]].to_vec();
-
-        data.append(&mut input.to_le_bytes().to_vec());
+        self.check_lockout(LockoutScope::Config)?;
+        ConfigParamWrite::from(param.clone())
+            .info()
+            .check(input as i64)?;
+
+        let cmd: u8 = self.stid();
+
+        let id = ConfigParamWrite::from(param.clone())
+            .try_id()?
+            .to_le_bytes();
+        let data: Vec<u8> = [
+            &self.header(SET_CONFIG_PARA_HEADER)[..],
+            &id[..],
+            &input.to_le_bytes()[..],
+        ]
+        .concat();
 
         let command = Command { cmd, data };
 
-        let delay = Duration::from_millis(50);
+        let delay = self.pacing().response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"System Config Cmd{:?}",command};
-        let rx_len = 9;
-        match self.i2c.transfer(command, rx_len, delay) {
+        eps_log! {"System Config Cmd{:?}",command};
+        let rx_len = config_response_len(1);
+        let result = match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"System Config Response {:?}",x};
-                match match_stat(x[4]) {
+                eps_log! {"System Config Response {:?}",x};
+                match check_config_response(&x, rx_len) {
                     Ok(()) => Ok(Output::U8(u8::from_le_bytes([x[8]]))),
                     Err(e) => Err(e),
                 }
             }
-            Err(_e) => Err(EpsError::TransferError),
-        }
+            Err(e) => Err(transfer_error(e)),
+        };
+        self.verify_config(param.into(), &result, Output::U8(input))?;
+        result
     }
 
     fn set_config_para_i8(&self, param: ConfigParamWriteI8, input: i8) -> EpsResult<Output> {
-        let cmd: u8 = PIU_STID;
-
-        let id = ConfigParamWrite::from(param).get_id().to_le_bytes();
-        let mut data: Vec<u8> = [ALL_IVID, SET_CONFIG_PARA, OVERRIDE_BID, id[0], id[1]].to_vec();
-
-        data.append(&mut input.to_le_bytes().to_vec());
+        self.check_lockout(LockoutScope::Config)?;
+        ConfigParamWrite::from(param.clone())
+            .info()
+            .check(input as i64)?;
+
+        let cmd: u8 = self.stid();
+
+        let id = ConfigParamWrite::from(param.clone())
+            .try_id()?
+            .to_le_bytes();
+        let data: Vec<u8> = [
+            &self.header(SET_CONFIG_PARA_HEADER)[..],
+            &id[..],
+            &input.to_le_bytes()[..],
+        ]
+        .concat();
 
         let command = Command { cmd, data };
 
-        let delay = Duration::from_millis(50);
+        let delay = self.pacing().response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"System Config Cmd{:?}",command};
-        let rx_len = 9;
-        match self.i2c.transfer(command, rx_len, delay) {
+        eps_log! {"System Config Cmd{:?}",command};
+        let rx_len = config_response_len(1);
+        let result = match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"System Config Response {:?}",x};
-                match match_stat(x[4]) {
+                eps_log! {"System Config Response {:?}",x};
+                match check_config_response(&x, rx_len) {
                     Ok(()) => Ok(Output::I8(i8::from_le_bytes([x[8]]))),
                     Err(e) => Err(e),
                 }
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
+        };
+        self.verify_config(param.into(), &result, Output::I8(input))?;
+        result
+    }
+
+    // Write a parameter of any width, the value has to have the width of the parameter
+    fn set_config_para(&self, param: ConfigParamWrite, value: Output) -> EpsResult<Output> {
+        if value.width() != param.get_len() {
+            return Err(EpsError::TypeMismatch);
         }
+        self.set_config_para_output(&param, &value)
+    }
+
+    // Write a parameter and read it back, independent of Eps::verify()
+    fn set_config_para_verified(
+        &self,
+        param: ConfigParamWrite,
+        value: Output,
+    ) -> EpsResult<Output> {
+        self.set_config_para(param.clone(), value.clone())?;
+        let actual = self.get_config_para_write(param)?;
+        if actual != value {
+            #[cfg(feature = "debug")]
+            eps_log! {"Verification Failed: expected {:?}, read {:?}",value,actual};
+            return Err(EpsError::VerificationFailed {
+                expected: value,
+                actual,
+            });
+        }
+        Ok(actual)
     }
 
     fn reset_param(&self, param: ConfigParamWrite) -> EpsResult<Output> {
-        let cmd: u8 = PIU_STID;
+        self.check_lockout(LockoutScope::Config)?;
+
+        let cmd: u8 = self.stid();
 
         let id = param.get_id().to_le_bytes();
-        let data: Vec<u8> = [ALL_IVID, RESET_CONFIG_PARA, OVERRIDE_BID, id[0], id[1]].to_vec();
+        let data: Vec<u8> = [&self.header(RESET_CONFIG_PARA_HEADER)[..], &id[..]].concat();
 
         let command = Command { cmd, data };
 
-        let delay = Duration::from_millis(50);
+        let delay = self.pacing().nvm_delay();
 
         #[cfg(feature = "debug")]
-        println! {"System Config Cmd{:?}",command};
+        eps_log! {"System Config Cmd{:?}",command};
 
         match param.get_id() {
             0x6000..=0x60FF => {
-                let rx_len = 12;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(4);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => {
                                 Ok(Output::U32(u32::from_le_bytes([x[8], x[9], x[10], x[11]])))
                             }
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x4000..=0x40FF => {
-                let rx_len = 10;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(2);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::U16(u16::from_le_bytes([x[8], x[9]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x3000..=0x30FF => {
-                let rx_len = 10;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(2);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::I16(i16::from_le_bytes([x[8], x[9]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x2000..=0x20FF => {
-                let rx_len = 8;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(1);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::U8(u8::from_le_bytes([x[8]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             0x1000..=0x10FF => {
-                let rx_len = 8;
-                match self.i2c.transfer(command, rx_len, delay) {
+                let rx_len = config_response_len(1);
+                match self.transfer(command, rx_len, delay) {
                     Ok(x) => {
                         #[cfg(feature = "debug")]
-                        println! {"System Config Response {:?}",x};
-                        match match_stat(x[4]) {
+                        eps_log! {"System Config Response {:?}",x};
+                        match check_config_response(&x, rx_len) {
                             Ok(()) => Ok(Output::I8(i8::from_le_bytes([x[8]]))),
                             Err(e) => Err(e),
                         }
                     }
-                    Err(_e) => Err(EpsError::TransferError),
+                    Err(e) => Err(transfer_error(e)),
                 }
             }
             _ => Err(EpsError::InvalidInput),
@@ -1049,146 +1591,143 @@ impl EpsConfig for Eps {
     }
 
     fn reset_all_conf(&self) -> EpsResult<()> {
-        let cmd_code: u8 = RESET_CONFIG_ALL;
         let config_key: u8 = 0xA7;
 
-        let cmd: u8 = PIU_STID;
+        self.check_lockout(LockoutScope::Config)?;
+        self.check_armed()?;
+
+        let cmd: u8 = self.stid();
         // Config key must be 0xA7, any other value will be rejected with a parameter error
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID, config_key].to_vec();
+        let data: Vec<u8> = [&self.header(RESET_CONFIG_ALL_HEADER)[..], &[config_key]].concat();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing().nvm_delay();
 
         #[cfg(feature = "debug")]
-        println! {"Reset All Config Cmd {:?}",command};
+        eps_log! {"Reset All Config Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Reset All Config Response {:?}", x};
-                match_stat(x[4])
+                eps_log! {"Reset All Config Response {:?}", x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     fn load_config(&self) -> EpsResult<()> {
-        let cmd_code: u8 = LOAD_CONFIG;
         let config_key: u8 = 0xA7;
 
-        let cmd: u8 = PIU_STID;
+        self.check_lockout(LockoutScope::Config)?;
+
+        let cmd: u8 = self.stid();
         // Config key must be 0xA7, any other value will be rejected with a parameter error
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID, config_key].to_vec();
+        let data: Vec<u8> = [&self.header(LOAD_CONFIG_HEADER)[..], &[config_key]].concat();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing().nvm_delay();
 
         #[cfg(feature = "debug")]
-        println! {"Load Config Cmd {:?}",command};
+        eps_log! {"Load Config Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Load Config Response {:?}", x};
-                match_stat(x[4])
+                eps_log! {"Load Config Response {:?}", x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     fn save_config_force(&self) -> EpsResult<()> {
-        let cmd_code: u8 = SAVE_CONFIG;
         let config_key: u8 = 0xA7;
         let checksum = [0x00, 0x00];
 
-        let cmd: u8 = PIU_STID;
+        let cmd: u8 = self.stid();
         // Config key must be 0xA7, any other value will be rejected with a parameter error
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID, config_key, checksum[0], checksum[1]].to_vec();
+        let data: Vec<u8> = [
+            &self.header(SAVE_CONFIG_HEADER)[..],
+            &[config_key],
+            &checksum[..],
+        ]
+        .concat();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing().nvm_delay();
 
         #[cfg(feature = "debug")]
-        println! {"Save Config Cmd {:?}",command};
+        eps_log! {"Save Config Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Save Config Response {:?}", x};
-                match_stat(x[4])
+                eps_log! {"Save Config Response {:?}", x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     fn save_config(&self) -> EpsResult<()> {
-        let cmd_code: u8 = SAVE_CONFIG;
         let config_key: u8 = 0xA7;
         let checksum = match self.calculate_checksum() {
             Ok(x) => x.to_le_bytes(),
             Err(e) => return Err(e),
         };
 
-        let cmd: u8 = PIU_STID;
+        let cmd: u8 = self.stid();
         // Config key must be 0xA7, any other value will be rejected with a parameter error
-        let mut data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID, config_key].to_vec();
-        data.append(&mut checksum.to_vec());
+        let data: Vec<u8> = [
+            &self.header(SAVE_CONFIG_HEADER)[..],
+            &[config_key],
+            &checksum[..],
+        ]
+        .concat();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing().nvm_delay();
 
         #[cfg(feature = "debug")]
-        println! {"Save Config Cmd {:?}",command};
+        eps_log! {"Save Config Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Save Config Response {:?}", x};
-                match_stat(x[4])
+                eps_log! {"Save Config Response {:?}", x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     fn calculate_checksum(&self) -> EpsResult<u16> {
-        let mut crc: u16 = 0xFFFF;
-
         let config_data = match self.get_config_data() {
             Ok(x) => x,
             Err(e) => return Err(e),
         };
 
-        for byte in config_data.iter() {
-            crc ^= u16::from(*byte) << 8;
-            for _ in 0..8 {
-                if crc & 0x8000 != 0 {
-                    crc = (crc << 1) ^ 0x1021;
-                } else {
-                    crc <<= 1;
-                }
-            }
-        }
-
-        Ok(crc)
+        Ok(crc16(&config_data))
     }
 
     fn get_config_data(&self) -> EpsResult<Vec<u8>> {
         let mut result: Vec<u8> = Vec::new();
 
-        for param in ConfigParamWrite::iter_id() {
-            let param_data =
-                match self.get_config_para_write(ConfigParamWrite::from_id(param).unwrap()) {
-                    Ok(x) => x,
-                    Err(e) => return Err(e),
-                };
+        // Parameters of battery packs that aren't fitted are rejected by the unit
+        for param in ConfigParamWrite::iter_for_packs(self.battery_packs()) {
+            let param_data = match self.get_config_para_write(param.clone()) {
+                Ok(x) => x,
+                Err(e) => return Err(e),
+            };
             match param_data {
                 Output::U32(x) => result.append(&mut x.to_le_bytes().to_vec()),
                 Output::U16(x) => result.append(&mut x.to_le_bytes().to_vec()),
@@ -1200,4 +1739,147 @@ impl EpsConfig for Eps {
 
         Ok(result)
     }
+
+    // Read all writable and read-only parameters
+    fn get_all_config(&self) -> EpsResult<ConfigSnapshot> {
+        let mut snapshot = ConfigSnapshot::new();
+        for param in ConfigParamWrite::iter_for_packs(self.battery_packs()) {
+            let value = self.get_config_para_write(param.clone())?;
+            snapshot.insert_write(param, value);
+        }
+        for (_, param) in CONFIG_PARAM_READ_IDS.iter() {
+            let value = self.get_config_para_read(param.clone())?;
+            snapshot.insert_read(param, value);
+        }
+        Ok(snapshot)
+    }
+    // Raw access to parameters not modelled by ConfigParamWrite/ConfigParamRead, e.g. ones
+    // added in newer firmware. The value is the little endian bytes of the parameter,
+    // its width is given by the ID (see param_width).
+    fn get_config_param_by_id(&self, id: u16) -> EpsResult<Vec<u8>> {
+        let width = match param_width(id) {
+            Some(w) => w,
+            None => return Err(EpsError::InvalidInput),
+        };
+
+        let cmd: u8 = self.stid();
+        let data: Vec<u8> = [
+            &self.header(GET_CONFIG_PARA_HEADER)[..],
+            &id.to_le_bytes()[..],
+        ]
+        .concat();
+        let command = Command { cmd, data };
+
+        let rx_len = config_response_len(width);
+        let delay = self.pacing().response_delay;
+
+        #[cfg(feature = "debug")]
+        eps_log! {"System Config Cmd{:?}",command};
+
+        match self.transfer(command, rx_len, delay) {
+            Ok(x) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"System Config Response {:?}",x};
+                match check_config_response(&x, rx_len) {
+                    Ok(()) => Ok(x[8..rx_len].to_vec()),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(transfer_error(e)),
+        }
+    }
+
+    // Returns the value read back by the EPS, bytes has to have the width of the ID
+    fn set_config_param_by_id(&self, id: u16, bytes: &[u8]) -> EpsResult<Vec<u8>> {
+        self.check_lockout(LockoutScope::Config)?;
+        match param_width(id) {
+            Some(w) if w == bytes.len() => (),
+            Some(_) => return Err(EpsError::TypeMismatch),
+            None => return Err(EpsError::InvalidInput),
+        }
+
+        let cmd: u8 = self.stid();
+        let data: Vec<u8> = [
+            &self.header(SET_CONFIG_PARA_HEADER)[..],
+            &id.to_le_bytes()[..],
+            bytes,
+        ]
+        .concat();
+        let command = Command { cmd, data };
+
+        let rx_len = config_response_len(bytes.len());
+        let delay = self.pacing().response_delay;
+
+        #[cfg(feature = "debug")]
+        eps_log! {"System Config Cmd{:?}",command};
+
+        match self.transfer(command, rx_len, delay) {
+            Ok(x) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"System Config Response {:?}",x};
+                match check_config_response(&x, rx_len) {
+                    Ok(()) => Ok(x[8..rx_len].to_vec()),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(transfer_error(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn write_ids_round_trip() {
+        for (id, param) in CONFIG_PARAM_WRITE_IDS.iter() {
+            assert_eq!(param.get_id(), *id, "{:?}", param);
+            assert_eq!(
+                ConfigParamWrite::from_id(param.get_id()).as_ref(),
+                Some(param)
+            );
+        }
+        assert_eq!(ChStartupDelay(32).get_id(), 0x0000);
+        assert_eq!(ChLatchoffDelay(32).try_id(), Err(EpsError::InvalidInput));
+    }
+
+    #[test]
+    fn read_ids_round_trip() {
+        for (id, param) in CONFIG_PARAM_READ_IDS.iter() {
+            assert_eq!(param.get_id(), *id, "{:?}", param);
+            assert_eq!(
+                ConfigParamRead::from_id(param.get_id()).as_ref(),
+                Some(param)
+            );
+        }
+    }
+
+    #[test]
+    fn ids_unique() {
+        let mut ids = BTreeSet::new();
+        for (id, _) in CONFIG_PARAM_WRITE_IDS.iter() {
+            assert!(ids.insert(*id), "duplicate ID {:#06x}", id);
+        }
+        for (id, _) in CONFIG_PARAM_READ_IDS.iter() {
+            assert!(ids.insert(*id), "duplicate ID {:#06x}", id);
+        }
+    }
+
+    #[test]
+    fn battery_pack_params() {
+        for (_, param) in CONFIG_PARAM_WRITE_IDS.iter() {
+            let name = format!("{:?}", param).to_lowercase();
+            let pack = match (name.contains("bp2"), name.contains("bp3")) {
+                (true, _) => 2,
+                (_, true) => 3,
+                _ => 1,
+            };
+            assert_eq!(param.battery_pack(), pack, "{:?}", param);
+        }
+        assert_eq!(ConfigParamWrite::iter_for_packs(1).count(), 105);
+        assert_eq!(ConfigParamWrite::iter_for_packs(2).count(), 120);
+        assert_eq!(ConfigParamWrite::iter_for_packs(3).count(), 135);
+    }
 }
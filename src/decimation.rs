@@ -0,0 +1,100 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decimation of housekeeping samples
+// Sits between the housekeeping sampling and the storage/downlink sinks so
+// sampling can run at a high local rate while only a fraction is kept.
+
+#[cfg(feature = "serde")]
+use serde::*;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Decimation {
+    // Keep every sample
+    None,
+    // Keep 1 in N samples, starting with the first
+    OneInN(u32),
+    // Keep a sample when it differs from the last kept one by more than threshold,
+    // or when max_skip samples have been dropped in a row
+    OnChange { threshold: f64, max_skip: u32 },
+}
+
+// Distance between two samples, used by Decimation::OnChange
+pub type ChangeFn<T> = fn(&T, &T) -> f64;
+
+pub struct Decimator<T> {
+    mode: Decimation,
+    change: ChangeFn<T>,
+    last: Option<T>,
+    skipped: u32,
+}
+impl<T: Clone> Decimator<T> {
+    pub fn new(mode: Decimation, change: ChangeFn<T>) -> Self {
+        Decimator {
+            mode,
+            change,
+            last: None,
+            skipped: 0,
+        }
+    }
+
+    // Decimator that only supports OneInN and None, change is never evaluated
+    pub fn every(n: u32) -> Self {
+        Decimator::new(Decimation::OneInN(n), |_, _| 0.0)
+    }
+
+    pub fn mode(&self) -> &Decimation {
+        &self.mode
+    }
+
+    // Returns true if the sample should be passed on to the sinks
+    pub fn keep(&mut self, sample: &T) -> bool {
+        let keep = match (&self.mode, &self.last) {
+            (_, None) => true,
+            (Decimation::None, _) => true,
+            (Decimation::OneInN(n), _) => self.skipped + 1 >= *n,
+            (
+                Decimation::OnChange {
+                    threshold,
+                    max_skip,
+                },
+                Some(last),
+            ) => self.skipped >= *max_skip || (self.change)(last, sample) > *threshold,
+        };
+        if keep {
+            self.last = Some(sample.clone());
+            self.skipped = 0;
+        } else {
+            self.skipped += 1;
+        }
+        keep
+    }
+
+    // Pass the sample through if it is kept
+    pub fn filter(&mut self, sample: T) -> Option<T> {
+        if self.keep(&sample) {
+            Some(sample)
+        } else {
+            None
+        }
+    }
+
+    // Start over, the next sample is always kept
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.skipped = 0;
+    }
+}
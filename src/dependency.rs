@@ -0,0 +1,231 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel dependencies
+// A channel can require other channels to be on before it is switched on,
+// e.g. a payload 12 V rail requiring the 3.3 V controller rail. With
+// dependencies declared, the switching commands read the channel states first
+// and refuse with EpsError::DependencyViolation when a channel would be switched
+// on before its prerequisites, or a prerequisite switched off under a channel
+// still on. Prerequisites and dependents switched in the same command count as
+// a violation, they have to be switched in order (see Eps::switch_on_ordered).
+
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChannelDependencies {
+    // Bitmask of the channels required by each channel
+    requires: BTreeMap<ChannelId, u32>,
+}
+
+impl ChannelDependencies {
+    pub fn new() -> Self {
+        ChannelDependencies::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requires.values().all(|m| *m == 0)
+    }
+
+    // Declare that channel requires prerequisite to be on.
+    // Refused if it would make a channel (indirectly) depend on itself.
+    pub fn require(&mut self, channel: ChannelId, prerequisite: ChannelId) -> EpsResult<()> {
+        if channel > MAX_CHANNEL || prerequisite > MAX_CHANNEL {
+            return Err(EpsError::InvalidInput);
+        }
+        if channel == prerequisite || self.all_prerequisites(prerequisite) & (1 << channel) != 0 {
+            return Err(EpsError::InvalidInput);
+        }
+        *self.requires.entry(channel).or_insert(0) |= 1 << prerequisite;
+        Ok(())
+    }
+
+    // Returns false if there was no such dependency
+    pub fn remove(&mut self, channel: ChannelId, prerequisite: ChannelId) -> bool {
+        match self.requires.get_mut(&channel) {
+            Some(m) if prerequisite <= MAX_CHANNEL && *m & (1 << prerequisite) != 0 => {
+                *m &= !(1 << prerequisite);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Channels directly required by channel, as bitmask
+    pub fn prerequisites(&self, channel: ChannelId) -> u32 {
+        self.requires.get(&channel).copied().unwrap_or(0)
+    }
+
+    // Channels directly requiring channel, as bitmask
+    pub fn dependents(&self, channel: ChannelId) -> u32 {
+        self.requires
+            .iter()
+            .filter(|(_, m)| channel <= MAX_CHANNEL && *m & (1 << channel) != 0)
+            .fold(0, |acc, (c, _)| acc | 1 << c)
+    }
+
+    // Channels required directly or indirectly by channel
+    fn all_prerequisites(&self, channel: ChannelId) -> u32 {
+        let mut all = 0u32;
+        let mut todo = self.prerequisites(channel);
+        while todo != 0 {
+            let c = todo.trailing_zeros() as ChannelId;
+            todo &= todo - 1;
+            if all & (1 << c) == 0 {
+                all |= 1 << c;
+                todo |= self.prerequisites(c);
+            }
+        }
+        all
+    }
+
+    // Check switching on/off (bitmasks) with the channels in current on.
+    // Returns the first (channel, prerequisite) pair that would be violated.
+    pub fn check(&self, on: u32, off: u32, current: u32) -> Result<(), (ChannelId, ChannelId)> {
+        for (c, m) in &self.requires {
+            if on & (1 << c) != 0 && m & !current != 0 {
+                return Err((*c, (m & !current).trailing_zeros() as ChannelId));
+            }
+            if (current | on) & (1 << c) != 0 && m & off != 0 {
+                return Err((*c, (m & off).trailing_zeros() as ChannelId));
+            }
+        }
+        Ok(())
+    }
+
+    // The channels of mask in an order that switches prerequisites first
+    pub fn power_up_order(&self, mask: u32) -> Vec<ChannelId> {
+        let mut order = Vec::new();
+        let mut done = 0u32;
+        let mut todo = mask;
+        while todo != 0 {
+            let c = todo.trailing_zeros() as ChannelId;
+            let mut chain = self.all_prerequisites(c) & mask & !done;
+            while chain != 0 {
+                // Prerequisites without prerequisites of their own in the chain go first
+                let next = (0..=MAX_CHANNEL)
+                    .find(|p| chain & (1 << p) != 0 && self.all_prerequisites(*p) & chain == 0)
+                    .unwrap_or(chain.trailing_zeros() as ChannelId);
+                order.push(next);
+                done |= 1 << next;
+                chain &= !(1 << next);
+            }
+            order.push(c);
+            done |= 1 << c;
+            todo &= !done;
+        }
+        order
+    }
+}
+
+impl Eps {
+    pub fn dependencies(&self) -> &ChannelDependencies {
+        &self.dependencies
+    }
+
+    pub fn set_dependencies(&mut self, dependencies: ChannelDependencies) {
+        self.dependencies = dependencies;
+    }
+
+    // Contingency override, switch regardless of the declared dependencies
+    pub fn set_dependency_override(&mut self, dependency_override: bool) {
+        self.dependency_override = dependency_override;
+    }
+
+    pub fn dependency_override(&self) -> bool {
+        self.dependency_override
+    }
+
+    // Check switching on/off (bitmasks) against the dependencies, current as
+    // read by check_switching()
+    pub(crate) fn check_dependencies(&self, on: u32, off: u32, current: u32) -> EpsResult<()> {
+        match self.dependencies.check(on, off, current) {
+            Ok(()) => Ok(()),
+            Err((_channel, _prerequisite)) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"Channel {} depends on channel {}",_channel,_prerequisite};
+                Err(EpsError::DependencyViolation)
+            }
+        }
+    }
+
+    // Switch the channels of mask on one by one, prerequisites first
+    pub fn switch_on_ordered(&self, mask: u32) -> EpsResult<()> {
+        for c in self.dependencies.power_up_order(mask) {
+            self.ensure_channel(Channel::try_from(c)?, BusChannel::On)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_refused() {
+        let mut deps = ChannelDependencies::new();
+        assert_eq!(deps.require(3, 3), Err(EpsError::InvalidInput));
+        deps.require(1, 0).unwrap();
+        deps.require(2, 1).unwrap();
+        assert_eq!(deps.require(0, 2), Err(EpsError::InvalidInput));
+        assert_eq!(deps.require(0, 1), Err(EpsError::InvalidInput));
+        assert_eq!(
+            deps.require(MAX_CHANNEL + 1, 0),
+            Err(EpsError::InvalidInput)
+        );
+        assert_eq!(deps.prerequisites(2), 1 << 1);
+        assert_eq!(deps.dependents(1), 1 << 2);
+        assert!(deps.remove(2, 1));
+        assert!(!deps.remove(2, 1));
+        deps.require(0, 2).unwrap();
+    }
+
+    #[test]
+    fn missing_prerequisite() {
+        let mut deps = ChannelDependencies::new();
+        deps.require(2, 1).unwrap();
+        deps.require(1, 0).unwrap();
+        // Switching on without the prerequisite on
+        assert_eq!(deps.check(1 << 2, 0, 1 << 0), Err((2, 1)));
+        assert_eq!(deps.check(1 << 2, 0, 0b011), Ok(()));
+        // Prerequisite and dependent in the same command
+        assert_eq!(deps.check(0b110, 0, 1 << 0), Err((2, 1)));
+        // Prerequisite switched off under a dependent still on
+        assert_eq!(deps.check(0, 1 << 1, 0b111), Err((2, 1)));
+        assert_eq!(deps.check(0, 0b110, 0b111), Err((2, 1)));
+        assert_eq!(deps.check(0, 1 << 2, 0b111), Ok(()));
+    }
+
+    #[test]
+    fn power_up_order() {
+        let mut deps = ChannelDependencies::new();
+        deps.require(0, 5).unwrap();
+        deps.require(5, 9).unwrap();
+        deps.require(0, 2).unwrap();
+        deps.require(7, 9).unwrap();
+        let order = deps.power_up_order(1 << 0 | 1 << 2 | 1 << 5 | 1 << 7 | 1 << 9);
+        assert_eq!(order, vec![2, 9, 5, 0, 7]);
+        // Prerequisites outside of the mask aren't added
+        assert_eq!(deps.power_up_order(1 << 0 | 1 << 5), vec![5, 0]);
+        assert_eq!(deps.power_up_order(0), Vec::<ChannelId>::new());
+    }
+}
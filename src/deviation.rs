@@ -0,0 +1,159 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of instantaneous (engineering) and averaged housekeeping
+// Large differences between the two point at transients or noisy sensors.
+// Frames are compared field by field on their serialized form, status
+// bitfields are skipped since a numeric difference means nothing there.
+
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviationMargins {
+    // Absolute margin in the unit of the field (mV, mA, mW, 0.01 degC ...)
+    pub absolute: f64,
+    // Margin relative to the averaged value, e.g. 0.1 for 10 %
+    pub relative: f64,
+    // Absolute margins for single fields, overriding absolute/relative, e.g. "vip_input.volt"
+    pub per_field: BTreeMap<String, f64>,
+    // Fields with a path segment starting with one of these are not compared
+    pub skip_prefixes: Vec<String>,
+}
+impl Default for DeviationMargins {
+    fn default() -> Self {
+        DeviationMargins {
+            absolute: 50.0,
+            relative: 0.1,
+            per_field: BTreeMap::new(),
+            skip_prefixes: vec!["stat_".to_string(), "batt_stat".to_string()],
+        }
+    }
+}
+impl DeviationMargins {
+    fn skipped(&self, field: &str) -> bool {
+        field.split('.').any(|seg| {
+            self.skip_prefixes
+                .iter()
+                .any(|p| seg.starts_with(p.as_str()))
+        })
+    }
+    fn exceeded(&self, field: &str, eng: f64, avg: f64) -> bool {
+        let diff = (eng - avg).abs();
+        match self.per_field.get(field) {
+            Some(m) => diff > *m,
+            None => diff > self.absolute && diff > self.relative * avg.abs(),
+        }
+    }
+}
+
+// A field where the engineering value is outside the margins around the average
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Deviation {
+    // Field path, nested fields joined with '.', e.g. "vip_cnt_ch03.curr"
+    pub field: String,
+    pub eng: f64,
+    pub avg: f64,
+}
+
+// Compare two housekeeping frames of the same type field by field
+pub fn compare_hk<T: Serialize>(eng: &T, avg: &T, margins: &DeviationMargins) -> Vec<Deviation> {
+    let eng = numeric_fields(eng);
+    let avg = numeric_fields(avg);
+    eng.into_iter()
+        .filter(|(field, _)| !margins.skipped(field))
+        .filter_map(|(field, e)| {
+            let a = *avg.get(&field)?;
+            if margins.exceeded(&field, e, a) {
+                Some(Deviation {
+                    field,
+                    eng: e,
+                    avg: a,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn numeric_fields<T: Serialize>(x: &T) -> BTreeMap<String, f64> {
+    let mut fields = BTreeMap::new();
+    if let Ok(v) = serde_json::to_value(x) {
+        flatten(&v, String::new(), &mut fields);
+    }
+    fields
+}
+
+fn flatten(v: &Value, path: String, fields: &mut BTreeMap<String, f64>) {
+    match v {
+        Value::Number(n) => {
+            if let Some(f) = n.as_f64() {
+                fields.insert(path, f);
+            }
+        }
+        Value::Object(map) => {
+            for (k, v) in map {
+                let p = if path.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{}.{}", path, k)
+                };
+                flatten(v, p, fields);
+            }
+        }
+        _ => (),
+    }
+}
+
+impl Eps {
+    // Fetch the engineering and the averaged HK of a unit and report the deviating fields
+    pub fn avg_deviations(
+        &self,
+        unit: StID,
+        margins: &DeviationMargins,
+    ) -> EpsResult<Vec<Deviation>> {
+        match unit {
+            StID::PduStid => Ok(compare_hk(
+                &self.pdu_hk(PDUHkSel::PDUEngHK)?,
+                &self.pdu_hk(PDUHkSel::PDUAvgHK)?,
+                margins,
+            )),
+            StID::PbuStid => Ok(compare_hk(
+                &self.pbu_hk(PBUHkSel::PBUEngHK)?,
+                &self.pbu_hk(PBUHkSel::PBUAvgHK)?,
+                margins,
+            )),
+            StID::PcuStid => Ok(compare_hk(
+                &self.pcu_hk(PCUHkSel::PCUEngHK)?,
+                &self.pcu_hk(PCUHkSel::PCUAvgHK)?,
+                margins,
+            )),
+            StID::PiuStid => Ok(compare_hk(
+                &self.piu_hk(PIUHkSel::PIUEngHK)?,
+                &self.piu_hk(PIUHkSel::PIUAvgHK)?,
+                margins,
+            )),
+            StID::OverrideStid => Err(EpsError::InvalidInput),
+        }
+    }
+}
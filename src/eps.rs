@@ -24,151 +24,718 @@
 // Dependancies
 use i2c_rs::{Command, Connection as I2c};
 
-use std::time::Duration;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::error::*;
+use crate::icd::transfer_error;
 use crate::*;
-use std::convert::From;
-
-// // StID match shortcut
-// fn match_st_id(typ: StID) -> u8 {
-//     match typ {
-//         StID::PduStid => PDU_STID,
-//         StID::PbuStid => PBU_STID,
-//         StID::PcuStid => PCU_STID,
-//         StID::PiuStid => PIU_STID,
-//         StID::OverrideStid => OVERRIDE_STID,
-//     }
-// }
+#[cfg(feature = "serde")]
+use serde::*;
+#[cfg(feature = "strum")]
+use strum_macros::{Display, EnumIter, EnumString};
+
+// StID match shortcut
+fn match_st_id(typ: &StID) -> u8 {
+    match typ {
+        StID::PduStid => PDU_STID,
+        StID::PbuStid => PBU_STID,
+        StID::PcuStid => PCU_STID,
+        StID::PiuStid => PIU_STID,
+        StID::OverrideStid => OVERRIDE_STID,
+    }
+}
+
+// Command classes, each can be given its own response delay in the PacingProfile
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
+pub enum CommandClass {
+    // No-op, cancel, watchdog, reset and correct time
+    System,
+    // Channel and group switching, mode switches
+    Switching,
+    // System status, raw and engineering housekeeping, OC fault and ABF state
+    Housekeeping,
+    // Averaged housekeeping
+    AveragedHousekeeping,
+    // Get, set and reset of a config parameter
+    Config,
+    // Commands writing persistent memory (save/load/reset all config, reset counters)
+    Nvm,
+}
+impl CommandClass {
+    pub fn of(cc: u8) -> Self {
+        match cc {
+            OUTPUT_BUS_GROUP_ON
+            | OUTPUT_BUS_GROUP_OFF
+            | OUTPUT_BUS_GROUP_STATE
+            | OUTPUT_BUS_CHANNEL_ON
+            | OUTPUT_BUS_CHANNEL_OFF
+            | SWITCH_TO_NOMINAL_MODE
+            | SWITCH_TO_SAFETY_MODE => CommandClass::Switching,
+            GET_PDU_HK_DATA_AVRG | GET_PBU_HK_DATA_AVRG | GET_PCU_HK_DATA_AVRG
+            | GET_PIU_HK_DATA_AVRG => CommandClass::AveragedHousekeeping,
+            cc if is_hk_request(cc) => CommandClass::Housekeeping,
+            GET_CONFIG_PARA | SET_CONFIG_PARA | RESET_CONFIG_PARA => CommandClass::Config,
+            RESET_CONFIG_ALL | LOAD_CONFIG | SAVE_CONFIG | RST_CAUSE_CNTR => CommandClass::Nvm,
+            _ => CommandClass::System,
+        }
+    }
+}
+
+// Pacing of the command/response transfers with the EPS
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PacingProfile {
+    // Delay between sending a command and reading its response
+    pub response_delay: Duration,
+    // Minimum gap between the end of one transfer and the start of the next
+    pub min_gap: Duration,
+    // Additional response delay for commands writing persistent memory (save/load/reset config, reset counters)
+    pub nvm_extra_delay: Duration,
+    // Response delay of a command class, replaces the delay the command would use otherwise
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub class_delays: BTreeMap<CommandClass, Duration>,
+    // Limit for a whole transaction including retries, pacing waits and fresh polling,
+    // None for no limit. A successful attempt finishing after it is still returned
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timeout: Option<Duration>,
+}
+impl PacingProfile {
+    pub fn nvm_delay(&self) -> Duration {
+        self.response_delay + self.nvm_extra_delay
+    }
+    // Response delay for command code cc, delay if its class has no delay of its own
+    pub fn delay_for(&self, cc: u8, delay: Duration) -> Duration {
+        match self.class_delays.get(&CommandClass::of(cc)) {
+            Some(d) => *d,
+            None => delay,
+        }
+    }
+    pub fn with_class_delay(mut self, class: CommandClass, delay: Duration) -> Self {
+        self.class_delays.insert(class, delay);
+        self
+    }
+}
+impl Default for PacingProfile {
+    fn default() -> Self {
+        PacingProfile {
+            response_delay: Duration::from_millis(50),
+            min_gap: Duration::from_millis(0),
+            nvm_extra_delay: Duration::from_millis(0),
+            class_delays: BTreeMap::new(),
+            timeout: None,
+        }
+    }
+}
+
+// Token-bucket limit on the outgoing command rate.
+// Each command takes a token, tokens are refilled at one per interval up to burst.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RateLimit {
+    // Time to refill one token, i.e. 1 / maximum sustained command rate
+    pub interval: Duration,
+    // Maximum number of commands that can be sent back to back
+    pub burst: u32,
+}
+impl RateLimit {
+    pub fn per_second(commands: u32, burst: u32) -> Self {
+        RateLimit {
+            interval: Duration::from_secs(1) / commands.max(1),
+            burst: burst.max(1),
+        }
+    }
+}
+
+// Transfers since construction, a failure is an I2C error or a STAT error code
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TransferStats {
+    pub transfers: u64,
+    pub failures: u64,
+    // Repeated attempts of failed transfers, see RetryPolicy
+    pub retries: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+// Commands that are never held back by the rate limit
+fn is_safety_critical(cc: u8) -> bool {
+    matches!(
+        cc,
+        SYS_RESET
+            | CANCEL_OP
+            | WATCHDOG
+            | OUTPUT_BUS_GROUP_OFF
+            | OUTPUT_BUS_CHANNEL_OFF
+            | SWITCH_TO_SAFETY_MODE
+    )
+}
+
+// Housekeeping and status requests, for the age of the last successful HK read
+fn is_hk_request(cc: u8) -> bool {
+    matches!(
+        cc,
+        GET_SYS_STATUS
+            | GET_PDU_OC_FAULT_STATE
+            | GET_PBU_ABF_PLACED_STATE
+            | GET_PDU_HK_DATA_RAW
+            | GET_PDU_HK_DATA_ENG
+            | GET_PDU_HK_DATA_AVRG
+            | GET_PBU_HK_DATA_RAW
+            | GET_PBU_HK_DATA_ENG
+            | GET_PBU_HK_DATA_AVRG
+            | GET_PCU_HK_DATA_RAW
+            | GET_PCU_HK_DATA_ENG
+            | GET_PCU_HK_DATA_AVRG
+            | GET_PIU_HK_DATA_RAW
+            | GET_PIU_HK_DATA_ENG
+            | GET_PIU_HK_DATA_AVRG
+    )
+}
+
+// Commands changing the state of the EPS, these are recorded in the command journal
+#[cfg(feature = "serde")]
+fn is_state_changing(cc: u8) -> bool {
+    matches!(
+        cc,
+        SYS_RESET
+            | CANCEL_OP
+            | CORRECT_TIME
+            | RST_CAUSE_CNTR
+            | OUTPUT_BUS_GROUP_ON
+            | OUTPUT_BUS_GROUP_OFF
+            | OUTPUT_BUS_GROUP_STATE
+            | OUTPUT_BUS_CHANNEL_ON
+            | OUTPUT_BUS_CHANNEL_OFF
+            | SWITCH_TO_NOMINAL_MODE
+            | SWITCH_TO_SAFETY_MODE
+            | SET_CONFIG_PARA
+            | RESET_CONFIG_PARA
+            | RESET_CONFIG_ALL
+            | LOAD_CONFIG
+            | SAVE_CONFIG
+    )
+}
+
+// Error of a transaction running past PacingProfile::timeout
+pub(crate) fn transaction_timeout() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::TimedOut, "transaction timeout")
+}
+
+// Sleep for d, cut short at the transaction deadline, which is a TimedOut error
+pub(crate) fn sleep_within(d: Duration, deadline: Option<Instant>) -> std::io::Result<()> {
+    match deadline {
+        Some(deadline) => {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if d >= left {
+                thread::sleep(left);
+                return Err(transaction_timeout());
+            }
+            thread::sleep(d);
+        }
+        None => thread::sleep(d),
+    }
+    Ok(())
+}
+
+// Length of the response header and reserved byte in front of the HK data
+const HK_DATA_OFFSET: usize = 6;
+
+// PDUHk, PBUHk and PCUHk convert the data after the header. ResponseTooShort is
+// reported with the lengths of the complete response frame, like the others.
+fn hk_from_frame<T>(x: &[u8]) -> EpsResult<T>
+where
+    T: for<'a> TryFrom<&'a [u8], Error = EpsError>,
+{
+    match T::try_from(x.get(HK_DATA_OFFSET..).unwrap_or_default()) {
+        Err(EpsError::ResponseTooShort(expected, _)) => Err(EpsError::ResponseTooShort(
+            expected + HK_DATA_OFFSET,
+            x.len(),
+        )),
+        result => result,
+    }
+}
 
 pub struct Eps {
-    pub i2c: I2c,
+    pub(crate) transport: Box<dyn EpsTransport + Send>,
+    // System addressed by the commands, PIU for the integrated ICEPSv2
+    target: StID,
+    // Interface version and board ID sent in the command frames
+    ivid: u8,
+    bid: u8,
+    // Battery packs connected to the PBU, sets the length of the PBU HK response
+    battery_packs: u8,
+    // Sets the length of the PIU HK response
+    daughterboard: DaughterboardPresence,
+    pub(crate) soc: SocEstimator,
+    pacing: PacingProfile,
+    delay_override: Cell<Option<Duration>>,
+    last_transfer: Cell<Option<Instant>>,
+    rate_limit: Option<RateLimit>,
+    bucket: Cell<Option<TokenBucket>>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) response_mode: ResponseMode,
+    pub(crate) transfer_mode: TransferMode,
+    pub(crate) fresh_timeout: Duration,
+    pub(crate) lockouts: Vec<Lockout>,
+    pub(crate) verify: bool,
+    pub(crate) strict: bool,
+    pub(crate) dependencies: ChannelDependencies,
+    pub(crate) dependency_override: bool,
+    pub(crate) policy: ChannelPolicy,
+    pub(crate) safety_entry: Cell<Option<SafetyEntry>>,
+    pub(crate) armed: Cell<Option<Instant>>,
+    pub(crate) stats: Cell<TransferStats>,
+    pub(crate) last_watchdog: Cell<Option<Instant>>,
+    pub(crate) last_hk: Cell<Option<Instant>>,
+    #[cfg(feature = "serde")]
+    pub(crate) journal: Option<CommandJournal>,
 }
 
 impl Eps {
     // Basic function to initialise an instance of the EpsStruct
     pub fn new(i2c_path: String, i2c_addr: u16) -> EpsResult<Self> {
+        Eps::with_transport(I2c::from_path(&i2c_path, i2c_addr))
+    }
+
+    // Builder for an Eps with non-default settings, see EpsBuilder
+    pub fn builder(i2c_path: String, i2c_addr: u16) -> EpsBuilder {
+        EpsBuilder::new(i2c_path, i2c_addr)
+    }
+
+    // Talk to the EPS through another bus or a test double
+    pub fn with_transport<T: EpsTransport + Send + 'static>(transport: T) -> EpsResult<Self> {
         Ok(Self {
-            i2c: I2c::from_path(&i2c_path, i2c_addr),
+            transport: Box::new(transport),
+            target: StID::PiuStid,
+            ivid: ALL_IVID,
+            bid: OVERRIDE_BID,
+            battery_packs: 1,
+            daughterboard: DaughterboardPresence::Present,
+            soc: SocEstimator::default(),
+            pacing: PacingProfile::default(),
+            delay_override: Cell::new(None),
+            last_transfer: Cell::new(None),
+            rate_limit: None,
+            bucket: Cell::new(None),
+            retry: None,
+            response_mode: ResponseMode::Accept,
+            transfer_mode: TransferMode::Combined,
+            fresh_timeout: Duration::from_millis(500),
+            lockouts: Vec::new(),
+            verify: false,
+            strict: false,
+            dependencies: ChannelDependencies::new(),
+            dependency_override: false,
+            policy: ChannelPolicy::new(),
+            safety_entry: Cell::new(None),
+            armed: Cell::new(None),
+            stats: Cell::new(TransferStats::default()),
+            last_watchdog: Cell::new(None),
+            last_hk: Cell::new(None),
+            #[cfg(feature = "serde")]
+            journal: None,
         })
     }
 
+    pub fn target(&self) -> &StID {
+        &self.target
+    }
+
+    // Address discrete PDU/PBU/PCU units instead of the integrated PIU.
+    // System commands and config go to the target, the unit specific HK and
+    // output bus commands to the unit providing them.
+    pub fn set_target(&mut self, target: StID) {
+        self.target = target;
+    }
+
+    // STID of the target
+    pub(crate) fn stid(&self) -> u8 {
+        match_st_id(&self.target)
+    }
+
+    // STID for commands served by unit, all of them are served by the PIU in an integrated system
+    pub(crate) fn unit_stid(&self, unit: StID) -> u8 {
+        match self.target {
+            StID::PiuStid => PIU_STID,
+            _ => match_st_id(&unit),
+        }
+    }
+
+    pub fn ivid(&self) -> u8 {
+        self.ivid
+    }
+
+    // Interface version of the firmware, ALL_IVID (0x07) by default
+    pub fn set_ivid(&mut self, ivid: u8) {
+        self.ivid = ivid;
+    }
+
+    pub fn bid(&self) -> u8 {
+        self.bid
+    }
+
+    // Board ID of the addressed unit, 0x00 (any board) by default
+    pub fn set_bid(&mut self, bid: u8) {
+        self.bid = bid;
+    }
+
+    pub fn battery_packs(&self) -> u8 {
+        self.battery_packs
+    }
+
+    // Number of battery packs (1 - 3), 1 by default
+    pub fn set_battery_packs(&mut self, packs: u8) -> EpsResult<()> {
+        if !(1..=3).contains(&packs) {
+            return Err(EpsError::InvalidInput);
+        }
+        self.battery_packs = packs;
+        Ok(())
+    }
+
+    pub fn daughterboard(&self) -> &DaughterboardPresence {
+        &self.daughterboard
+    }
+
+    // Without daughterboard piu_hk() reads the short PIU HK frame, see PIUHkShort
+    pub fn set_daughterboard(&mut self, daughterboard: DaughterboardPresence) {
+        self.daughterboard = daughterboard;
+    }
+
+    // Command frame header with the configured IVID and BID
+    pub(crate) fn header(&self, header: [u8; 3]) -> [u8; 3] {
+        [self.ivid, header[1], self.bid]
+    }
+
+    pub fn pacing(&self) -> &PacingProfile {
+        &self.pacing
+    }
+
+    // Response delays below the ICD minimum are rejected
+    pub fn set_pacing(&mut self, pacing: PacingProfile) -> EpsResult<()> {
+        let min = Duration::from_millis(HK_MIN_DELAY_MS);
+        if pacing.response_delay < min || pacing.class_delays.values().any(|d| *d < min) {
+            return Err(EpsError::InvalidInput);
+        }
+        self.pacing = pacing;
+        Ok(())
+    }
+
+    // Run f with the response delay of all its transfers set to delay,
+    // e.g. a longer settling time for a single averaged HK read.
+    // Delays below the ICD minimum are raised to the minimum.
+    pub fn with_delay<T, F>(&self, delay: Duration, f: F) -> T
+    where
+        F: FnOnce(&Self) -> T,
+    {
+        let previous = self
+            .delay_override
+            .replace(Some(delay.max(Duration::from_millis(HK_MIN_DELAY_MS))));
+        let result = f(self);
+        self.delay_override.set(previous);
+        result
+    }
+
+    pub fn rate_limit(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+
+    // None disables rate limiting (default)
+    pub fn set_rate_limit(&mut self, rate_limit: Option<RateLimit>) {
+        self.rate_limit = rate_limit;
+        self.bucket.set(None);
+    }
+
+    // Take a token for a command with command code cc, waiting for the refill if the bucket is empty
+    fn throttle(&self, cc: u8, deadline: Option<Instant>) -> std::io::Result<()> {
+        let limit = match &self.rate_limit {
+            Some(x) if !is_safety_critical(cc) => x,
+            _ => return Ok(()),
+        };
+        let burst = limit.burst as f64;
+        let interval = limit.interval.as_secs_f64();
+        let now = Instant::now();
+        let mut bucket = self.bucket.get().unwrap_or(TokenBucket {
+            tokens: burst,
+            refilled: now,
+        });
+        if interval > 0.0 {
+            let refill = now.duration_since(bucket.refilled).as_secs_f64() / interval;
+            bucket.tokens = (bucket.tokens + refill).min(burst);
+        } else {
+            bucket.tokens = burst;
+        }
+        bucket.refilled = now;
+        if bucket.tokens < 1.0 {
+            sleep_within(
+                Duration::from_secs_f64((1.0 - bucket.tokens) * interval),
+                deadline,
+            )?;
+            bucket.tokens = 1.0;
+            bucket.refilled = Instant::now();
+        }
+        bucket.tokens -= 1.0;
+        self.bucket.set(Some(bucket));
+        Ok(())
+    }
+
+    // All transfers with the EPS go through here to respect the rate limit,
+    // the minimum gap of the pacing profile and the retry policy
+    pub(crate) fn transfer(
+        &self,
+        command: Command,
+        rx_len: usize,
+        delay: Duration,
+    ) -> std::io::Result<Vec<u8>> {
+        let cc = command.data.get(1).copied().unwrap_or_default();
+        #[cfg(feature = "serde")]
+        let journal_entry = match &self.journal {
+            Some(_) if is_state_changing(cc) => Some((command.cmd, command.data.clone())),
+            _ => None,
+        };
+        let (stid, data) = (command.cmd, command.data);
+        // Per call override first, then the delay of the command class
+        let delay = match self.delay_override.get() {
+            Some(d) => d,
+            None => self.pacing.delay_for(cc, delay),
+        };
+        // One deadline for the whole transaction: pacing, retries and fresh polling
+        let deadline = self.pacing.timeout.map(|t| Instant::now() + t);
+        let mut attempt = 0;
+        let result = loop {
+            let result = self.transfer_once(stid, &data, rx_len, delay, deadline);
+            attempt += 1;
+            // A response that arrived late is still the result of an executed command
+            if result.is_err() && deadline.is_some_and(|d| Instant::now() >= d) {
+                break Err(transaction_timeout());
+            }
+            match (&result, &self.retry) {
+                (Err(e), Some(retry)) if attempt < retry.attempts && retry.retries(cc, e) => {
+                    #[cfg(feature = "debug")]
+                    eps_log! {"Transfer of {:#04x} failed ({}), retry {}", cc, e, attempt};
+                    let mut stats = self.stats.get();
+                    stats.retries += 1;
+                    self.stats.set(stats);
+                    if let Err(e) = sleep_within(retry.backoff(attempt), deadline) {
+                        break Err(e);
+                    }
+                }
+                _ => break result,
+            }
+        };
+        self.record_transfer(cc, &result);
+        #[cfg(feature = "serde")]
+        if let (Some(journal), Some((cmd, data))) = (&self.journal, journal_entry) {
+            let stat = match &result {
+                Ok(x) => x.get(4).copied(),
+                Err(_) => None,
+            };
+            journal.append(cmd, data, rx_len, delay, stat);
+        }
+        result
+    }
+
+    fn transfer_once(
+        &self,
+        stid: u8,
+        data: &[u8],
+        rx_len: usize,
+        delay: Duration,
+        deadline: Option<Instant>,
+    ) -> std::io::Result<Vec<u8>> {
+        let cc = data.get(1).copied().unwrap_or_default();
+        self.throttle(cc, deadline)?;
+        if let Some(last) = self.last_transfer.get() {
+            let elapsed = last.elapsed();
+            if elapsed < self.pacing.min_gap {
+                sleep_within(self.pacing.min_gap - elapsed, deadline)?;
+            }
+        }
+        let command = Command {
+            cmd: stid,
+            data: data.to_vec(),
+        };
+        let result = match self.exchange(command, rx_len, delay, deadline) {
+            // The STAT is read from every response, so a reply shorter than the header is an error
+            Ok(x) if x.len() < 5 => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "response shorter than header",
+            )),
+            Ok(x) => match self.await_fresh(stid, cc, rx_len, x, deadline) {
+                Ok(x) => match self.check_icd(stid, data, &x) {
+                    Ok(()) => Ok(x),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        self.last_transfer.set(Some(Instant::now()));
+        result
+    }
+
+    fn record_transfer(&self, cc: u8, result: &std::io::Result<Vec<u8>>) {
+        let ok = match result {
+            Ok(x) => x.get(4).is_some_and(|stat| stat & !STAT_NEW == 0),
+            Err(_) => false,
+        };
+        let mut stats = self.stats.get();
+        stats.transfers += 1;
+        if !ok {
+            stats.failures += 1;
+        } else if cc == WATCHDOG {
+            self.last_watchdog.set(self.last_transfer.get());
+        } else if is_hk_request(cc) {
+            self.last_hk.set(self.last_transfer.get());
+        }
+        self.stats.set(stats);
+    }
+
+    pub fn transfer_stats(&self) -> TransferStats {
+        self.stats.get()
+    }
+
     // No-operation. Check system availability, without changing anything
     pub fn eps_ping(&self) -> EpsResult<()> {
-        let cmd_code: u8 = NO_OP;
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        let cmd: u8 = self.stid();
+        let data: Vec<u8> = self.header(NO_OP_HEADER).to_vec();
         let command = Command { cmd, data }; // i2c command
 
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         // #[cfg(feature = "debug")]
-        println! {"Eps Ping Cmd {:?}",command};
+        eps_log! {"Eps Ping Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 // #[cfg(feature = "debug")]
-                println! {"Eps Ping Response{:?}",x};
-                match_stat(x[4])
+                eps_log! {"Eps Ping Response{:?}",x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     // Software reset. A reply to this command will not always be retrievable (system will shut down after this)
     pub fn sys_reset(&self, ret_key: u8) -> EpsResult<()> {
+        self.check_armed()?;
+
         // let ret_key: u8 = 0xA6; // Reset key
-        let cmd_code: u8 = SYS_RESET; // command code
-        let cmd: u8 = PIU_STID;
+        let cmd: u8 = self.stid();
 
         // The value of ret_key needs to be set to 0xA6 for the command to be accepted.
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID, ret_key].to_vec();
+        let data: Vec<u8> = [&self.header(SYS_RESET_HEADER)[..], &[ret_key]].concat();
         let command = Command { cmd, data }; // i2c command
 
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"System Reset Cmd {:?}",command};
+        eps_log! {"System Reset Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            // The (5th byte) responsed need to be parsed with match_stat
+        match self.transfer(command, rx_len, delay) {
+            // The (5th byte) responsed need to be parsed with check_stat
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"System Reset Response{:?}",x};
-                match_stat(x[4])
+                eps_log! {"System Reset Response{:?}",x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     // Switches off any command-enable output bus channels.
     // All force-enable channels will remain enabled.
     pub fn shutdown_all(&self) -> EpsResult<()> {
-        let cmd_code: u8 = CANCEL_OP;
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        self.check_armed()?;
+
+        let cmd: u8 = self.unit_stid(StID::PduStid);
+        let data: Vec<u8> = self.header(CANCEL_OP_HEADER).to_vec();
         let command = Command { cmd, data }; // i2c command
 
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"Shutdown All Cmd {:?}",command};
+        eps_log! {"Shutdown All Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            // The (5th byte) responsed need to be parsed with match_stat
+        match self.transfer(command, rx_len, delay) {
+            // The (5th byte) responsed need to be parsed with check_stat
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Shutdown All Response{:?}",x};
-                match_stat(x[4])
+                eps_log! {"Shutdown All Response{:?}",x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     // Resets the watchdog timer keeping the system from performing a reset (0x06)
     // Note tha any traffic with the system implicitly performs a watchdog reset.
     pub fn watchdog_reset(&self) -> EpsResult<()> {
-        let cmd_code: u8 = WATCHDOG;
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        let cmd: u8 = self.stid();
+        let data: Vec<u8> = self.header(WATCHDOG_HEADER).to_vec();
         let command = Command { cmd, data }; // i2c command
 
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"Watchdog Reset Cmd {:?}",command};
+        eps_log! {"Watchdog Reset Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            // The (5th byte) responsed need to be parsed with match_stat
+        match self.transfer(command, rx_len, delay) {
+            // The (5th byte) responsed need to be parsed with check_stat
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Watchdog Reset Response{:?}",x};
-                match_stat(x[4])
+                eps_log! {"Watchdog Reset Response{:?}",x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     fn set_group(&self, typ_group: BusGroup, channels: BusChannelState) -> EpsResult<()> {
+        self.check_lockout(LockoutScope::Channels)?;
+        channels.validate(typ_group)?;
+        match typ_group {
+            BusGroup::BusGroupOn => self.check_switching(channels.on_mask() as u32, 0)?,
+            BusGroup::BusGroupOff => self.check_switching(0, channels.off_mask() as u32)?,
+            BusGroup::BusGroupState => {
+                self.check_switching(channels.on_mask() as u32, channels.off_mask() as u32)?
+            }
+        }
+
         // Match correct command arg
-        let cmd_code: u8 = match typ_group {
-            BusGroup::BusGroupOn => OUTPUT_BUS_GROUP_ON,
-            BusGroup::BusGroupOff => OUTPUT_BUS_GROUP_OFF,
-            BusGroup::BusGroupState => OUTPUT_BUS_GROUP_STATE,
+        let header = match typ_group {
+            BusGroup::BusGroupOn => OUTPUT_BUS_GROUP_ON_HEADER,
+            BusGroup::BusGroupOff => OUTPUT_BUS_GROUP_OFF_HEADER,
+            BusGroup::BusGroupState => OUTPUT_BUS_GROUP_STATE_HEADER,
         };
 
-        let cmd: u8 = PIU_STID;
+        let cmd: u8 = self.unit_stid(StID::PduStid);
         let group_bytes = match typ_group {
             BusGroup::BusGroupOn => channels.on().to_le_bytes(),
             BusGroup::BusGroupOff => channels.off().to_le_bytes(),
             BusGroup::BusGroupState => {
-                let current_state = match self.piu_hk(PIUHkSel::PIUEngHK) {
-                    Ok(x) => x.stat_ch_on,
-                    Err(e) => return Err(e),
+                // Only read back the current state if some channels are to be kept
+                let current_state = if channels.keep_mask() == 0 {
+                    0
+                } else {
+                    match self.piu_hk(PIUHkSel::PIUEngHK) {
+                        Ok(x) => x.stat_ch_on.bits(),
+                        Err(e) => return Err(e),
+                    }
                 };
                 match channels.state(current_state) {
                     Ok(x) => x.to_le_bytes(),
@@ -178,32 +745,49 @@ impl Eps {
         }; // use little endian for ISIS{
 
         // e.g. 0b1010011 (=0x0503, decimal 83). This switches output bus channels 0, 1, 4 and 6
-        let data: Vec<u8> = [&[ALL_IVID, cmd_code, OVERRIDE_BID], &group_bytes[..]].concat();
+        let data: Vec<u8> = [&self.header(header)[..], &group_bytes[..]].concat();
 
-        // let data:Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID, group_bytes[0], group_bytes[1]].to_vec();
         let command = Command { cmd, data };
         // Send command
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"Set Group Cmd {:?}",command};
+        eps_log! {"Set Group Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            // The (5th byte) responsed need to be parsed with match_stat
+        let result = match self.transfer(command, rx_len, delay) {
+            // The (5th byte) responsed need to be parsed with check_stat
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Set Group Response {:?}",x};
-                match_stat(x[4])
+                eps_log! {"Set Group Response {:?}",x};
+                check_stat(&x)
+            }
+            Err(e) => Err(transfer_error(e)),
+        };
+        if result.is_ok() {
+            match typ_group {
+                BusGroup::BusGroupOn => self.verify_channels(channels.on_mask() as u32, 0)?,
+                BusGroup::BusGroupOff => self.verify_channels(0, channels.off_mask() as u32)?,
+                BusGroup::BusGroupState => {
+                    self.verify_channels(channels.on_mask() as u32, channels.off_mask() as u32)?
+                }
             }
-            Err(_e) => Err(EpsError::TransferError),
         }
+        result
     }
 
     // Turn-on/off output bus channels with bitflag, leave unmarked unaltered. （0x10,0x12,0x14）
     // LSB bit corresponds to bus channel 0 (CH0),
-    pub fn set_group_outputs(&self, typ_group: BusGroup, channels: Vec<u8>) -> EpsResult<()> {
-        let bus_channels = BusChannelState::set(typ_group, channels)?;
+    // Invalid channel indices are reported in EpsError::InvalidChannels
+    pub fn set_group_outputs(&self, typ_group: BusGroup, channels: Vec<Channel>) -> EpsResult<()> {
+        let bus_channels = match BusChannelState::from_group(typ_group, channels) {
+            Ok(x) => x,
+            Err(e) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"Set Group Outputs {:?}",e};
+                return Err(e);
+            }
+        };
 
         self.set_group(typ_group, bus_channels)
     }
@@ -212,315 +796,470 @@ impl Eps {
         self.set_group(typ_group, channels)
     }
 
-    // Turn a single output bus channel on using the bus channel index. (0x16,0x18)
-    // e.g. Index 0 represents channel 0 (CH0)
-    pub fn set_single_output(&self, typ_channel: BusChannel, eps_ch_idx: u8) -> EpsResult<()> {
-        // Check if rejection index error occurs within ISIS
-        // Designed for ICEPSv2 (17 channels), Consider to remove this for larger iEPS modules
-        if eps_ch_idx > 0x10 {
-            return Err::<(), EpsError>(EpsError::InvalidInput);
-        }
+    // Turn a single output bus channel on or off. (0x16,0x18)
+    // Use Channel::try_from() for a channel index, e.g. 0 for channel 0 (CH0)
+    pub fn set_single_output(&self, typ_channel: BusChannel, channel: Channel) -> EpsResult<()> {
+        self.check_lockout(LockoutScope::Channels)?;
+        let eps_ch_idx = channel.index();
 
-        let cmd_code: u8 = match typ_channel {
-            BusChannel::On => OUTPUT_BUS_CHANNEL_ON,
-            BusChannel::Off => OUTPUT_BUS_CHANNEL_OFF,
+        let header = match typ_channel {
+            BusChannel::On => OUTPUT_BUS_CHANNEL_ON_HEADER,
+            BusChannel::Off => OUTPUT_BUS_CHANNEL_OFF_HEADER,
             BusChannel::Keep => return Err(EpsError::InvalidInput),
         };
+        match typ_channel {
+            BusChannel::On => self.check_switching(1 << eps_ch_idx, 0)?,
+            _ => self.check_switching(0, 1 << eps_ch_idx)?,
+        }
 
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID, eps_ch_idx].to_vec();
+        let cmd: u8 = self.unit_stid(StID::PduStid);
+        let data: Vec<u8> = [&self.header(header)[..], &[eps_ch_idx]].concat();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"Set SingleOutput Cmd {:?}",command};
+        eps_log! {"Set SingleOutput Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            // The (5th byte) responsed need to be parsed with match_stat
+        let result = match self.transfer(command, rx_len, delay) {
+            // The (5th byte) responsed need to be parsed with check_stat
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Set SingleOutput Response {:?}",x};
-                match_stat(x[4])
+                eps_log! {"Set SingleOutput Response {:?}",x};
+                check_stat(&x)
+            }
+            Err(e) => Err(transfer_error(e)),
+        };
+        if result.is_ok() {
+            match typ_channel {
+                BusChannel::On => self.verify_channels(1 << eps_ch_idx, 0)?,
+                _ => self.verify_channels(0, 1 << eps_ch_idx)?,
             }
-            Err(_e) => Err(EpsError::TransferError),
         }
+        result
+    }
+
+    // Switch a channel only if it isn't in the desired state already.
+    // Returns true if a switch command was sent.
+    pub fn ensure_channel(&self, channel: Channel, desired: BusChannel) -> EpsResult<bool> {
+        let eps_ch_idx = channel.index();
+        let states = self.channel_states()?;
+        let on =
+            (states.on.on() as u32 | (states.ext_on.bits() as u32) << 16) & (1 << eps_ch_idx) != 0;
+        match desired {
+            BusChannel::On if !on => (),
+            BusChannel::Off if on => (),
+            _ => return Ok(false),
+        }
+        self.set_single_output(desired, channel)?;
+        Ok(true)
     }
 
     pub fn mode_switch(&self, mode: ModeSwitch) -> EpsResult<()> {
-        let cmd_code: u8 = match mode {
-            ModeSwitch::Nominal => SWITCH_TO_NOMINAL_MODE,
-            ModeSwitch::Safety => SWITCH_TO_SAFETY_MODE,
+        let header = match mode {
+            ModeSwitch::Nominal => SWITCH_TO_NOMINAL_MODE_HEADER,
+            ModeSwitch::Safety => SWITCH_TO_SAFETY_MODE_HEADER,
         };
 
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        let cmd: u8 = self.stid();
+        let data: Vec<u8> = self.header(header).to_vec();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"Mode Switch Cmd {:?}",command};
+        eps_log! {"Mode Switch Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            // The (5th byte) responsed need to be parsed with match_stat
+        let result = match self.transfer(command, rx_len, delay) {
+            // The (5th byte) responsed need to be parsed with check_stat
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Mode Switch Response {:?}",x};
-                match_stat(x[4])
+                eps_log! {"Mode Switch Response {:?}",x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
+        };
+        if result.is_ok() {
+            self.verify_mode(&mode)?;
         }
+        result
     }
 
     // Get EPS System Status
     pub fn system_status(&self) -> EpsResult<SystemStatus> {
-        let cmd_code: u8 = GET_SYS_STATUS;
+        self.system_status_delayed(self.pacing.response_delay)
+    }
 
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+    fn system_status_delayed(&self, delay: Duration) -> EpsResult<SystemStatus> {
+        let cmd: u8 = self.stid();
+        let data: Vec<u8> = self.header(GET_SYS_STATUS_HEADER).to_vec();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 36;
-        let delay = Duration::from_millis(50);
+        let rx_len = SystemStatus::RESPONSE_LEN;
 
         #[cfg(feature = "debug")]
-        println! {"System Status Cmd {:?}",command};
+        eps_log! {"System Status Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"System Status Response {:?}", x};
-                match match_stat(x[4]) {
-                    Ok(()) => SystemStatus::try_from(x),
+                eps_log! {"System Status Response {:?}", x};
+                match check_stat(&x) {
+                    Ok(()) => SystemStatus::try_from(x.as_slice()),
                     Err(e) => Err(e),
                 }
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     // 0x42  – Get Overcurrent Fault State
     pub fn overcurrent_state(&self) -> EpsResult<OverCurrentFaultState> {
-        let cmd_code: u8 = GET_PDU_OC_FAULT_STATE;
-
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        let cmd: u8 = self.unit_stid(StID::PduStid);
+        let data: Vec<u8> = self.header(GET_PDU_OC_FAULT_STATE_HEADER).to_vec();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 78;
-        let delay = Duration::from_millis(50);
+        let rx_len = OverCurrentFaultState::RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"OverCurrent Status Cmd {:?}",command};
+        eps_log! {"OverCurrent Status Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"OverCurrent Status Response {:?}", x};
-                match match_stat(x[4]) {
-                    Ok(()) => Ok(OverCurrentFaultState::from(x)),
+                eps_log! {"OverCurrent Status Response {:?}", x};
+                match check_stat(&x) {
+                    Ok(()) => OverCurrentFaultState::try_from(x.as_slice()),
                     // Ok(()) => Ok(bincode::deserialize::<OverCurrentFaultState>(&x[6..50])?),
                     Err(e) => Err(e),
                 }
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
+        }
+    }
+
+    // 0x42 – Latch-off state and fault counter of one channel
+    pub fn channel_fault(&self, ch: Channel) -> EpsResult<ChannelFault> {
+        match self.overcurrent_state() {
+            Ok(x) => Ok(x.fault(ch)),
+            Err(e) => Err(e),
         }
     }
 
-    // // 0x44  – Get ABF Placed State
-    // pub fn abf_state(&self) -> EpsResult<ABFState> {
-    //     let cmd_code: u8 = GET_PBU_ABF_PLACED_STATE;
+    // 0x42 – Get the channel-on states only
+    // Reads just the header and the STAT_CH_ON/STAT_CH_EXT_ON fields of the
+    // overcurrent fault state response instead of the full 78 bytes
+    pub fn channel_states(&self) -> EpsResult<ChannelStates> {
+        let cmd: u8 = self.unit_stid(StID::PduStid);
+        let data: Vec<u8> = self.header(GET_PDU_OC_FAULT_STATE_HEADER).to_vec();
+        let command = Command { cmd, data };
+
+        // Send command
+        let rx_len = ChannelStates::RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
-    //     let cmd: u8 = PIU_STID;
-    //     let data:Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
-    //     let command = Command{cmd, data};
+        #[cfg(feature = "debug")]
+        eps_log! {"Channel States Cmd {:?}",command};
 
-    //     // Send command
-    //     let rx_len = 8;
-    //     let delay = Duration::from_millis(50);
+        match self.transfer(command, rx_len, delay) {
+            Ok(x) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"Channel States Response {:?}", x};
+                match check_stat(&x) {
+                    Ok(()) => ChannelStates::try_from(x.as_slice()),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(transfer_error(e)),
+        }
+    }
 
-    //     #[cfg(feature = "debug")]
-    //     println!{"ABF State {:?}",command};
+    // 0x44  – Get ABF Placed State
+    pub fn abf_state(&self) -> EpsResult<ABFState> {
+        let cmd: u8 = self.unit_stid(StID::PbuStid);
+        let data: Vec<u8> = self.header(GET_PBU_ABF_PLACED_STATE_HEADER).to_vec();
+        let command = Command { cmd, data };
 
-    //     match self.i2c.transfer(command, rx_len, delay) {
-    //         Ok(x) => {
-    //             #[cfg(feature = "debug")]
-    //             println!{"ABF State Cmd {:?}", x};
-    //             match match_stat(x[4]){
-    //                 Ok(()) => Ok(ABFState::from(x)),
-    //                 // Ok(()) => Ok(bincode::deserialize::<ABFState>(&x[6..8])?),
-    //                 Err(e) => Err(e),
-    //             }
-    //         }
-    //         Err(_e) => Err(EpsError::TransferError),
-    //     }
+        // Send command
+        let rx_len = ABFState::RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
-    // }
+        #[cfg(feature = "debug")]
+        eps_log! {"ABF State Cmd {:?}",command};
+
+        match self.transfer(command, rx_len, delay) {
+            Ok(x) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"ABF State Response {:?}", x};
+                match check_stat(&x) {
+                    Ok(()) => ABFState::try_from(x.as_slice()),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(transfer_error(e)),
+        }
+    }
 
     // 0x52 and 0x54  – Get PDU Housekeeping Data (Engineering and Average Data)
     pub fn pdu_hk(&self, mode: PDUHkSel) -> EpsResult<PDUHk> {
-        let cmd_code: u8 = match mode {
-            PDUHkSel::PDURawHK => GET_PDU_HK_DATA_RAW,
-            PDUHkSel::PDUEngHK => GET_PDU_HK_DATA_ENG,
-            PDUHkSel::PDUAvgHK => GET_PDU_HK_DATA_AVRG,
+        self.pdu_hk_delayed(mode, self.pacing.response_delay)
+    }
+
+    fn pdu_hk_delayed(&self, mode: PDUHkSel, delay: Duration) -> EpsResult<PDUHk> {
+        let header = match mode {
+            PDUHkSel::PDURawHK => GET_PDU_HK_DATA_RAW_HEADER,
+            PDUHkSel::PDUEngHK => GET_PDU_HK_DATA_ENG_HEADER,
+            PDUHkSel::PDUAvgHK => GET_PDU_HK_DATA_AVRG_HEADER,
         };
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        let cmd: u8 = self.unit_stid(StID::PduStid);
+        let data: Vec<u8> = self.header(header).to_vec();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 258;
-        let delay = Duration::from_millis(50);
+        let rx_len = PDUHk::RESPONSE_LEN;
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            Ok(x) => match match_stat(x[4]) {
-                Ok(()) => Ok(PDUHk::from(x[6..156].to_vec())),
+        match self.transfer(command, rx_len, delay) {
+            Ok(x) => match check_stat(&x) {
+                Ok(()) => hk_from_frame::<PDUHk>(&x),
                 Err(e) => Err(e),
             },
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     // 0x62 and 0x64  – Get PBU Housekeeping Data (Engineering and Average Data)
     pub fn pbu_hk(&self, mode: PBUHkSel) -> EpsResult<PBUHk> {
-        let cmd_code: u8 = match mode {
-            PBUHkSel::PBURawHK => GET_PBU_HK_DATA_RAW,
-            PBUHkSel::PBUEngHK => GET_PBU_HK_DATA_ENG,
-            PBUHkSel::PBUAvgHK => GET_PBU_HK_DATA_AVRG,
+        self.pbu_hk_delayed(mode, self.pacing.response_delay)
+    }
+
+    fn pbu_hk_delayed(&self, mode: PBUHkSel, delay: Duration) -> EpsResult<PBUHk> {
+        let header = match mode {
+            PBUHkSel::PBURawHK => GET_PBU_HK_DATA_RAW_HEADER,
+            PBUHkSel::PBUEngHK => GET_PBU_HK_DATA_ENG_HEADER,
+            PBUHkSel::PBUAvgHK => GET_PBU_HK_DATA_AVRG_HEADER,
         };
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        let cmd: u8 = self.unit_stid(StID::PbuStid);
+        let data: Vec<u8> = self.header(header).to_vec();
         let command = Command { cmd, data };
 
-        // Send command
-        let rx_len = 84;
-        let delay = Duration::from_millis(50);
+        // Send command, header and reserved byte + 12 bytes + 22 bytes per battery pack
+        let rx_len = PBUHk::response_len(self.battery_packs);
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            Ok(x) => match match_stat(x[4]) {
-                Ok(()) => Ok(PBUHk::from(x[6..34].to_vec())),
+        match self.transfer(command, rx_len, delay) {
+            Ok(x) => match check_stat(&x) {
+                Ok(()) => hk_from_frame::<PBUHk>(&x),
                 Err(e) => Err(e),
             },
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     // 0x72 and 0x74  – Get PCU Housekeeping Data (Engineering and Average Data)
     pub fn pcu_hk(&self, mode: PCUHkSel) -> EpsResult<PCUHk> {
-        let cmd_code: u8 = match mode {
-            PCUHkSel::PCURawHK => GET_PCU_HK_DATA_RAW,
-            PCUHkSel::PCUEngHK => GET_PCU_HK_DATA_ENG,
-            PCUHkSel::PCUAvgHK => GET_PCU_HK_DATA_AVRG,
+        self.pcu_hk_delayed(mode, self.pacing.response_delay)
+    }
+
+    fn pcu_hk_delayed(&self, mode: PCUHkSel, delay: Duration) -> EpsResult<PCUHk> {
+        let header = match mode {
+            PCUHkSel::PCURawHK => GET_PCU_HK_DATA_RAW_HEADER,
+            PCUHkSel::PCUEngHK => GET_PCU_HK_DATA_ENG_HEADER,
+            PCUHkSel::PCUAvgHK => GET_PCU_HK_DATA_AVRG_HEADER,
         };
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        let cmd: u8 = self.unit_stid(StID::PcuStid);
+        let data: Vec<u8> = self.header(header).to_vec();
         let command = Command { cmd, data };
 
         // Send command
-        let rx_len = 72;
-        let delay = Duration::from_millis(50);
+        let rx_len = PCUHk::RESPONSE_LEN;
 
-        match self.i2c.transfer(command, rx_len, delay) {
-            Ok(x) => match match_stat(x[4]) {
-                Ok(()) => Ok(PCUHk::from(x[6..].to_vec())),
+        match self.transfer(command, rx_len, delay) {
+            Ok(x) => match check_stat(&x) {
+                Ok(()) => hk_from_frame::<PCUHk>(&x),
                 Err(e) => Err(e),
             },
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     // 0xA2 and 0xA4  – Get PIU Housekeeping Data (Engineering and Average Data)
     pub fn piu_hk(&self, mode: PIUHkSel) -> EpsResult<PIUHk> {
-        let cmd_code: u8 = match mode {
-            PIUHkSel::PIURawHK => GET_PIU_HK_DATA_RAW,
-            PIUHkSel::PIUEngHK => GET_PIU_HK_DATA_ENG,
-            PIUHkSel::PIUAvgHK => GET_PIU_HK_DATA_AVRG,
+        self.piu_hk_delayed(mode, self.pacing.response_delay)
+    }
+
+    fn piu_hk_delayed(&self, mode: PIUHkSel, delay: Duration) -> EpsResult<PIUHk> {
+        match self.daughterboard {
+            DaughterboardPresence::Present => {
+                match self.piu_hk_frame(mode, PIUHk::RESPONSE_LEN, delay) {
+                    Ok(x) => PIUHk::try_from(x.as_slice()),
+                    // One reseved byte. Starting from the 6th byte
+                    // Ok(()) => Ok(bincode::deserialize::<PIUHk>(&x[6..184])?),
+                    Err(e) => Err(e),
+                }
+            }
+            DaughterboardPresence::Absent => match self.piu_hk_short_delayed(mode, delay) {
+                Ok(x) => Ok(PIUHk::from(x)),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    // 0xA2 and 0xA4  – Get PIU Housekeeping Data of a unit without daughterboard
+    // Works with the daughterboard as well, the extended channels are not read then
+    pub fn piu_hk_short(&self, mode: PIUHkSel) -> EpsResult<PIUHkShort> {
+        self.piu_hk_short_delayed(mode, self.pacing.response_delay)
+    }
+
+    fn piu_hk_short_delayed(&self, mode: PIUHkSel, delay: Duration) -> EpsResult<PIUHkShort> {
+        match self.piu_hk_frame(mode, PIUHkShort::RESPONSE_LEN, delay) {
+            Ok(x) => PIUHkShort::try_from(x.as_slice()),
+            Err(e) => Err(e),
+        }
+    }
+
+    // PIU HK response of rx_len bytes, STAT checked
+    fn piu_hk_frame(&self, mode: PIUHkSel, rx_len: usize, delay: Duration) -> EpsResult<Vec<u8>> {
+        let header = match mode {
+            PIUHkSel::PIURawHK => GET_PIU_HK_DATA_RAW_HEADER,
+            PIUHkSel::PIUEngHK => GET_PIU_HK_DATA_ENG_HEADER,
+            PIUHkSel::PIUAvgHK => GET_PIU_HK_DATA_AVRG_HEADER,
         };
-        let cmd: u8 = PIU_STID;
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID].to_vec();
+        let cmd: u8 = self.unit_stid(StID::PiuStid);
+        let data: Vec<u8> = self.header(header).to_vec();
+        let command = Command { cmd, data };
+
+        #[cfg(feature = "debug")]
+        eps_log! {"PIU HK Cmd {:?}",command};
+
+        match self.transfer(command, rx_len, delay) {
+            Ok(x) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"PIU HK Response {:?}", x};
+                match check_stat(&x) {
+                    Ok(()) => Ok(x),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(transfer_error(e)),
+        }
+    }
+
+    // 0xA2 and 0xA4  – Get PIU Housekeeping Data without decoding it
+    // Fields are decoded on access through HkFrameRaw.
+    // HkFrameRaw covers the full frame, units without daughterboard are rejected.
+    pub fn piu_hk_raw(&self, mode: PIUHkSel) -> EpsResult<HkFrameRaw> {
+        if self.daughterboard == DaughterboardPresence::Absent {
+            return Err(EpsError::InvalidInput);
+        }
+        let header = match mode {
+            PIUHkSel::PIURawHK => GET_PIU_HK_DATA_RAW_HEADER,
+            PIUHkSel::PIUEngHK => GET_PIU_HK_DATA_ENG_HEADER,
+            PIUHkSel::PIUAvgHK => GET_PIU_HK_DATA_AVRG_HEADER,
+        };
+        let cmd: u8 = self.unit_stid(StID::PiuStid);
+        let data: Vec<u8> = self.header(header).to_vec();
         let command = Command { cmd, data };
 
         // Send command
-        // 116 bytes w/o daughterboard, 274 bytes with daughterboard
-        let rx_len = 274;
-        let delay = Duration::from_millis(50);
+        let rx_len = PIUHk::RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"PIU HK Cmd {:?}",command};
+        eps_log! {"PIU HK Raw Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"PIU HK Response {:?}", x};
-                match match_stat(x[4]) {
-                    Ok(()) => Ok(PIUHk::from(x)),
-                    // One reseved byte. Starting from the 6th byte
-                    // Ok(()) => Ok(bincode::deserialize::<PIUHk>(&x[6..184])?),
+                eps_log! {"PIU HK Raw Response {:?}", x};
+                match check_stat(&x) {
+                    Ok(()) => HkFrameRaw::try_from(x),
                     Err(e) => Err(e),
                 }
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
+        }
+    }
+
+    // Fetch several housekeeping products in one sweep, returned in the order requested.
+    // The commands are issued back-to-back using the minimum response delay from the ICD
+    // instead of the default 50 ms used by the individual calls.
+    pub fn collect_hk(&self, selection: &[HkSelection]) -> EpsResult<Vec<HkData>> {
+        let delay = Duration::from_millis(HK_MIN_DELAY_MS);
+        let mut result: Vec<HkData> = Vec::with_capacity(selection.len());
+
+        for sel in selection.iter() {
+            let hk = match sel {
+                HkSelection::SystemStatus => {
+                    HkData::SystemStatus(self.system_status_delayed(delay)?)
+                }
+                HkSelection::Pdu(mode) => HkData::Pdu(self.pdu_hk_delayed(mode.clone(), delay)?),
+                HkSelection::Pbu(mode) => HkData::Pbu(self.pbu_hk_delayed(mode.clone(), delay)?),
+                HkSelection::Pcu(mode) => HkData::Pcu(self.pcu_hk_delayed(mode.clone(), delay)?),
+                HkSelection::Piu(mode) => HkData::Piu(self.piu_hk_delayed(mode.clone(), delay)?),
+            };
+            result.push(hk);
         }
+        Ok(result)
     }
 
     // Correct the unit’s unix time with the specified amount of seconds.
     // unix time value is returned as part of the “0x40 (0x41) – Get System Status” response,
     pub fn correct_time(&self, time_correction: i32) -> EpsResult<()> {
-        let _cmd_code: u8 = CORRECT_TIME;
-        let cmd: u8 = PIU_STID;
+        let cmd: u8 = self.stid();
 
-        let mut data: Vec<u8> = [ALL_IVID, 0xC4, OVERRIDE_BID].to_vec();
-        data.append(&mut time_correction.to_le_bytes().to_vec());
+        let data: Vec<u8> = [
+            &self.header(CORRECT_TIME_HEADER)[..],
+            &time_correction.to_le_bytes()[..],
+        ]
+        .concat();
 
         let command = Command { cmd, data };
 
-        let rx_len = 1;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.response_delay;
 
         #[cfg(feature = "debug")]
-        println! {"Correct Time Cmd {:?}",command};
+        eps_log! {"Correct Time Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Correct Time Response {:?}", x};
-                match_stat(x[4])
+                eps_log! {"Correct Time Response {:?}", x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 
     //  Write all reset cause counters to zero in persistent memory (0xC6)
     pub fn reset_all_counters(&self) -> EpsResult<()> {
-        let cmd_code: u8 = RST_CAUSE_CNTR;
-        let cmd: u8 = PIU_STID;
+        let cmd: u8 = self.stid();
         let zero_key: u8 = 0xA7;
 
         // Zero key: 0xA7. Any other value causes this command to be rejected with a parameter error
         // XL: Not sure why zero_key is defined as i32 in manual, to be tested
-        let data: Vec<u8> = [ALL_IVID, cmd_code, OVERRIDE_BID, zero_key].to_vec();
+        let data: Vec<u8> = [&self.header(RST_CAUSE_CNTR_HEADER)[..], &[zero_key]].concat();
         let command = Command { cmd, data }; // i2c command
 
-        let rx_len = 5;
-        let delay = Duration::from_millis(50);
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing.nvm_delay();
 
         #[cfg(feature = "debug")]
-        println! {"Reset All Counters Cmd {:?}",command};
+        eps_log! {"Reset All Counters Cmd {:?}",command};
 
-        match self.i2c.transfer(command, rx_len, delay) {
+        match self.transfer(command, rx_len, delay) {
             Ok(x) => {
                 #[cfg(feature = "debug")]
-                println! {"Reset All Counters Response {:?}", x};
-                match_stat(x[4])
+                eps_log! {"Reset All Counters Response {:?}", x};
+                check_stat(&x)
             }
-            Err(_e) => Err(EpsError::TransferError),
+            Err(e) => Err(transfer_error(e)),
         }
     }
 }
@@ -1,115 +1,383 @@
+use crate::config::Output;
+use crate::objects::ChannelId;
+use crate::stat::StatKind;
+#[cfg(feature = "service")]
 use cubeos_service::Error;
-use failure::Fail;
+#[cfg(not(feature = "thiserror"))]
+use std::fmt;
 
 // Error list
-#[derive(Debug, Fail, Clone, PartialEq)]
+// New variants may be added, so matches outside the crate need a wildcard arm
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "thiserror", derive(thiserror::Error))]
+#[non_exhaustive]
 pub enum EpsError {
     /// Example error
-    #[fail(display = "Eps Error")]
+    #[cfg_attr(feature = "thiserror", error("Eps Error"))]
     Err,
     /// I2C Error
-    #[fail(display = "I2C Error")]
+    #[cfg_attr(feature = "thiserror", error("I2C Error"))]
     I2CError(std::io::ErrorKind),
-    #[fail(display = "I2C Error")]
+    #[cfg_attr(feature = "thiserror", error("I2C Error"))]
     I2CError2(u8),
     /// I2C Set Error
-    #[fail(display = "I2C Set Error")]
+    #[cfg_attr(feature = "thiserror", error("I2C Set Error"))]
     I2CSet,
-    #[fail(display = "Transfer error")]
+    #[cfg_attr(feature = "thiserror", error("Transfer error"))]
     TransferError,
-    #[fail(display = "InvalidInput error")]
+    #[cfg_attr(feature = "thiserror", error("InvalidInput error"))]
     InvalidInput,
-    // // Errors from deserialization
-    // #[fail(display = "bincode Error")]
-    // Bincode(u8),
-    // Response Status Information (STAT) Errors
-    #[fail(display = "Rejected")]
+    // Errors from binary (de)serialization
+    #[cfg_attr(feature = "thiserror", error("bincode Error"))]
+    Bincode(u8),
+    // Response Status Information (STAT) Errors, as received as service error
+    // codes. Responses of the EPS are reported as EpsError::Stat.
+    #[cfg_attr(feature = "thiserror", error("Rejected"))]
     Rejected,
-    #[fail(display = "Rejected: Invalid command code error")]
+    #[cfg_attr(feature = "thiserror", error("Rejected: Invalid command code error"))]
     InvalidCommandCode,
-    #[fail(display = "Rejected: Parameter missing error")]
+    #[cfg_attr(feature = "thiserror", error("Rejected: Parameter missing error"))]
     ParameterMissing,
-    #[fail(display = "Rejected: Parameter invalid error")]
+    #[cfg_attr(feature = "thiserror", error("Rejected: Parameter invalid error"))]
     Parameterinvalid,
-    #[fail(display = "Rejected: Unavailable in current mode/configuration error")]
+    #[cfg_attr(
+        feature = "thiserror",
+        error("Rejected: Unavailable in current mode/configuration error")
+    )]
     UnavailableMode,
-    #[fail(display = "Rejected: Invalid system type, interface version, or BID error")]
+    #[cfg_attr(
+        feature = "thiserror",
+        error("Rejected: Invalid system type, interface version, or BID error")
+    )]
     InvalidSystemType,
-    #[fail(display = "Internal error occurred during processing")]
+    #[cfg_attr(
+        feature = "thiserror",
+        error("Internal error occurred during processing")
+    )]
     InternalProcessing,
-    #[fail(display = "Invalid Reset Cause")]
+    #[cfg_attr(feature = "thiserror", error("Invalid Reset Cause"))]
     InvalidResetCause,
-    #[fail(display = "Invalid Eps Mode")]
+    #[cfg_attr(feature = "thiserror", error("Invalid Eps Mode"))]
     InvalidEpsMode,
-    #[fail(display = "Invalid Bus Channel State")]
+    #[cfg_attr(feature = "thiserror", error("Invalid Bus Channel State"))]
     InvalidBusChannelState,
+    // Reading or writing the persisted state file failed
+    #[cfg_attr(feature = "thiserror", error("State File Error"))]
+    StateFile(std::io::ErrorKind),
+    // Reading or validating a mission profile failed
+    #[cfg_attr(feature = "thiserror", error("Mission Profile Error"))]
+    Profile(std::io::ErrorKind),
+    // Command refused because an operational lockout window is active
+    #[cfg_attr(feature = "thiserror", error("Locked Out"))]
+    LockedOut,
+    // Read-back after a command didn't match what was commanded
+    #[cfg_attr(
+        feature = "thiserror",
+        error("Verification Failed: expected {expected:?}, read {actual:?}")
+    )]
+    VerificationFailed { expected: Output, actual: Output },
+    // Destructive command without a preceding Eps::arm()
+    #[cfg_attr(feature = "thiserror", error("Not Armed"))]
+    NotArmed,
+    // Switching refused, it would break the declared channel dependencies
+    #[cfg_attr(feature = "thiserror", error("Channel Dependency Violation"))]
+    DependencyViolation,
+    // Binary record with a wrong length or CRC
+    #[cfg_attr(feature = "thiserror", error("CRC Mismatch"))]
+    CrcMismatch,
+    // Channels (index) that can't be used in a bus group command, e.g. out of range or in conflicting states
+    #[cfg_attr(feature = "thiserror", error("Invalid Channels"))]
+    InvalidChannels(Vec<u8>),
+    // Response shorter than the telemetry it should contain (expected, received bytes).
+    // Lengths of the complete response frame, header included, for every Eps
+    // method. The TryFrom<&[u8]> of PDUHk, PBUHk and PCUHk take only the data
+    // after the header and report the length of that.
+    #[cfg_attr(feature = "thiserror", error("Response Too Short"))]
+    ResponseTooShort(usize, usize),
+    // Parsing or encoding a config snapshot failed
+    #[cfg_attr(feature = "thiserror", error("Config Snapshot Error"))]
+    Snapshot(std::io::ErrorKind),
+    // Value width doesn't match the config parameter
+    #[cfg_attr(feature = "thiserror", error("Type Mismatch"))]
+    TypeMismatch,
+    // The EPS handle is in use by another thread (SharedEps::try_lock)
+    #[cfg_attr(feature = "thiserror", error("Busy"))]
+    Busy,
+    // STAT rejection of a command: command code (from the response code), config
+    // parameter ID for the config parameter commands, and the raw STAT byte.
+    // Reported with the service error code of its kind.
+    #[cfg_attr(
+        feature = "thiserror",
+        error("{kind}: command {cmd:#04x}, parameter {param:x?}, STAT {stat:#04x}")
+    )]
+    Stat {
+        cmd: u8,
+        param: Option<u16>,
+        stat: u8,
+        kind: StatKind,
+    },
+    // Switching refused by the ChannelPolicy (channel, conflicting channel)
+    #[cfg_attr(
+        feature = "thiserror",
+        error("Channel Policy Violation: channel {0} conflicts with channel {1}")
+    )]
+    PolicyViolation(ChannelId, ChannelId),
+    // Response deviating from the ICD in strict mode, with the deviation found
+    #[cfg_attr(feature = "thiserror", error("ICD Deviation: {0}"))]
+    IcdDeviation(String),
+}
+
+// Without thiserror the errors are displayed as their Debug form
+#[cfg(not(feature = "thiserror"))]
+impl fmt::Display for EpsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+#[cfg(not(feature = "thiserror"))]
+impl std::error::Error for EpsError {}
+
+// Service error code of a STAT rejection, see stat.rs for the kinds
+impl StatKind {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            StatKind::Rejected => ErrorCode::Rejected,
+            StatKind::InvalidCommandCode => ErrorCode::InvalidCommandCode,
+            StatKind::ParameterMissing => ErrorCode::ParameterMissing,
+            StatKind::ParameterInvalid => ErrorCode::Parameterinvalid,
+            StatKind::UnavailableMode => ErrorCode::UnavailableMode,
+            StatKind::InvalidSystemType => ErrorCode::InvalidSystemType,
+            StatKind::InternalProcessing => ErrorCode::InternalProcessing,
+        }
+    }
+}
+
+// Stable numeric codes of the errors, sent as Error::ServiceError(code).
+// Codes must never be reused or renumbered, new errors get the next free code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    Err = 0,
+    I2CSet = 1,
+    TransferError = 2,
+    InvalidInput = 3,
+    Rejected = 4,
+    InvalidCommandCode = 5,
+    ParameterMissing = 6,
+    Parameterinvalid = 7,
+    UnavailableMode = 8,
+    InvalidSystemType = 9,
+    InternalProcessing = 10,
+    InvalidResetCause = 11,
+    InvalidEpsMode = 12,
+    InvalidBusChannelState = 13,
+    Bincode = 14,
+    StateFile = 15,
+    Profile = 16,
+    LockedOut = 17,
+    VerificationFailed = 18,
+    NotArmed = 19,
+    DependencyViolation = 20,
+    CrcMismatch = 21,
+    InvalidChannels = 22,
+    ResponseTooShort = 23,
+    Snapshot = 24,
+    TypeMismatch = 25,
+    Busy = 26,
+    PolicyViolation = 27,
+    IcdDeviation = 28,
+}
+impl From<ErrorCode> for u8 {
+    fn from(c: ErrorCode) -> u8 {
+        c as u8
+    }
+}
+impl TryFrom<u8> for ErrorCode {
+    type Error = u8;
+    fn try_from(v: u8) -> Result<ErrorCode, u8> {
+        match v {
+            0 => Ok(ErrorCode::Err),
+            1 => Ok(ErrorCode::I2CSet),
+            2 => Ok(ErrorCode::TransferError),
+            3 => Ok(ErrorCode::InvalidInput),
+            4 => Ok(ErrorCode::Rejected),
+            5 => Ok(ErrorCode::InvalidCommandCode),
+            6 => Ok(ErrorCode::ParameterMissing),
+            7 => Ok(ErrorCode::Parameterinvalid),
+            8 => Ok(ErrorCode::UnavailableMode),
+            9 => Ok(ErrorCode::InvalidSystemType),
+            10 => Ok(ErrorCode::InternalProcessing),
+            11 => Ok(ErrorCode::InvalidResetCause),
+            12 => Ok(ErrorCode::InvalidEpsMode),
+            13 => Ok(ErrorCode::InvalidBusChannelState),
+            14 => Ok(ErrorCode::Bincode),
+            15 => Ok(ErrorCode::StateFile),
+            16 => Ok(ErrorCode::Profile),
+            17 => Ok(ErrorCode::LockedOut),
+            18 => Ok(ErrorCode::VerificationFailed),
+            19 => Ok(ErrorCode::NotArmed),
+            20 => Ok(ErrorCode::DependencyViolation),
+            21 => Ok(ErrorCode::CrcMismatch),
+            22 => Ok(ErrorCode::InvalidChannels),
+            23 => Ok(ErrorCode::ResponseTooShort),
+            24 => Ok(ErrorCode::Snapshot),
+            25 => Ok(ErrorCode::TypeMismatch),
+            26 => Ok(ErrorCode::Busy),
+            27 => Ok(ErrorCode::PolicyViolation),
+            28 => Ok(ErrorCode::IcdDeviation),
+            _ => Err(v),
+        }
+    }
+}
+// Errors carrying a payload are restored with a default payload
+impl From<ErrorCode> for EpsError {
+    fn from(c: ErrorCode) -> EpsError {
+        match c {
+            ErrorCode::Err => EpsError::Err,
+            ErrorCode::I2CSet => EpsError::I2CSet,
+            ErrorCode::TransferError => EpsError::TransferError,
+            ErrorCode::InvalidInput => EpsError::InvalidInput,
+            ErrorCode::Rejected => EpsError::Rejected,
+            ErrorCode::InvalidCommandCode => EpsError::InvalidCommandCode,
+            ErrorCode::ParameterMissing => EpsError::ParameterMissing,
+            ErrorCode::Parameterinvalid => EpsError::Parameterinvalid,
+            ErrorCode::UnavailableMode => EpsError::UnavailableMode,
+            ErrorCode::InvalidSystemType => EpsError::InvalidSystemType,
+            ErrorCode::InternalProcessing => EpsError::InternalProcessing,
+            ErrorCode::InvalidResetCause => EpsError::InvalidResetCause,
+            ErrorCode::InvalidEpsMode => EpsError::InvalidEpsMode,
+            ErrorCode::InvalidBusChannelState => EpsError::InvalidBusChannelState,
+            ErrorCode::Bincode => EpsError::Bincode(0),
+            ErrorCode::StateFile => EpsError::StateFile(std::io::ErrorKind::Other),
+            ErrorCode::Profile => EpsError::Profile(std::io::ErrorKind::Other),
+            ErrorCode::LockedOut => EpsError::LockedOut,
+            ErrorCode::VerificationFailed => EpsError::VerificationFailed {
+                expected: Output::U8(0),
+                actual: Output::U8(0),
+            },
+            ErrorCode::NotArmed => EpsError::NotArmed,
+            ErrorCode::DependencyViolation => EpsError::DependencyViolation,
+            ErrorCode::CrcMismatch => EpsError::CrcMismatch,
+            ErrorCode::InvalidChannels => EpsError::InvalidChannels(Vec::new()),
+            ErrorCode::ResponseTooShort => EpsError::ResponseTooShort(0, 0),
+            ErrorCode::Snapshot => EpsError::Snapshot(std::io::ErrorKind::Other),
+            ErrorCode::TypeMismatch => EpsError::TypeMismatch,
+            ErrorCode::Busy => EpsError::Busy,
+            ErrorCode::PolicyViolation => EpsError::PolicyViolation(0, 0),
+            ErrorCode::IcdDeviation => EpsError::IcdDeviation(String::new()),
+        }
+    }
+}
+
+impl EpsError {
+    // Code of the error, None for I2C errors which are passed on as Error::Io
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            EpsError::I2CError(_) | EpsError::I2CError2(_) => None,
+            EpsError::Err => Some(ErrorCode::Err),
+            EpsError::I2CSet => Some(ErrorCode::I2CSet),
+            EpsError::TransferError => Some(ErrorCode::TransferError),
+            EpsError::InvalidInput => Some(ErrorCode::InvalidInput),
+            EpsError::Rejected => Some(ErrorCode::Rejected),
+            EpsError::InvalidCommandCode => Some(ErrorCode::InvalidCommandCode),
+            EpsError::ParameterMissing => Some(ErrorCode::ParameterMissing),
+            EpsError::Parameterinvalid => Some(ErrorCode::Parameterinvalid),
+            EpsError::UnavailableMode => Some(ErrorCode::UnavailableMode),
+            EpsError::InvalidSystemType => Some(ErrorCode::InvalidSystemType),
+            EpsError::InternalProcessing => Some(ErrorCode::InternalProcessing),
+            EpsError::InvalidResetCause => Some(ErrorCode::InvalidResetCause),
+            EpsError::InvalidEpsMode => Some(ErrorCode::InvalidEpsMode),
+            EpsError::InvalidBusChannelState => Some(ErrorCode::InvalidBusChannelState),
+            EpsError::Bincode(_) => Some(ErrorCode::Bincode),
+            EpsError::StateFile(_) => Some(ErrorCode::StateFile),
+            EpsError::Profile(_) => Some(ErrorCode::Profile),
+            EpsError::LockedOut => Some(ErrorCode::LockedOut),
+            EpsError::VerificationFailed { .. } => Some(ErrorCode::VerificationFailed),
+            EpsError::NotArmed => Some(ErrorCode::NotArmed),
+            EpsError::DependencyViolation => Some(ErrorCode::DependencyViolation),
+            EpsError::CrcMismatch => Some(ErrorCode::CrcMismatch),
+            EpsError::InvalidChannels(_) => Some(ErrorCode::InvalidChannels),
+            EpsError::ResponseTooShort(_, _) => Some(ErrorCode::ResponseTooShort),
+            EpsError::Snapshot(_) => Some(ErrorCode::Snapshot),
+            EpsError::TypeMismatch => Some(ErrorCode::TypeMismatch),
+            EpsError::Busy => Some(ErrorCode::Busy),
+            EpsError::Stat { kind, .. } => Some(kind.code()),
+            EpsError::PolicyViolation(_, _) => Some(ErrorCode::PolicyViolation),
+            EpsError::IcdDeviation(_) => Some(ErrorCode::IcdDeviation),
+        }
+    }
 }
 
 /// All Errors in EpsError are converted to Error::ServiceError(u8)
+#[cfg(feature = "service")]
 impl From<EpsError> for Error {
     fn from(e: EpsError) -> Error {
         match e {
-            EpsError::Err => Error::ServiceError(0),
             EpsError::I2CError(io) => Error::from(io),
             EpsError::I2CError2(io) => Error::Io(io),
-            EpsError::I2CSet => Error::ServiceError(1),
-            EpsError::TransferError => Error::ServiceError(2),
-            EpsError::InvalidInput => Error::ServiceError(3),
-            // EpsError::Bincode(io) => Error::Bincode(io),
-            EpsError::Rejected => Error::ServiceError(4),
-            EpsError::InvalidCommandCode => Error::ServiceError(5),
-            EpsError::ParameterMissing => Error::ServiceError(6),
-            EpsError::Parameterinvalid => Error::ServiceError(7),
-            EpsError::UnavailableMode => Error::ServiceError(8),
-            EpsError::InvalidSystemType => Error::ServiceError(9),
-            EpsError::InternalProcessing => Error::ServiceError(10),
-            EpsError::InvalidResetCause => Error::ServiceError(11),
-            EpsError::InvalidEpsMode => Error::ServiceError(12),
-            EpsError::InvalidBusChannelState => Error::ServiceError(13),
-            // _ => Error::ServiceError(0),
+            e => Error::ServiceError(e.code().unwrap_or(ErrorCode::Err).into()),
         }
     }
 }
 
+#[cfg(feature = "service")]
 impl From<Error> for EpsError {
     fn from(e: Error) -> EpsError {
         match e {
-            Error::ServiceError(0) => EpsError::Err,
             Error::Io(io) => EpsError::I2CError2(io),
-            Error::ServiceError(1) => EpsError::I2CSet,
-            Error::ServiceError(2) => EpsError::TransferError,
-            Error::ServiceError(3) => EpsError::InvalidInput,
-            // Error::Bincode(io) => EpsError::Bincode(io),
-            Error::ServiceError(4) => EpsError::Rejected,
-            Error::ServiceError(5) => EpsError::InvalidCommandCode,
-            Error::ServiceError(6) => EpsError::ParameterMissing,
-            Error::ServiceError(7) => EpsError::Parameterinvalid,
-            Error::ServiceError(8) => EpsError::UnavailableMode,
-            Error::ServiceError(9) => EpsError::InvalidSystemType,
-            Error::ServiceError(10) => EpsError::InternalProcessing,
-            Error::ServiceError(11) => EpsError::InvalidResetCause,
-            Error::ServiceError(12) => EpsError::InvalidEpsMode,
-            Error::ServiceError(13) => EpsError::InvalidBusChannelState,
+            Error::ServiceError(n) => match ErrorCode::try_from(n) {
+                Ok(c) => EpsError::from(c),
+                Err(_) => EpsError::Err,
+            },
             _ => EpsError::Err,
         }
     }
 }
 
-// impl From<bincode::Error> for EpsError {
-//     fn from(b: bincode::Error) -> EpsError {
-//         match *b {
-//             bincode::ErrorKind::Io(_) => EpsError::Bincode(0),
-//             bincode::ErrorKind::InvalidUtf8Encoding(_) => EpsError::Bincode(1),
-//             bincode::ErrorKind::InvalidBoolEncoding(_) => EpsError::Bincode(2),
-//             bincode::ErrorKind::InvalidCharEncoding => EpsError::Bincode(3),
-//             bincode::ErrorKind::InvalidTagEncoding(_) => EpsError::Bincode(4),
-//             bincode::ErrorKind::DeserializeAnyNotSupported => EpsError::Bincode(5),
-//             bincode::ErrorKind::SizeLimit => EpsError::Bincode(6),
-//             bincode::ErrorKind::SequenceMustHaveLength => EpsError::Bincode(7),
-//             bincode::ErrorKind::Custom(_) => EpsError::Bincode(8),
-//         }
-//     }
-// }
+#[cfg(feature = "bincode")]
+impl From<bincode::Error> for EpsError {
+    fn from(b: bincode::Error) -> EpsError {
+        match *b {
+            bincode::ErrorKind::Io(_) => EpsError::Bincode(0),
+            bincode::ErrorKind::InvalidUtf8Encoding(_) => EpsError::Bincode(1),
+            bincode::ErrorKind::InvalidBoolEncoding(_) => EpsError::Bincode(2),
+            bincode::ErrorKind::InvalidCharEncoding => EpsError::Bincode(3),
+            bincode::ErrorKind::InvalidTagEncoding(_) => EpsError::Bincode(4),
+            bincode::ErrorKind::DeserializeAnyNotSupported => EpsError::Bincode(5),
+            bincode::ErrorKind::SizeLimit => EpsError::Bincode(6),
+            bincode::ErrorKind::SequenceMustHaveLength => EpsError::Bincode(7),
+            bincode::ErrorKind::Custom(_) => EpsError::Bincode(8),
+        }
+    }
+}
 
 // Result type to be implemented
 pub type EpsResult<T> = Result<T, EpsError>;
+
+#[cfg(all(test, feature = "service"))]
+mod tests {
+    use super::*;
+
+    // u8 -> ErrorCode -> EpsError -> Error::ServiceError -> EpsError
+    #[test]
+    fn error_codes_round_trip() {
+        let mut codes = 0;
+        for n in 0..=u8::MAX {
+            let code = match ErrorCode::try_from(n) {
+                Ok(code) => code,
+                Err(_) => continue,
+            };
+            // Codes are assigned without gaps
+            assert_eq!(n, codes);
+            codes += 1;
+            assert_eq!(u8::from(code), n);
+            let e = EpsError::from(code);
+            assert_eq!(e.code(), Some(code));
+            let service = Error::from(e.clone());
+            assert!(matches!(service, Error::ServiceError(x) if x == n));
+            assert_eq!(EpsError::from(service), e);
+        }
+        assert!(codes > 0);
+    }
+}
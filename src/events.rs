@@ -0,0 +1,193 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Callbacks on EPS mode, channel and safety events
+// The registry compares each observed status with the previous one and calls
+// the registered closures on a change, e.g. to mute the transmitter when the
+// EPS enters safety mode. Feed it from EventRegistry::poll() or with the
+// samples of an HkPoller (observe()). The first observation only sets the
+// reference, no events are raised for it.
+
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+use crate::poller::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SafetyEvent {
+    // EPS switched to safety mode, on command or autonomously
+    SafetyMode,
+    ContingencyMode,
+    // Channels newly latched off by overcurrent protection (bitmask)
+    Overcurrent { channels: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EpsEvent {
+    ModeChanged { from: EpsMode, to: EpsMode },
+    // Bitmasks of the channels switched since the previous observation
+    ChannelsChanged { switched_on: u32, switched_off: u32 },
+    Safety(SafetyEvent),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum EventKind {
+    Mode,
+    Channels,
+    Safety,
+}
+impl EpsEvent {
+    fn kind(&self) -> EventKind {
+        match self {
+            EpsEvent::ModeChanged { .. } => EventKind::Mode,
+            EpsEvent::ChannelsChanged { .. } => EventKind::Channels,
+            EpsEvent::Safety(_) => EventKind::Safety,
+        }
+    }
+}
+
+// Returned on registration, to remove the callback again
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CallbackId(u64);
+
+type Callback = Box<dyn FnMut(&EpsEvent) + Send>;
+
+#[derive(Default)]
+pub struct EventRegistry {
+    callbacks: Vec<(CallbackId, EventKind, Callback)>,
+    next_id: u64,
+    mode: Option<EpsMode>,
+    channels: Option<u32>,
+    ocf: Option<u32>,
+}
+
+impl EventRegistry {
+    pub fn new() -> Self {
+        EventRegistry::default()
+    }
+
+    pub fn on_mode_change<F: FnMut(&EpsEvent) + Send + 'static>(&mut self, f: F) -> CallbackId {
+        self.register(EventKind::Mode, Box::new(f))
+    }
+
+    pub fn on_channel_change<F: FnMut(&EpsEvent) + Send + 'static>(&mut self, f: F) -> CallbackId {
+        self.register(EventKind::Channels, Box::new(f))
+    }
+
+    pub fn on_safety_event<F: FnMut(&EpsEvent) + Send + 'static>(&mut self, f: F) -> CallbackId {
+        self.register(EventKind::Safety, Box::new(f))
+    }
+
+    fn register(&mut self, kind: EventKind, f: Callback) -> CallbackId {
+        let id = CallbackId(self.next_id);
+        self.next_id += 1;
+        self.callbacks.push((id, kind, f));
+        id
+    }
+
+    // Returns false if the callback wasn't registered
+    pub fn remove(&mut self, id: CallbackId) -> bool {
+        let len = self.callbacks.len();
+        self.callbacks.retain(|(i, _, _)| *i != id);
+        self.callbacks.len() != len
+    }
+
+    fn raise(&mut self, event: EpsEvent) {
+        let kind = event.kind();
+        for (_, k, f) in self.callbacks.iter_mut() {
+            if *k == kind {
+                f(&event);
+            }
+        }
+    }
+
+    pub fn observe_status(&mut self, status: &SystemStatus) {
+        let to = status.mode();
+        if let Some(from) = self.mode.replace(to.clone()) {
+            if from != to {
+                self.raise(EpsEvent::ModeChanged {
+                    from,
+                    to: to.clone(),
+                });
+                match to {
+                    EpsMode::Safety => self.raise(EpsEvent::Safety(SafetyEvent::SafetyMode)),
+                    EpsMode::Contigency => {
+                        self.raise(EpsEvent::Safety(SafetyEvent::ContingencyMode))
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    // Channel-on mask of channel 0 - 31
+    pub fn observe_channels(&mut self, on: u32) {
+        if let Some(prev) = self.channels.replace(on) {
+            if prev != on {
+                self.raise(EpsEvent::ChannelsChanged {
+                    switched_on: on & !prev,
+                    switched_off: prev & !on,
+                });
+            }
+        }
+    }
+
+    pub fn observe_overcurrent(&mut self, ocf: &OverCurrentFaultState) {
+        self.observe_channels(
+            ocf.stat_ch_on.bits() as u32 | (ocf.stat_ch_ext_on.bits() as u32) << 16,
+        );
+        self.observe_faults(
+            ocf.stat_ch_ocf.bits() as u32 | (ocf.stat_ch_ext_ocf.bits() as u32) << 16,
+        );
+    }
+
+    pub fn observe_piu_hk(&mut self, hk: &PIUHk) {
+        self.observe_channels(
+            hk.stat_ch_on.bits() as u32 | (hk.stat_ch_ext_on.bits() as u32) << 16,
+        );
+        self.observe_faults(
+            hk.stat_ch_ocf.bits() as u32 | (hk.stat_ch_ext_ocf.bits() as u32) << 16,
+        );
+    }
+
+    // Overcurrent latch-off mask of channel 0 - 31
+    fn observe_faults(&mut self, faulted: u32) {
+        let prev = self.ocf.replace(faulted).unwrap_or(faulted);
+        if faulted & !prev != 0 {
+            self.raise(EpsEvent::Safety(SafetyEvent::Overcurrent {
+                channels: faulted & !prev,
+            }));
+        }
+    }
+
+    // Feed a poller sample, failed reads are ignored
+    pub fn observe(&mut self, data: &PollData) {
+        match data {
+            PollData::Hk(HkData::SystemStatus(s)) => self.observe_status(s),
+            PollData::Hk(HkData::Piu(hk)) => self.observe_piu_hk(hk),
+            PollData::OverCurrent(ocf) => self.observe_overcurrent(ocf),
+            _ => (),
+        }
+    }
+
+    // Read system status and overcurrent state and raise the resulting events
+    pub fn poll(&mut self, eps: &Eps) -> EpsResult<()> {
+        let status = eps.system_status()?;
+        let ocf = eps.overcurrent_state()?;
+        self.observe_status(&status);
+        self.observe_overcurrent(&ocf);
+        Ok(())
+    }
+}
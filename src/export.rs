@@ -0,0 +1,122 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! CSV and flat JSON export of housekeeping
+// Both go through the serialized form of the structs, so new fields show up
+// without a mapper to maintain. Nested fields are joined with '.', array
+// elements get their index, e.g. "vip_input.volt" or "bp1.vip_bp_output.curr".
+// Fields are ordered by path.
+// The CSV columns come from a template value, so rows of frames with optional
+// parts (PBUHk with fewer battery packs) line up with the header and leave the
+// missing cells empty.
+
+use crate::objects::*;
+use serde::*;
+use serde_json::{Map, Value};
+
+// Leaf values of the serialized form by their field path
+pub fn flatten_json<T: Serialize>(value: &T) -> Map<String, Value> {
+    let mut fields = Map::new();
+    if let Ok(v) = serde_json::to_value(value) {
+        flatten(v, String::new(), &mut fields);
+    }
+    fields
+}
+
+fn flatten(v: Value, path: String, fields: &mut Map<String, Value>) {
+    let join = |k: &str| {
+        if path.is_empty() {
+            k.to_string()
+        } else {
+            format!("{}.{}", path, k)
+        }
+    };
+    match v {
+        Value::Object(map) => {
+            for (k, v) in map {
+                flatten(v, join(&k), fields);
+            }
+        }
+        Value::Array(a) => {
+            for (i, v) in a.into_iter().enumerate() {
+                flatten(v, join(&i.to_string()), fields);
+            }
+        }
+        v => {
+            fields.insert(path, v);
+        }
+    }
+}
+
+// Strings are quoted if they contain a separator, quote or line break
+fn csv_cell(v: Option<&Value>) -> String {
+    match v {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => {
+            if s.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.clone()
+            }
+        }
+        Some(v) => v.to_string(),
+    }
+}
+
+pub trait HkExport: Serialize + Default {
+    // Value the CSV columns are taken from, with all optional parts present
+    fn csv_template() -> Self {
+        Self::default()
+    }
+
+    fn csv_columns() -> Vec<String> {
+        flatten_json(&Self::csv_template())
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    fn csv_header() -> String {
+        Self::csv_columns().join(",")
+    }
+
+    fn to_csv_row(&self) -> String {
+        let fields = flatten_json(self);
+        Self::csv_columns()
+            .iter()
+            .map(|k| csv_cell(fields.get(k)))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    // JSON object with one key per field path
+    fn to_flat_json(&self) -> Value {
+        Value::Object(flatten_json(self))
+    }
+}
+
+impl HkExport for SystemStatus {}
+impl HkExport for PDUHk {}
+impl HkExport for PCUHk {}
+impl HkExport for PIUHk {}
+impl HkExport for PBUHk {
+    fn csv_template() -> Self {
+        PBUHk {
+            bp2: Some(BattPackData::default()),
+            bp3: Some(BattPackData::default()),
+            ..PBUHk::default()
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Firmware and ICD version reporting
+// The crate is written against IVID 7 of the ICD, units reporting another
+// interface version may interpret commands differently.
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::icd::transfer_error;
+use crate::*;
+use i2c_rs::Command;
+#[cfg(feature = "serde")]
+use serde::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FirmwareInfo {
+    // Identifiers echoed in the response header of a no-operation (0x02)
+    pub reply_stid: u8,
+    pub reply_ivid: u8,
+    pub reply_bid: u8,
+    // Read-only parameters of the unit
+    pub stid: u8,
+    pub ivid: u8,
+    pub bid_used: u8,
+    pub nvm_save_cntr: i64,
+    pub conf_param_changed: bool,
+    // Interface version the handle is configured to speak
+    pub expected_ivid: u8,
+}
+impl FirmwareInfo {
+    // False if the unit speaks another interface version than the crate
+    pub fn ivid_matches(&self) -> bool {
+        self.ivid == self.expected_ivid && self.reply_ivid == self.expected_ivid
+    }
+}
+
+impl Eps {
+    // Collect the identifiers and version related parameters of the unit
+    pub fn firmware_info(&self) -> EpsResult<FirmwareInfo> {
+        let cmd: u8 = self.stid();
+        let data: Vec<u8> = self.header(NO_OP_HEADER).to_vec();
+        let command = Command { cmd, data };
+
+        let rx_len = STAT_RESPONSE_LEN;
+        let delay = self.pacing().response_delay;
+
+        #[cfg(feature = "debug")]
+        eps_log! {"Firmware Info Cmd {:?}",command};
+
+        let header = match self.transfer(command, rx_len, delay) {
+            Ok(x) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"Firmware Info Response {:?}",x};
+                match check_stat(&x) {
+                    Ok(()) => x,
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(e) => return Err(transfer_error(e)),
+        };
+
+        let info = FirmwareInfo {
+            reply_stid: header[0],
+            reply_ivid: header[1],
+            reply_bid: header[3],
+            stid: self.get_config_para_read(ConfigParamRead::Stid)?.as_i64() as u8,
+            ivid: self.get_config_para_read(ConfigParamRead::Ivid)?.as_i64() as u8,
+            bid_used: self
+                .get_config_para_read(ConfigParamRead::BidUsed)?
+                .as_i64() as u8,
+            nvm_save_cntr: self
+                .get_config_para_read(ConfigParamRead::ConfNvmSaveCntr)?
+                .as_i64(),
+            conf_param_changed: self
+                .get_config_para_read(ConfigParamRead::ConfParamChanged)?
+                .as_i64()
+                != 0,
+            expected_ivid: self.ivid(),
+        };
+
+        if !info.ivid_matches() {
+            eps_log! {"Warning: EPS reports IVID {} (reply {}), configured IVID {}", info.ivid, info.reply_ivid, info.expected_ivid};
+        }
+        Ok(info)
+    }
+}
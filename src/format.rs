@@ -0,0 +1,46 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! defmt::Format for the types that can't derive it
+// Bitflag types are logged as their raw word, errors through their Debug output.
+
+use crate::error::*;
+use crate::objects::*;
+
+macro_rules! format_bits {
+    ($($t:ty),*) => {
+        $(
+            impl defmt::Format for $t {
+                fn format(&self, f: defmt::Formatter) {
+                    defmt::write!(f, "{}({=u16:#06x})", stringify!($t), self.bits())
+                }
+            }
+        )*
+    };
+}
+
+format_bits!(
+    BattPackStatus,
+    ChannelOnState,
+    ChannelOverCurrentState,
+    ExtChannelOnState,
+    ExtChannelOverCurrentState
+);
+
+impl defmt::Format for EpsError {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}", defmt::Debug2Format(self))
+    }
+}
@@ -0,0 +1,82 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Response freshness
+// The EPS sets the NEW bit (0x80) of STAT the first time a response is read.
+// A response without it is a re-read of an older one, e.g. because the unit
+// hasn't finished processing the command yet. ResponseMode::WaitForFresh
+// reads the response again (without resending the command) until the NEW bit
+// shows or the timeout expires.
+
+use crate::eps::*;
+use crate::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::io::{Error, ErrorKind};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ResponseMode {
+    // Take any response, fresh or not (default)
+    #[default]
+    Accept,
+    // Re-read stale responses until a fresh one is available
+    WaitForFresh,
+}
+
+impl Eps {
+    pub fn response_mode(&self) -> ResponseMode {
+        self.response_mode
+    }
+
+    pub fn set_response_mode(&mut self, mode: ResponseMode) {
+        self.response_mode = mode;
+    }
+
+    pub fn fresh_timeout(&self) -> Duration {
+        self.fresh_timeout
+    }
+
+    // Time to wait for a fresh response in ResponseMode::WaitForFresh
+    pub fn set_fresh_timeout(&mut self, timeout: Duration) {
+        self.fresh_timeout = timeout;
+    }
+
+    // Re-read a stale response x until it is fresh, within the fresh timeout
+    // and the deadline of the transaction
+    pub(crate) fn await_fresh(
+        &self,
+        stid: u8,
+        cc: u8,
+        rx_len: usize,
+        mut x: Vec<u8>,
+        deadline: Option<Instant>,
+    ) -> std::io::Result<Vec<u8>> {
+        // No reply is expected after a reset
+        if self.response_mode == ResponseMode::Accept || cc == SYS_RESET {
+            return Ok(x);
+        }
+        let start = Instant::now();
+        while x.get(4).is_some_and(|stat| stat & STAT_NEW == 0) {
+            if start.elapsed() >= self.fresh_timeout {
+                return Err(Error::new(ErrorKind::TimedOut, "no fresh response"));
+            }
+            sleep_within(Duration::from_millis(HK_MIN_DELAY_MS), deadline)?;
+            x = self.transport.read(stid, rx_len)?;
+        }
+        Ok(x)
+    }
+}
@@ -0,0 +1,88 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Transport on embedded-hal
+// HalTransport carries the command and response frames of Eps over any
+// embedded_hal::i2c::I2c bus, so the whole API (commands, parsing, pacing,
+// retries) runs on buses other than i2c-rs:
+//
+//     let eps = Eps::with_transport(HalTransport::new(i2c, 0x20))?;
+//
+// Bus errors are reported as io::Error with the embedded-hal error kind.
+
+use crate::transport::*;
+use embedded_hal::i2c::I2c;
+use std::cell::RefCell;
+use std::time::Duration;
+
+pub struct HalTransport<I2C> {
+    // EpsTransport takes &self, the bus needs &mut for every transfer
+    i2c: RefCell<I2C>,
+    addr: u8,
+}
+
+impl<I2C: I2c> HalTransport<I2C> {
+    // Transport to the EPS at the 7-bit address addr
+    pub fn new(i2c: I2C, addr: u8) -> Self {
+        HalTransport {
+            i2c: RefCell::new(i2c),
+            addr,
+        }
+    }
+
+    pub fn addr(&self) -> u8 {
+        self.addr
+    }
+
+    // Give back the bus
+    pub fn release(self) -> I2C {
+        self.i2c.into_inner()
+    }
+}
+
+fn bus_error<E: embedded_hal::i2c::Error>(e: E) -> std::io::Error {
+    std::io::Error::other(format!("I2C bus error: {:?}", e.kind()))
+}
+
+impl<I2C: I2c> EpsTransport for HalTransport<I2C> {
+    fn transfer(
+        &self,
+        command: Command,
+        rx_len: usize,
+        delay: Duration,
+    ) -> std::io::Result<Vec<u8>> {
+        let stid = command.cmd;
+        self.write(command)?;
+        std::thread::sleep(delay);
+        self.read(stid, rx_len)
+    }
+
+    fn read(&self, _stid: u8, rx_len: usize) -> std::io::Result<Vec<u8>> {
+        let mut response = vec![0u8; rx_len];
+        self.i2c
+            .borrow_mut()
+            .read(self.addr, &mut response)
+            .map_err(bus_error)?;
+        Ok(response)
+    }
+
+    fn write(&self, command: Command) -> std::io::Result<()> {
+        // Frame on the bus: STID, then IVID, CC, BID and the payload
+        let frame = [&[command.cmd][..], &command.data].concat();
+        self.i2c
+            .borrow_mut()
+            .write(self.addr, &frame)
+            .map_err(bus_error)
+    }
+}
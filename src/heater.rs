@@ -0,0 +1,79 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Battery heater control
+// Groups the heater config parameters of one battery pack: automatic heating
+// enable (AUTO_HEAT_ENA_BPx) and the on/off thresholds (LO/HI_THR_BPx_HEATER).
+// Writes go to RAM, use save_config() to keep them across resets.
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+
+pub struct HeaterControl<'a> {
+    eps: &'a Eps,
+    pack: BatteryPack,
+}
+
+impl Eps {
+    // Heater control of a battery pack, e.g. eps.heater(BatteryPack::Bp1).enable_auto()
+    pub fn heater(&self, pack: BatteryPack) -> HeaterControl<'_> {
+        HeaterControl { eps: self, pack }
+    }
+}
+
+impl HeaterControl<'_> {
+    pub fn pack(&self) -> BatteryPack {
+        self.pack
+    }
+
+    fn auto_param(&self) -> ConfigParamWriteI8 {
+        match self.pack {
+            BatteryPack::Bp1 => ConfigParamWriteI8::AutoHeatEnaBP1,
+            BatteryPack::Bp2 => ConfigParamWriteI8::AutoHeatEnaBP2,
+            BatteryPack::Bp3 => ConfigParamWriteI8::AutoHeatEnaBP3,
+        }
+    }
+
+    // Let the EPS switch the heater by the thresholds
+    pub fn enable_auto(&self) -> EpsResult<()> {
+        self.eps.set_config_para_i8(self.auto_param(), 1)?;
+        Ok(())
+    }
+
+    pub fn disable_auto(&self) -> EpsResult<()> {
+        self.eps.set_config_para_i8(self.auto_param(), 0)?;
+        Ok(())
+    }
+
+    pub fn auto_enabled(&self) -> EpsResult<bool> {
+        match self.eps.get_config_para_write(self.auto_param().into())? {
+            Output::I8(x) => Ok(x != 0),
+            _ => Err(EpsError::InvalidInput),
+        }
+    }
+
+    // Heater on (lo) and off (hi) thresholds in degC
+    pub fn thresholds(&self) -> EpsResult<(f32, f32)> {
+        self.eps.heater_thresholds(self.pack.number())
+    }
+
+    // Set the heater thresholds in degC, InvalidInput unless lo_c is below hi_c
+    pub fn set_thresholds(&self, lo_c: f32, hi_c: f32) -> EpsResult<()> {
+        self.eps
+            .set_heater_thresholds(self.pack.number(), lo_c, hi_c)
+    }
+}
@@ -0,0 +1,194 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Strict ICD compliance checks
+// Responses are normally indexed without looking at the header, which tolerates
+// firmware quirks in flight. In strict mode every response is checked against
+// the ICD before it is parsed, meant for commissioning and interface testing.
+// A deviation fails the transfer with EpsError::IcdDeviation describing it.
+
+use crate::eps::*;
+use crate::*;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+
+// Deviation found by check_icd(), carried through the io::Error of the
+// transfer so transfer_error() can report it as EpsError::IcdDeviation
+#[derive(Debug)]
+struct IcdDeviation(String);
+impl fmt::Display for IcdDeviation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl std::error::Error for IcdDeviation {}
+
+// Error of a failed transfer: the ICD deviation in strict mode, TransferError otherwise
+pub(crate) fn transfer_error(e: std::io::Error) -> EpsError {
+    match e
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<IcdDeviation>())
+    {
+        Some(IcdDeviation(d)) => EpsError::IcdDeviation(d.clone()),
+        None => EpsError::TransferError,
+    }
+}
+
+// Reserved bits of the STAT byte
+const STAT_RESERVED: u8 = 0x70;
+
+// Responses with a reserved byte between STAT and the data
+fn has_reserved_byte(cc: u8) -> bool {
+    matches!(
+        cc,
+        GET_PDU_OC_FAULT_STATE
+            | GET_PBU_ABF_PLACED_STATE
+            | GET_PDU_HK_DATA_RAW
+            | GET_PDU_HK_DATA_ENG
+            | GET_PDU_HK_DATA_AVRG
+            | GET_PBU_HK_DATA_RAW
+            | GET_PBU_HK_DATA_ENG
+            | GET_PBU_HK_DATA_AVRG
+            | GET_PCU_HK_DATA_RAW
+            | GET_PCU_HK_DATA_ENG
+            | GET_PCU_HK_DATA_AVRG
+            | GET_CONFIG_PARA
+            | SET_CONFIG_PARA
+            | RESET_CONFIG_PARA
+            | GET_PIU_HK_DATA_RAW
+            | GET_PIU_HK_DATA_ENG
+            | GET_PIU_HK_DATA_AVRG
+    )
+}
+
+// Response lengths of the ICD, including the reads that stop early on purpose
+const STAT_ONLY_LENS: &[usize] = &[STAT_RESPONSE_LEN];
+const OC_FAULT_STATE_LENS: &[usize] = &[
+    ChannelStates::RESPONSE_LEN,
+    OverCurrentFaultState::RESPONSE_LEN,
+];
+const PBU_HK_LENS: &[usize] = &[
+    PBUHk::response_len(1),
+    PBUHk::response_len(2),
+    PBUHk::response_len(3),
+];
+const PIU_HK_LENS: &[usize] = &[PIUHkShort::RESPONSE_LEN, PIUHk::RESPONSE_LEN];
+const CONFIG_PARA_LENS: &[usize] = &[
+    config_response_len(1),
+    config_response_len(2),
+    config_response_len(4),
+];
+
+// Valid response lengths to command code cc, None for codes the ICD doesn't define
+fn icd_response_lens(cc: u8) -> Option<&'static [usize]> {
+    match cc {
+        NO_OP
+        | CANCEL_OP
+        | WATCHDOG
+        | CORRECT_TIME
+        | RST_CAUSE_CNTR
+        | OUTPUT_BUS_GROUP_ON
+        | OUTPUT_BUS_GROUP_OFF
+        | OUTPUT_BUS_GROUP_STATE
+        | OUTPUT_BUS_CHANNEL_ON
+        | OUTPUT_BUS_CHANNEL_OFF
+        | SWITCH_TO_NOMINAL_MODE
+        | SWITCH_TO_SAFETY_MODE
+        | RESET_CONFIG_ALL
+        | LOAD_CONFIG
+        | SAVE_CONFIG => Some(STAT_ONLY_LENS),
+        GET_SYS_STATUS => Some(&[SystemStatus::RESPONSE_LEN]),
+        GET_PDU_OC_FAULT_STATE => Some(OC_FAULT_STATE_LENS),
+        GET_PBU_ABF_PLACED_STATE => Some(&[ABFState::RESPONSE_LEN]),
+        GET_PDU_HK_DATA_RAW | GET_PDU_HK_DATA_ENG | GET_PDU_HK_DATA_AVRG => {
+            Some(&[PDUHk::RESPONSE_LEN])
+        }
+        GET_PBU_HK_DATA_RAW | GET_PBU_HK_DATA_ENG | GET_PBU_HK_DATA_AVRG => Some(PBU_HK_LENS),
+        GET_PCU_HK_DATA_RAW | GET_PCU_HK_DATA_ENG | GET_PCU_HK_DATA_AVRG => {
+            Some(&[PCUHk::RESPONSE_LEN])
+        }
+        GET_PIU_HK_DATA_RAW | GET_PIU_HK_DATA_ENG | GET_PIU_HK_DATA_AVRG => Some(PIU_HK_LENS),
+        GET_CONFIG_PARA | SET_CONFIG_PARA | RESET_CONFIG_PARA => Some(CONFIG_PARA_LENS),
+        _ => None,
+    }
+}
+
+// Deviation of a response length from the ICD lengths of command code cc
+fn length_deviation(cc: u8, len: usize) -> Option<String> {
+    match icd_response_lens(cc) {
+        Some(lens) if !lens.contains(&len) => {
+            Some(format!("length {} expected one of {:?}", len, lens))
+        }
+        _ => None,
+    }
+}
+
+impl Eps {
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    // Enable or disable strict ICD checking of responses (disabled by default)
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    // Check a response to the command [stid] + data against the ICD.
+    // Header fields sent as wildcards (IVID 0x07, BID 0x00) are not compared.
+    pub(crate) fn check_icd(&self, stid: u8, data: &[u8], x: &[u8]) -> std::io::Result<()> {
+        if !self.strict {
+            return Ok(());
+        }
+        let (ivid, cc, bid) = match data {
+            [ivid, cc, bid, ..] => (*ivid, *cc, *bid),
+            _ => return Ok(()),
+        };
+        // No reply is expected after a reset
+        if cc == SYS_RESET {
+            return Ok(());
+        }
+        let deviation = if let Some(d) = length_deviation(cc, x.len()) {
+            Some(d)
+        } else if x.len() < 5 {
+            Some(format!("length {} shorter than the header", x.len()))
+        } else if x[0] != stid {
+            Some(format!("STID {:#04x} expected {:#04x}", x[0], stid))
+        } else if ivid != ALL_IVID && x[1] != ivid {
+            Some(format!("IVID {:#04x} expected {:#04x}", x[1], ivid))
+        } else if x[2] != cc.wrapping_add(1) {
+            Some(format!(
+                "RC {:#04x} expected {:#04x}",
+                x[2],
+                cc.wrapping_add(1)
+            ))
+        } else if bid != OVERRIDE_BID && x[3] != bid {
+            Some(format!("BID {:#04x} expected {:#04x}", x[3], bid))
+        } else if x[4] & STAT_RESERVED != 0 {
+            Some(format!("reserved STAT bits set {:#04x}", x[4]))
+        } else if has_reserved_byte(cc) && x.len() > 5 && x[5] != 0 {
+            Some(format!("reserved byte {:#04x}", x[5]))
+        } else {
+            None
+        };
+        match deviation {
+            Some(d) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"ICD deviation in response to {:#04x}: {}", cc, d};
+                Err(Error::new(ErrorKind::InvalidData, IcdDeviation(d)))
+            }
+            None => Ok(()),
+        }
+    }
+}
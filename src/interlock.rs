@@ -0,0 +1,62 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Arm/disarm interlock for destructive commands
+// sys_reset, shutdown_all and reset_all_conf are refused with
+// EpsError::NotArmed unless Eps::arm() was called shortly before. Arming is
+// single use, it is consumed by the first destructive command.
+
+use crate::eps::*;
+use crate::error::*;
+use std::time::{Duration, Instant};
+
+// Returned by Eps::arm(), tells until when the interlock is armed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ArmToken {
+    pub expires: Instant,
+}
+impl ArmToken {
+    pub fn remaining(&self) -> Duration {
+        self.expires.saturating_duration_since(Instant::now())
+    }
+    pub fn is_expired(&self) -> bool {
+        Instant::now() > self.expires
+    }
+}
+
+impl Eps {
+    // Allow one destructive command within the given time
+    pub fn arm(&self, valid_for: Duration) -> ArmToken {
+        let expires = Instant::now() + valid_for;
+        self.armed.set(Some(expires));
+        ArmToken { expires }
+    }
+
+    pub fn disarm(&self) {
+        self.armed.set(None);
+    }
+
+    pub fn is_armed(&self) -> bool {
+        matches!(self.armed.get(), Some(expires) if Instant::now() <= expires)
+    }
+
+    // Consume the arming, error if not armed or expired
+    pub(crate) fn check_armed(&self) -> EpsResult<()> {
+        match self.armed.take() {
+            Some(expires) if Instant::now() <= expires => Ok(()),
+            _ => Err(EpsError::NotArmed),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command journal
+// With a journal attached, every state changing command frame is appended to
+// a JSON lines file together with its timestamp and response STAT. The
+// journal can be replayed against another unit (e.g. after an EPS swap during
+// AIT) or a simulator.
+
+use crate::clock::obc_time;
+use crate::eps::*;
+use crate::error::*;
+use crate::icd::transfer_error;
+use crate::*;
+use i2c_rs::Command;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JournalEntry {
+    // OBC time in UNIX seconds
+    pub time: i64,
+    // STID the command was sent to
+    pub cmd: u8,
+    // Command frame [IVID, CC, BID] + payload
+    pub data: Vec<u8>,
+    pub rx_len: usize,
+    pub delay: Duration,
+    // STAT of the response, None if the transfer failed
+    pub stat: Option<u8>,
+}
+impl JournalEntry {
+    pub fn command_code(&self) -> u8 {
+        self.data.get(1).copied().unwrap_or_default()
+    }
+    // True if the EPS accepted the command
+    pub fn accepted(&self) -> bool {
+        self.stat.is_some_and(|stat| stat & !STAT_NEW == 0)
+    }
+}
+
+// Append-only journal file
+pub struct CommandJournal {
+    file: File,
+}
+impl CommandJournal {
+    pub fn open<P: AsRef<Path>>(path: P) -> EpsResult<Self> {
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Ok(CommandJournal { file }),
+            Err(e) => Err(EpsError::StateFile(e.kind())),
+        }
+    }
+
+    // Journaling must not get in the way of commanding, write errors are only reported in debug builds
+    pub(crate) fn append(
+        &self,
+        cmd: u8,
+        data: Vec<u8>,
+        rx_len: usize,
+        delay: Duration,
+        stat: Option<u8>,
+    ) {
+        let entry = JournalEntry {
+            time: obc_time(),
+            cmd,
+            data,
+            rx_len,
+            delay,
+            stat,
+        };
+        if let Ok(mut line) = serde_json::to_vec(&entry) {
+            line.push(b'\n');
+            let _res = (&self.file).write_all(&line);
+            #[cfg(feature = "debug")]
+            if let Err(e) = _res {
+                eps_log! {"Journal Write Error {:?}",e};
+            }
+        }
+    }
+
+    // Read all entries of a journal file
+    pub fn read<P: AsRef<Path>>(path: P) -> EpsResult<Vec<JournalEntry>> {
+        let file = match File::open(path) {
+            Ok(x) => x,
+            Err(e) => return Err(EpsError::StateFile(e.kind())),
+        };
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = match line {
+                Ok(x) => x,
+                Err(e) => return Err(EpsError::StateFile(e.kind())),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(x) => entries.push(x),
+                Err(_) => return Err(EpsError::StateFile(ErrorKind::InvalidData)),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+// Outcome of replaying one journal entry
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayResult {
+    pub entry: JournalEntry,
+    pub result: EpsResult<()>,
+}
+
+impl Eps {
+    // Record all state changing commands from now on, None stops journaling
+    pub fn set_journal(&mut self, journal: Option<CommandJournal>) {
+        self.journal = journal;
+    }
+
+    // Re-send the accepted commands of a journal in order. Rejected or failed
+    // commands of the original run are skipped. Replayed commands go through the
+    // normal transfer path, so they are journaled again if a journal is attached.
+    pub fn replay(&self, entries: &[JournalEntry]) -> Vec<ReplayResult> {
+        entries
+            .iter()
+            .filter(|e| e.accepted())
+            .map(|entry| {
+                let command = Command {
+                    cmd: entry.cmd,
+                    data: entry.data.clone(),
+                };
+                let result = match self.transfer(command, entry.rx_len, entry.delay) {
+                    Ok(x) => check_stat(&x),
+                    Err(e) => Err(transfer_error(e)),
+                };
+                ReplayResult {
+                    entry: entry.clone(),
+                    result,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,129 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON output styles for ground systems
+// The types serialize with their Rust field names. Consumers with other naming
+// conventions wrap the value in a Styled, which rewrites the serialized form
+// instead of every consumer carrying its own re-mapping layer.
+
+use serde::ser::Error;
+use serde::*;
+use serde_json::{Map, Value};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JsonStyle {
+    // Field names in camelCase, e.g. "vip_bp_output" -> "vipBpOutput"
+    pub camel_case: bool,
+    // Drop raw ADC fields (names ending in "_raw")
+    pub skip_raw: bool,
+    // Lift the fields of VIP blocks into the parent, e.g. "vip_input": {"volt": ..} -> "vip_input_volt"
+    pub flatten_vip: bool,
+}
+
+impl JsonStyle {
+    // Plain field names, as derived
+    pub fn native() -> Self {
+        JsonStyle::default()
+    }
+
+    // camelCase, no raw values, flat VIP fields
+    pub fn ground() -> Self {
+        JsonStyle {
+            camel_case: true,
+            skip_raw: true,
+            flatten_vip: true,
+        }
+    }
+
+    pub fn wrap<'a, T: Serialize>(&self, value: &'a T) -> Styled<'a, T> {
+        Styled {
+            value,
+            style: *self,
+        }
+    }
+
+    pub fn to_string<T: Serialize>(&self, value: &T) -> serde_json::Result<String> {
+        serde_json::to_string(&self.wrap(value))
+    }
+
+    // Rewrite an already serialized value
+    pub fn apply(&self, v: Value) -> Value {
+        match v {
+            Value::Object(map) => {
+                let mut out = Map::new();
+                for (k, v) in map {
+                    if self.skip_raw && k.ends_with("_raw") {
+                        continue;
+                    }
+                    let v = self.apply(v);
+                    match v {
+                        Value::Object(inner) if self.flatten_vip && k.starts_with("vip") => {
+                            for (ik, iv) in inner {
+                                out.insert(self.key(&format!("{}_{}", k, ik)), iv);
+                            }
+                        }
+                        v => {
+                            out.insert(self.key(&k), v);
+                        }
+                    }
+                }
+                Value::Object(out)
+            }
+            Value::Array(a) => Value::Array(a.into_iter().map(|v| self.apply(v)).collect()),
+            v => v,
+        }
+    }
+
+    fn key(&self, k: &str) -> String {
+        if self.camel_case {
+            camel_case(k)
+        } else {
+            k.to_string()
+        }
+    }
+}
+
+fn camel_case(k: &str) -> String {
+    let mut out = String::with_capacity(k.len());
+    let mut upper = false;
+    for c in k.chars() {
+        if c == '_' {
+            upper = !out.is_empty();
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// A value serialized in a JsonStyle
+// Goes through serde_json::Value, so only meant for self-describing formats
+pub struct Styled<'a, T> {
+    value: &'a T,
+    style: JsonStyle,
+}
+
+impl<'a, T: Serialize> Serialize for Styled<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let v = match serde_json::to_value(self.value) {
+            Ok(v) => v,
+            Err(e) => return Err(S::Error::custom(e)),
+        };
+        self.style.apply(v).serialize(serializer)
+    }
+}
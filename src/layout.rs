@@ -0,0 +1,78 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Response layouts
+// Wire length of the EPS responses, the rx_len of the commands is taken from
+// here instead of being repeated at every call. Lengths cover the complete
+// response frame: STID, IVID, RC, BID, STAT, the reserved byte (where the ICD
+// has one) and the data. Where the ICD response is longer than what is parsed
+// (e.g. counters of channels the unit doesn't have) the ICD length is used.
+
+use crate::objects::*;
+
+// Response only carrying the STAT (STID, IVID, RC, BID, STAT)
+pub(crate) const STAT_RESPONSE_LEN: usize = 5;
+// Config parameter responses: header, reserved byte and the parameter ID
+pub(crate) const CONFIG_RESPONSE_HEADER_LEN: usize = 8;
+
+// Response of a config parameter of width bytes (get/set/reset config parameter)
+pub(crate) const fn config_response_len(width: usize) -> usize {
+    CONFIG_RESPONSE_HEADER_LEN + width
+}
+
+pub trait ResponseLayout {
+    // Length of the complete response frame in bytes
+    const RESPONSE_LEN: usize;
+}
+
+impl ResponseLayout for SystemStatus {
+    const RESPONSE_LEN: usize = 36;
+}
+// Overcurrent fault state (0x42), counters of all 32 channels in the ICD
+impl ResponseLayout for OverCurrentFaultState {
+    const RESPONSE_LEN: usize = 78;
+}
+// First 10 bytes of the overcurrent fault state (0x42), up to the channel on states
+impl ResponseLayout for ChannelStates {
+    const RESPONSE_LEN: usize = 10;
+}
+impl ResponseLayout for ABFState {
+    const RESPONSE_LEN: usize = 8;
+}
+// PDU HK with VIP data of all 32 channels in the ICD
+impl ResponseLayout for PDUHk {
+    const RESPONSE_LEN: usize = 258;
+}
+// PBU HK of a unit with three battery packs, see PBUHk::response_len()
+impl ResponseLayout for PBUHk {
+    const RESPONSE_LEN: usize = PBUHk::response_len(3);
+}
+impl ResponseLayout for PCUHk {
+    const RESPONSE_LEN: usize = 72;
+}
+// PIU HK with daughterboard, VIP data of all 32 channels in the ICD
+impl ResponseLayout for PIUHk {
+    const RESPONSE_LEN: usize = 274;
+}
+impl ResponseLayout for PIUHkShort {
+    const RESPONSE_LEN: usize = PIU_HK_SHORT_LEN;
+}
+
+impl PBUHk {
+    // PBU HK response of a unit with packs battery packs, 22 bytes per pack
+    pub const fn response_len(packs: u8) -> usize {
+        18 + 22 * packs as usize
+    }
+}
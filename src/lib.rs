@@ -1,21 +1,235 @@
 // #![deny(missing_docs)]
+// Without std only the protocol constants and StatKind are built
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
 
+// Diagnostics output, routed through defmt with the defmt feature and printed otherwise
+#[cfg(feature = "std")]
+macro_rules! eps_log {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "defmt")]
+        defmt::debug!("{=str}", format!($($arg)*).as_str());
+        #[cfg(not(feature = "defmt"))]
+        println!($($arg)*);
+    };
+}
+
+#[cfg(feature = "std")]
+pub use crate::alarms::*;
+#[cfg(feature = "std")]
+pub use crate::api::*;
+#[cfg(feature = "bincode")]
+pub use crate::binary::*;
+#[cfg(feature = "std")]
+pub use crate::builder::*;
+#[cfg(feature = "std")]
+pub use crate::calibration::*;
+#[cfg(feature = "std")]
+pub use crate::clock::*;
+#[cfg(feature = "std")]
 pub use crate::config::*;
+#[cfg(feature = "std")]
+pub use crate::decimation::*;
+#[cfg(feature = "std")]
+pub use crate::dependency::*;
+#[cfg(feature = "serde")]
+pub use crate::deviation::*;
+#[cfg(feature = "std")]
 pub use crate::eps::*;
+#[cfg(feature = "std")]
 pub use crate::error::*;
+#[cfg(feature = "std")]
+pub use crate::events::*;
+#[cfg(feature = "serde")]
+pub use crate::export::*;
+#[cfg(feature = "std")]
+pub use crate::firmware::*;
+#[cfg(feature = "std")]
+pub use crate::fresh::*;
+#[cfg(feature = "embedded")]
+pub use crate::hal::*;
+#[cfg(feature = "std")]
+pub use crate::heater::*;
+#[cfg(feature = "std")]
+pub use crate::interlock::*;
+#[cfg(feature = "serde")]
+pub use crate::journal::*;
+#[cfg(feature = "serde")]
+pub use crate::json::*;
+#[cfg(feature = "std")]
+pub use crate::layout::*;
+#[cfg(feature = "std")]
+pub use crate::liveness::*;
+#[cfg(feature = "std")]
+pub use crate::lockout::*;
+#[cfg(feature = "std")]
+pub use crate::maintenance::*;
+#[cfg(feature = "std")]
+pub use crate::mock::*;
+#[cfg(feature = "std")]
 pub use crate::objects::*;
+#[cfg(feature = "std")]
+pub use crate::paraminfo::*;
+#[cfg(feature = "std")]
+pub use crate::policy::*;
+#[cfg(feature = "std")]
+pub use crate::poller::*;
+#[cfg(feature = "std")]
+pub use crate::power::*;
+#[cfg(feature = "serde")]
+pub use crate::profile::*;
+#[cfg(feature = "std")]
+pub use crate::queue::*;
+#[cfg(feature = "std")]
+pub use crate::resample::*;
+#[cfg(feature = "std")]
+pub use crate::resets::*;
+#[cfg(feature = "std")]
+pub use crate::retry::*;
+#[cfg(feature = "std")]
+pub use crate::safety::*;
+#[cfg(feature = "std")]
+pub use crate::sequence::*;
+#[cfg(feature = "std")]
+pub use crate::shared::*;
+#[cfg(feature = "std")]
+pub use crate::sim::*;
+#[cfg(feature = "std")]
+pub use crate::snapshot::*;
+#[cfg(feature = "std")]
+pub use crate::soc::*;
+pub use crate::stat::*;
+#[cfg(feature = "serde")]
+pub use crate::state::*;
+#[cfg(feature = "std")]
+pub use crate::supply::*;
+#[cfg(feature = "std")]
+pub use crate::telemetry::*;
+#[cfg(feature = "std")]
+pub use crate::transport::*;
+#[cfg(feature = "std")]
+pub use crate::units::*;
+#[cfg(feature = "std")]
+pub use crate::vd::*;
+#[cfg(feature = "std")]
+pub use crate::watch::*;
+#[cfg(feature = "std")]
+pub use crate::watchdog::*;
 
+#[cfg(feature = "std")]
+mod alarms;
+#[cfg(feature = "std")]
+mod api;
+#[cfg(feature = "bincode")]
+mod binary;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod calibration;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "std")]
 mod config;
+#[cfg(feature = "std")]
+mod decimation;
+#[cfg(feature = "std")]
+mod dependency;
+#[cfg(feature = "serde")]
+mod deviation;
+#[cfg(feature = "std")]
 mod eps;
+#[cfg(feature = "std")]
 mod error;
+#[cfg(feature = "std")]
+mod events;
+#[cfg(feature = "serde")]
+mod export;
+#[cfg(feature = "std")]
+mod firmware;
+#[cfg(all(feature = "std", feature = "defmt"))]
+mod format;
+#[cfg(feature = "std")]
+mod fresh;
+#[cfg(feature = "embedded")]
+mod hal;
+#[cfg(feature = "std")]
+mod heater;
+#[cfg(feature = "std")]
+mod icd;
+#[cfg(feature = "std")]
+mod interlock;
+#[cfg(feature = "serde")]
+mod journal;
+#[cfg(feature = "serde")]
+mod json;
+#[cfg(feature = "std")]
+mod layout;
+#[cfg(feature = "std")]
+mod liveness;
+#[cfg(feature = "std")]
+mod lockout;
+#[cfg(feature = "std")]
+mod maintenance;
+#[cfg(feature = "std")]
+mod mock;
+#[cfg(feature = "std")]
 mod objects;
+#[cfg(feature = "std")]
+mod paraminfo;
+#[cfg(feature = "std")]
+mod policy;
+#[cfg(feature = "std")]
+mod poller;
+#[cfg(feature = "std")]
+mod power;
+#[cfg(feature = "serde")]
+mod profile;
+#[cfg(feature = "std")]
+mod queue;
+#[cfg(feature = "std")]
+mod resample;
+#[cfg(feature = "std")]
+mod resets;
+#[cfg(feature = "std")]
+mod retry;
+#[cfg(feature = "std")]
+mod safety;
+#[cfg(feature = "std")]
+mod sequence;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+mod sim;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod soc;
+mod stat;
+#[cfg(feature = "serde")]
+mod state;
+#[cfg(feature = "std")]
+mod supply;
+#[cfg(feature = "std")]
+mod telemetry;
+#[cfg(feature = "std")]
+mod transport;
+#[cfg(feature = "std")]
+mod units;
+#[cfg(feature = "std")]
+mod vd;
+#[cfg(feature = "std")]
+mod verify;
+#[cfg(feature = "std")]
+mod watch;
+#[cfg(feature = "std")]
+mod watchdog;
 
 // ID's
-// const PDU_STID: u8 = 0x11;
-// const PBU_STID: u8 = 0x12;
-// const PCU_STID: u8 = 0x13;
+const PDU_STID: u8 = 0x11;
+const PBU_STID: u8 = 0x12;
+const PCU_STID: u8 = 0x13;
 const PIU_STID: u8 = 0x1A;
-// const OVERRIDE_STID: u8 = 0x00;
+const OVERRIDE_STID: u8 = 0x00;
 const ALL_IVID: u8 = 0x07;
 // const OVERRIDE_IVID: u8 = 0x00;
 // const PDU_BID: u8 = 0x00;
@@ -46,7 +260,7 @@ const SWITCH_TO_SAFETY_MODE: u8 = 0x32;
 // Data request commands
 const GET_SYS_STATUS: u8 = 0x40;
 const GET_PDU_OC_FAULT_STATE: u8 = 0x42;
-// const GET_PBU_ABF_PLACED_STATE: u8 = 0x44;
+const GET_PBU_ABF_PLACED_STATE: u8 = 0x44;
 const GET_PDU_HK_DATA_RAW: u8 = 0x50;
 const GET_PDU_HK_DATA_ENG: u8 = 0x52;
 const GET_PDU_HK_DATA_AVRG: u8 = 0x54;
@@ -70,20 +284,93 @@ const GET_PIU_HK_DATA_RAW: u8 = 0xA0;
 const GET_PIU_HK_DATA_ENG: u8 = 0xA2;
 const GET_PIU_HK_DATA_AVRG: u8 = 0xA4;
 
-// Most other functions return the STAT parameter. Write function here to check the the STAT for the error code
-fn match_stat(typ: u8) -> EpsResult<()> {
-    // is it <T, Error> ?
-    match typ {
-        0x00 => Ok(()),
-        0x80 => Ok(()),
-        0x01 => Err(EpsError::Rejected),
-        0x02 => Err(EpsError::InvalidCommandCode),
-        0x03 => Err(EpsError::ParameterMissing),
-        0x04 => Err(EpsError::Parameterinvalid),
-        0x05 => Err(EpsError::UnavailableMode),
-        0x06 => Err(EpsError::InvalidSystemType),
-        _ => Err(EpsError::InternalProcessing),
-        // Reserved values: 0x10, 0x20, 0x40
-        // NEW 0x80 set when the response is read for the first time
+// Command frame headers [IVID, CC, BID], built at compile time.
+// The STID is sent as the i2c command byte. Payload bytes noted after each header are appended at call time.
+const fn frame_header(cc: u8) -> [u8; 3] {
+    [ALL_IVID, cc, OVERRIDE_BID]
+}
+const SYS_RESET_HEADER: [u8; 3] = frame_header(SYS_RESET); // + reset key (u8)
+const NO_OP_HEADER: [u8; 3] = frame_header(NO_OP);
+const CANCEL_OP_HEADER: [u8; 3] = frame_header(CANCEL_OP);
+const WATCHDOG_HEADER: [u8; 3] = frame_header(WATCHDOG);
+const CORRECT_TIME_HEADER: [u8; 3] = frame_header(CORRECT_TIME); // + time correction (i32)
+const RST_CAUSE_CNTR_HEADER: [u8; 3] = frame_header(RST_CAUSE_CNTR); // + zero key (u8)
+const OUTPUT_BUS_GROUP_ON_HEADER: [u8; 3] = frame_header(OUTPUT_BUS_GROUP_ON); // + channel bitflag (u16)
+const OUTPUT_BUS_GROUP_OFF_HEADER: [u8; 3] = frame_header(OUTPUT_BUS_GROUP_OFF); // + channel bitflag (u16)
+const OUTPUT_BUS_GROUP_STATE_HEADER: [u8; 3] = frame_header(OUTPUT_BUS_GROUP_STATE); // + channel bitflag (u16)
+const OUTPUT_BUS_CHANNEL_ON_HEADER: [u8; 3] = frame_header(OUTPUT_BUS_CHANNEL_ON); // + channel index (u8)
+const OUTPUT_BUS_CHANNEL_OFF_HEADER: [u8; 3] = frame_header(OUTPUT_BUS_CHANNEL_OFF); // + channel index (u8)
+const SWITCH_TO_NOMINAL_MODE_HEADER: [u8; 3] = frame_header(SWITCH_TO_NOMINAL_MODE);
+const SWITCH_TO_SAFETY_MODE_HEADER: [u8; 3] = frame_header(SWITCH_TO_SAFETY_MODE);
+const GET_SYS_STATUS_HEADER: [u8; 3] = frame_header(GET_SYS_STATUS);
+const GET_PDU_OC_FAULT_STATE_HEADER: [u8; 3] = frame_header(GET_PDU_OC_FAULT_STATE);
+const GET_PBU_ABF_PLACED_STATE_HEADER: [u8; 3] = frame_header(GET_PBU_ABF_PLACED_STATE);
+const GET_PDU_HK_DATA_RAW_HEADER: [u8; 3] = frame_header(GET_PDU_HK_DATA_RAW);
+const GET_PDU_HK_DATA_ENG_HEADER: [u8; 3] = frame_header(GET_PDU_HK_DATA_ENG);
+const GET_PDU_HK_DATA_AVRG_HEADER: [u8; 3] = frame_header(GET_PDU_HK_DATA_AVRG);
+const GET_PBU_HK_DATA_RAW_HEADER: [u8; 3] = frame_header(GET_PBU_HK_DATA_RAW);
+const GET_PBU_HK_DATA_ENG_HEADER: [u8; 3] = frame_header(GET_PBU_HK_DATA_ENG);
+const GET_PBU_HK_DATA_AVRG_HEADER: [u8; 3] = frame_header(GET_PBU_HK_DATA_AVRG);
+const GET_PCU_HK_DATA_RAW_HEADER: [u8; 3] = frame_header(GET_PCU_HK_DATA_RAW);
+const GET_PCU_HK_DATA_ENG_HEADER: [u8; 3] = frame_header(GET_PCU_HK_DATA_ENG);
+const GET_PCU_HK_DATA_AVRG_HEADER: [u8; 3] = frame_header(GET_PCU_HK_DATA_AVRG);
+const GET_CONFIG_PARA_HEADER: [u8; 3] = frame_header(GET_CONFIG_PARA); // + parameter ID (u16)
+const SET_CONFIG_PARA_HEADER: [u8; 3] = frame_header(SET_CONFIG_PARA); // + parameter ID (u16) + value
+const RESET_CONFIG_PARA_HEADER: [u8; 3] = frame_header(RESET_CONFIG_PARA); // + parameter ID (u16)
+const RESET_CONFIG_ALL_HEADER: [u8; 3] = frame_header(RESET_CONFIG_ALL); // + config key (u8)
+const LOAD_CONFIG_HEADER: [u8; 3] = frame_header(LOAD_CONFIG); // + config key (u8)
+const SAVE_CONFIG_HEADER: [u8; 3] = frame_header(SAVE_CONFIG); // + config key (u8) + checksum (u16)
+const GET_PIU_HK_DATA_RAW_HEADER: [u8; 3] = frame_header(GET_PIU_HK_DATA_RAW);
+const GET_PIU_HK_DATA_ENG_HEADER: [u8; 3] = frame_header(GET_PIU_HK_DATA_ENG);
+const GET_PIU_HK_DATA_AVRG_HEADER: [u8; 3] = frame_header(GET_PIU_HK_DATA_AVRG);
+
+// Minimum delay between sending a command and reading its response (ICD), used for batched HK reads
+const HK_MIN_DELAY_MS: u64 = 20;
+
+// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF), as used for the config checksum
+pub(crate) fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data.iter() {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            if crc & 0x8000 != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
+
+// NEW flag of STAT, set when the response is read for the first time
+const STAT_NEW: u8 = 0x80;
+
+// Most other functions return the STAT parameter. Check it in the response frame,
+// a rejection carries the command code, taken from the response code (CC + 1),
+// and the parameter ID of the config parameter commands.
+#[cfg(feature = "std")]
+fn check_stat(x: &[u8]) -> EpsResult<()> {
+    let stat = match x.get(4) {
+        Some(stat) => *stat,
+        None => return Err(EpsError::TransferError),
+    };
+    match StatKind::from_stat(stat) {
+        None => Ok(()),
+        Some(kind) => {
+            let cmd = x[2].wrapping_sub(1);
+            let param = match cmd {
+                GET_CONFIG_PARA | SET_CONFIG_PARA | RESET_CONFIG_PARA => {
+                    x.get(6..8).map(|id| u16::from_le_bytes([id[0], id[1]]))
+                }
+                _ => None,
+            };
+            Err(EpsError::Stat {
+                cmd,
+                param,
+                stat,
+                kind,
+            })
+        }
     }
 }
@@ -0,0 +1,152 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Liveness heartbeat for the OBC supervisor
+// One call combines a ping, the time left until the EPS watchdog expires, the
+// age of the last successful HK read and the transfer error rate into a single
+// verdict, with the reason for each failed factor. Meant to be called every
+// few seconds, the error rate is taken over the transfers since the previous
+// heartbeat.
+
+use crate::config::*;
+use crate::eps::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LivenessLimits {
+    pub max_ping: Duration,
+    // Minimum time left before the watchdog expires
+    pub min_watchdog_margin: Duration,
+    pub max_hk_age: Duration,
+    // Failed / total transfers since the previous heartbeat, 0 - 1
+    pub max_error_rate: f32,
+}
+impl Default for LivenessLimits {
+    fn default() -> Self {
+        LivenessLimits {
+            max_ping: Duration::from_millis(200),
+            min_watchdog_margin: Duration::from_secs(60),
+            max_hk_age: Duration::from_secs(30),
+            max_error_rate: 0.2,
+        }
+    }
+}
+
+// A factor outside its limit
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LivenessIssue {
+    PingFailed,
+    PingSlow,
+    // No watchdog kick seen yet, or the timeout couldn't be read
+    WatchdogUnknown,
+    WatchdogMargin,
+    NoHk,
+    HkStale,
+    ErrorRate,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Liveness {
+    // True if no factor is outside its limit
+    pub alive: bool,
+    pub ping: Option<Duration>,
+    pub watchdog_margin: Option<Duration>,
+    pub hk_age: Option<Duration>,
+    pub error_rate: f32,
+    pub issues: Vec<LivenessIssue>,
+}
+
+pub struct LivenessMonitor {
+    pub limits: LivenessLimits,
+    // Watchdog timeout of the unit, read once from TtcWdgTimeoutUsed
+    watchdog_timeout: Option<Duration>,
+    last_stats: TransferStats,
+}
+
+impl LivenessMonitor {
+    pub fn new(limits: LivenessLimits) -> Self {
+        LivenessMonitor {
+            limits,
+            watchdog_timeout: None,
+            last_stats: TransferStats::default(),
+        }
+    }
+
+    pub fn heartbeat(&mut self, eps: &Eps) -> Liveness {
+        let mut issues = Vec::new();
+
+        let start = Instant::now();
+        let ping = match eps.eps_ping() {
+            Ok(()) => Some(start.elapsed()),
+            Err(_) => None,
+        };
+        match ping {
+            None => issues.push(LivenessIssue::PingFailed),
+            Some(p) if p > self.limits.max_ping => issues.push(LivenessIssue::PingSlow),
+            _ => (),
+        }
+
+        if self.watchdog_timeout.is_none() {
+            if let Ok(x) = eps.get_config_para_read(ConfigParamRead::TtcWdgTimeoutUsed) {
+                self.watchdog_timeout = Some(Duration::from_secs(x.as_i64().max(0) as u64));
+            }
+        }
+        let watchdog_margin = match (self.watchdog_timeout, eps.last_watchdog.get()) {
+            (Some(timeout), Some(kick)) => Some(timeout.saturating_sub(kick.elapsed())),
+            _ => None,
+        };
+        match watchdog_margin {
+            None => issues.push(LivenessIssue::WatchdogUnknown),
+            Some(m) if m < self.limits.min_watchdog_margin => {
+                issues.push(LivenessIssue::WatchdogMargin)
+            }
+            _ => (),
+        }
+
+        let hk_age = eps.last_hk.get().map(|t| t.elapsed());
+        match hk_age {
+            None => issues.push(LivenessIssue::NoHk),
+            Some(a) if a > self.limits.max_hk_age => issues.push(LivenessIssue::HkStale),
+            _ => (),
+        }
+
+        let stats = eps.transfer_stats();
+        let transfers = stats.transfers - self.last_stats.transfers;
+        let failures = stats.failures - self.last_stats.failures;
+        self.last_stats = stats;
+        let error_rate = if transfers == 0 {
+            0.0
+        } else {
+            failures as f32 / transfers as f32
+        };
+        if error_rate > self.limits.max_error_rate {
+            issues.push(LivenessIssue::ErrorRate);
+        }
+
+        Liveness {
+            alive: issues.is_empty(),
+            ping,
+            watchdog_margin,
+            hk_age,
+            error_rate,
+            issues,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Operational lockout windows
+// While a lockout is active, channel switching and/or config writes are
+// refused with EpsError::LockedOut before anything is sent to the EPS,
+// e.g. to protect a payload during imaging.
+
+use crate::clock::obc_time;
+use crate::eps::*;
+use crate::error::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+// Commands refused during a lockout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LockoutScope {
+    // Output bus group and single channel commands (0x10 - 0x18)
+    Channels,
+    // Config parameter set/reset, reset all and load (0x84 - 0x92)
+    Config,
+    All,
+}
+impl LockoutScope {
+    fn covers(&self, scope: LockoutScope) -> bool {
+        *self == LockoutScope::All || *self == scope
+    }
+}
+
+// When a lockout is active, times are OBC UNIX seconds
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LockoutWindow {
+    // From start (inclusive) to end (exclusive)
+    Between { start: i64, end: i64 },
+    // Until removed with Eps::remove_lockout
+    UntilRemoved,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Lockout {
+    pub name: String,
+    pub scope: LockoutScope,
+    pub window: LockoutWindow,
+}
+impl Lockout {
+    pub fn is_active_at(&self, t: i64) -> bool {
+        match self.window {
+            LockoutWindow::Between { start, end } => start <= t && t < end,
+            LockoutWindow::UntilRemoved => true,
+        }
+    }
+}
+
+impl Eps {
+    // Add a lockout, replacing a lockout with the same name
+    pub fn add_lockout(&mut self, lockout: Lockout) {
+        self.lockouts.retain(|l| l.name != lockout.name);
+        self.lockouts.push(lockout);
+    }
+
+    // Returns false if there was no lockout with that name
+    pub fn remove_lockout(&mut self, name: &str) -> bool {
+        let len = self.lockouts.len();
+        self.lockouts.retain(|l| l.name != name);
+        self.lockouts.len() != len
+    }
+
+    // Drop lockouts whose window has passed
+    pub fn prune_lockouts(&mut self) {
+        let now = obc_time();
+        self.lockouts.retain(|l| match l.window {
+            LockoutWindow::Between { end, .. } => end > now,
+            LockoutWindow::UntilRemoved => true,
+        });
+    }
+
+    pub fn lockouts(&self) -> &[Lockout] {
+        &self.lockouts
+    }
+
+    // First lockout covering scope that is active now
+    pub fn active_lockout(&self, scope: LockoutScope) -> Option<&Lockout> {
+        let now = obc_time();
+        self.lockouts
+            .iter()
+            .find(|l| l.scope.covers(scope) && l.is_active_at(now))
+    }
+
+    pub(crate) fn check_lockout(&self, scope: LockoutScope) -> EpsResult<()> {
+        match self.active_lockout(scope) {
+            Some(_l) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"Locked Out {:?}",_l};
+                Err(EpsError::LockedOut)
+            }
+            None => Ok(()),
+        }
+    }
+}
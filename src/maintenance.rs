@@ -0,0 +1,139 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Maintenance reminders
+// Keeps track of config changes, NVM saves and the EPS reset counters and
+// turns them into recommended maintenance actions for the operators.
+
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MaintenanceAction {
+    // Config was changed in RAM and not saved to NVM for longer than the limit (0x70 save)
+    SaveConfig { unsaved_for: u64 },
+    // A reset counter is close to wrapping around, reset the counters (0xC6)
+    ResetCounters { cause: ResetCause, count: u16 },
+    // Watchdog resets increased since the last check, check the watchdog kicking
+    CheckWatchdog { new_resets: u16 },
+    // Number of NVM saves passed the limit, avoid unnecessary saves to limit flash wear
+    LimitNvmSaves { saves: u32 },
+    // EPS has been up for longer than the limit without a reset
+    LongUptime { uptime: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MaintenanceLimits {
+    // Seconds a config change may stay unsaved
+    pub unsaved_config: u64,
+    // Reset counter value from which ResetCounters is recommended
+    pub reset_counter: u16,
+    // NVM saves from which LimitNvmSaves is reported
+    pub nvm_saves: u32,
+    // Uptime (seconds) from which LongUptime is reported, None to disable
+    pub uptime: Option<u32>,
+}
+impl Default for MaintenanceLimits {
+    fn default() -> Self {
+        MaintenanceLimits {
+            unsaved_config: 48 * 3600,
+            reset_counter: 0xF000,
+            nvm_saves: 10_000,
+            uptime: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MaintenanceTracker {
+    pub limits: MaintenanceLimits,
+    // OBC time (UNIX seconds) of the first unsaved config change
+    config_changed: Option<i64>,
+    nvm_saves: u32,
+    watchdog_resets: Option<u16>,
+}
+impl MaintenanceTracker {
+    pub fn new(limits: MaintenanceLimits) -> Self {
+        MaintenanceTracker {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    // Call after a config parameter was set or reset
+    pub fn note_config_change(&mut self, t: i64) {
+        if self.config_changed.is_none() {
+            self.config_changed = Some(t);
+        }
+    }
+
+    // Call after the config was saved to NVM
+    pub fn note_save(&mut self) {
+        self.config_changed = None;
+        self.nvm_saves = self.nvm_saves.saturating_add(1);
+    }
+
+    pub fn nvm_saves(&self) -> u32 {
+        self.nvm_saves
+    }
+
+    // Recommended actions at OBC time t given the latest system status (0x40)
+    pub fn check(&mut self, t: i64, status: &SystemStatus) -> Vec<MaintenanceAction> {
+        let mut actions = Vec::new();
+
+        if let Some(changed) = self.config_changed {
+            let unsaved_for = t.saturating_sub(changed).max(0) as u64;
+            if unsaved_for > self.limits.unsaved_config {
+                actions.push(MaintenanceAction::SaveConfig { unsaved_for });
+            }
+        }
+
+        for (cause, count) in status.reset_counters() {
+            if cause == ResetCause::Watchdog {
+                if let Some(previous) = self.watchdog_resets {
+                    let new_resets = count.wrapping_sub(previous);
+                    if new_resets != 0 && new_resets < 0x8000 {
+                        actions.push(MaintenanceAction::CheckWatchdog { new_resets });
+                    }
+                }
+                self.watchdog_resets = Some(count);
+            }
+            if count >= self.limits.reset_counter {
+                actions.push(MaintenanceAction::ResetCounters { cause, count });
+            }
+        }
+
+        if self.nvm_saves >= self.limits.nvm_saves {
+            actions.push(MaintenanceAction::LimitNvmSaves {
+                saves: self.nvm_saves,
+            });
+        }
+
+        if let Some(limit) = self.limits.uptime {
+            if status.uptime_secs() >= limit {
+                actions.push(MaintenanceAction::LongUptime {
+                    uptime: status.uptime_secs(),
+                });
+            }
+        }
+
+        actions
+    }
+}
@@ -0,0 +1,651 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-memory ICEPSv2 emulator
+// MockEps answers the ICEPSv2 command set without hardware: it keeps the
+// channel states, the operating mode, the reset counters, the configuration
+// parameters (active and saved copy) and replies with correctly framed
+// responses and STAT codes. It is a transport, so service code is tested
+// against the real Eps:
+//
+//     let mock = MockEps::new();
+//     let eps = Eps::with_transport(mock.clone());
+//
+// Clones share the emulated unit, the test keeps one to inspect it or to
+// inject rejections and stale responses. HK frames are zero-filled apart from
+// the channel states, unless a PowerModel is attached: the PBU and PCU
+// engineering and average HK are then the frames of the model, which follows
+// the channel commands and latches channels off when stepped with step(). The
+// overcurrent fault state reports its latches and fault counters.
+// Raw HK and the PDU HK stay zero-filled.
+
+use crate::transport::*;
+use crate::*;
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// STAT codes of the responses
+const STAT_ACCEPTED: u8 = 0x00;
+const STAT_INVALID_CC: u8 = 0x02;
+const STAT_PARAM_MISSING: u8 = 0x03;
+const STAT_PARAM_INVALID: u8 = 0x04;
+const STAT_UNAVAILABLE: u8 = 0x05;
+const STAT_INVALID_SYSTEM: u8 = 0x06;
+
+// Keys of the reset and NVM commands
+const RESET_KEY: u8 = 0xA6;
+const CONFIG_KEY: u8 = 0xA7;
+
+// Number of output bus channels of the ICEPSv2
+const CHANNELS: u8 = 17;
+
+// Config parameters read back from the emulated unit
+const TTC_WDG_TIMEOUT: u16 = 0x4000;
+const TTC_WDG_TIMEOUT_USED: u16 = 0x4810;
+
+#[derive(Debug)]
+struct MockState {
+    mode: EpsMode,
+    // Channel-on bitflags, bit n is channel n
+    channels: u32,
+    reset_cause: ResetCause,
+    rc_cnt_pwron: u16,
+    rc_cnt_wdg: u16,
+    rc_cnt_cmd: u16,
+    rc_cnt_mcu: u16,
+    rc_cnt_lowpwr: u16,
+    nvm_save_cnt: u16,
+    // ABF_PLACED bytes of connector 0 and 1
+    abf: [u8; 2],
+    booted: Instant,
+    last_command: Instant,
+    last_watchdog: Instant,
+    // Offset of the unit clock to the host clock, changed by CORRECT_TIME
+    time_offset: i64,
+    // Active parameters, the saved (NVM) copy and whether they differ
+    config: BTreeMap<u16, u32>,
+    saved: BTreeMap<u16, u32>,
+    conf_changed: bool,
+    // Source of the PBU and PCU HK, switched along with the channels
+    model: Option<PowerModel>,
+    // STAT returned instead of executing the next command
+    reject_next: Option<u8>,
+    commands: usize,
+    // Last response available to read, STAT without the NEW flag, and whether it has been read
+    response: Vec<u8>,
+    response_read: bool,
+    // Response of a command still being processed, after stale_left more reads
+    pending: Option<Vec<u8>>,
+    stale_left: usize,
+    stale_next: usize,
+}
+
+// Emulated ICEPSv2, usable as transport of Eps
+#[derive(Clone, Debug)]
+pub struct MockEps {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Default for MockEps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockEps {
+    // Unit after power on, in nominal mode with all channels off and the default configuration
+    pub fn new() -> Self {
+        let now = Instant::now();
+        MockEps {
+            state: Arc::new(Mutex::new(MockState {
+                mode: EpsMode::Nominal,
+                channels: 0,
+                reset_cause: ResetCause::PowerOn,
+                rc_cnt_pwron: 1,
+                rc_cnt_wdg: 0,
+                rc_cnt_cmd: 0,
+                rc_cnt_mcu: 0,
+                rc_cnt_lowpwr: 0,
+                nvm_save_cnt: 0,
+                abf: [0xAB, 0xAB],
+                booted: now,
+                last_command: now,
+                last_watchdog: now,
+                time_offset: 0,
+                config: BTreeMap::new(),
+                saved: BTreeMap::new(),
+                conf_changed: false,
+                model: None,
+                reject_next: None,
+                commands: 0,
+                response: vec![PIU_STID, ALL_IVID, 0, OVERRIDE_BID, STAT_ACCEPTED],
+                response_read: true,
+                pending: None,
+                stale_left: 0,
+                stale_next: 0,
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        // A panicking test thread must not take the other clones down with it
+        match self.state.lock() {
+            Ok(s) => s,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    // Channel-on bitflags of the emulated unit
+    pub fn channels(&self) -> u32 {
+        self.lock().channels
+    }
+
+    // Switch channels without a command, e.g. to emulate startup-enabled channels
+    pub fn set_channels(&self, channels: u32) {
+        self.lock()
+            .switch_channels(channels & ((1 << CHANNELS) - 1));
+    }
+
+    // Answer the PBU and PCU HK from the model, the unit takes over its channel states
+    pub fn set_power_model(&self, model: PowerModel) {
+        let mut state = self.lock();
+        state.channels = model.channels_on() & ((1 << CHANNELS) - 1);
+        state.model = Some(model);
+    }
+
+    // Copy of the attached model
+    pub fn power_model(&self) -> Option<PowerModel> {
+        self.lock().model.clone()
+    }
+
+    // Advance the attached model by dt, channels it latched off are off on the unit too
+    pub fn step(&self, dt: Duration) {
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        if let Some(model) = state.model.as_mut() {
+            model.step(dt);
+            state.channels = model.channels_on() & ((1 << CHANNELS) - 1);
+        }
+    }
+
+    pub fn mode(&self) -> EpsMode {
+        self.lock().mode.clone()
+    }
+
+    // Active value of a write parameter, parameters never set read as 0
+    pub fn config(&self, id: u16) -> u32 {
+        self.lock().config.get(&id).copied().unwrap_or(0)
+    }
+
+    // Answer the next command with the given STAT instead of executing it
+    pub fn reject_next(&self, stat: u8) {
+        self.lock().reject_next = Some(stat);
+    }
+
+    // Let the next command take n reads to process, the previous response is read until then
+    pub fn stale_next(&self, reads: usize) {
+        self.lock().stale_next = reads;
+    }
+
+    // Place or remove the arm-before-flight pin of connector 0 or 1
+    pub fn set_abf_placed(&self, connector: usize, placed: bool) {
+        if let Some(abf) = self.lock().abf.get_mut(connector) {
+            *abf = if placed { 0xAB } else { 0x00 };
+        }
+    }
+
+    // Number of commands received
+    pub fn commands(&self) -> usize {
+        self.lock().commands
+    }
+}
+
+impl MockState {
+    // Changes the channel-on bitflags, the model switches the channels that changed
+    fn switch_channels(&mut self, channels: u32) {
+        if let Some(model) = self.model.as_mut() {
+            let changed = self.channels ^ channels;
+            for ch in (0..CHANNELS).filter(|ch| changed & (1 << ch) != 0) {
+                model.switch(ch, channels & (1 << ch) != 0);
+            }
+        }
+        self.channels = channels;
+    }
+
+    // Read the current response, the NEW flag is set on the first read
+    fn read_response(&mut self, rx_len: usize) -> Vec<u8> {
+        if self.stale_left > 0 {
+            self.stale_left -= 1;
+        } else if let Some(response) = self.pending.take() {
+            self.response = response;
+            self.response_read = false;
+        }
+        let mut response = self.response.clone();
+        if !self.response_read {
+            response[4] |= STAT_NEW;
+            self.response_read = true;
+        }
+        response.resize(rx_len, 0);
+        response
+    }
+
+    fn reset(&mut self, cause: ResetCause) {
+        match cause {
+            ResetCause::PowerOn => self.rc_cnt_pwron = self.rc_cnt_pwron.wrapping_add(1),
+            ResetCause::Watchdog => self.rc_cnt_wdg = self.rc_cnt_wdg.wrapping_add(1),
+            ResetCause::Commanded => self.rc_cnt_cmd = self.rc_cnt_cmd.wrapping_add(1),
+            ResetCause::EpsUpset => self.rc_cnt_mcu = self.rc_cnt_mcu.wrapping_add(1),
+            ResetCause::EmergLowPwr => self.rc_cnt_lowpwr = self.rc_cnt_lowpwr.wrapping_add(1),
+        }
+        let now = Instant::now();
+        self.reset_cause = cause;
+        self.mode = EpsMode::Nominal;
+        self.switch_channels(0);
+        // Unsaved parameters are lost, the unit boots with the NVM copy
+        self.config = self.saved.clone();
+        self.conf_changed = false;
+        self.booted = now;
+        self.last_watchdog = now;
+    }
+
+    // The unit resets itself when the TTC watchdog isn't kicked in time
+    fn check_watchdog(&mut self) {
+        let timeout = self.config.get(&TTC_WDG_TIMEOUT).copied().unwrap_or(0);
+        if timeout != 0 && self.last_watchdog.elapsed() > Duration::from_secs(timeout as u64) {
+            self.reset(ResetCause::Watchdog);
+        }
+    }
+
+    fn unix_time(&self) -> i64 {
+        let host = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(_) => 0,
+        };
+        host + self.time_offset
+    }
+
+    fn read_param(&self, id: u16) -> Option<u32> {
+        if ConfigParamWrite::from_id(id).is_some() {
+            return Some(self.config.get(&id).copied().unwrap_or(0));
+        }
+        ConfigParamRead::from_id(id)?;
+        Some(match id {
+            0x1800 => self.conf_changed as u32,
+            0x2800 => PIU_STID as u32,
+            0x2801 => ALL_IVID as u32,
+            0x4801 => self.nvm_save_cnt as u32,
+            0x4803 => self.reset_cause.clone() as u32,
+            0x4804 => self.rc_cnt_pwron as u32,
+            0x4805 => self.rc_cnt_wdg as u32,
+            0x4806 => self.rc_cnt_cmd as u32,
+            0x4807 => self.rc_cnt_mcu as u32,
+            0x4808 => self.rc_cnt_lowpwr as u32,
+            TTC_WDG_TIMEOUT_USED => self.config.get(&TTC_WDG_TIMEOUT).copied().unwrap_or(0),
+            0x4811 => self.last_command.elapsed().as_secs().min(u16::MAX as u64) as u32,
+            _ => 0,
+        })
+    }
+
+    // Executes a command, returns the STAT and the response data after the header
+    fn execute(&mut self, cc: u8, payload: &[u8]) -> (u8, Vec<u8>) {
+        match cc {
+            NO_OP | CANCEL_OP => (STAT_ACCEPTED, Vec::new()),
+            WATCHDOG => {
+                self.last_watchdog = Instant::now();
+                (STAT_ACCEPTED, Vec::new())
+            }
+            SYS_RESET => match payload.first() {
+                None => (STAT_PARAM_MISSING, Vec::new()),
+                Some(&RESET_KEY) => {
+                    self.reset(ResetCause::Commanded);
+                    (STAT_ACCEPTED, Vec::new())
+                }
+                Some(_) => (STAT_PARAM_INVALID, Vec::new()),
+            },
+            CORRECT_TIME => match payload {
+                [a, b, c, d, ..] => {
+                    self.time_offset += i32::from_le_bytes([*a, *b, *c, *d]) as i64;
+                    (STAT_ACCEPTED, Vec::new())
+                }
+                _ => (STAT_PARAM_MISSING, Vec::new()),
+            },
+            RST_CAUSE_CNTR => match payload.first() {
+                None => (STAT_PARAM_MISSING, Vec::new()),
+                Some(&CONFIG_KEY) => {
+                    self.rc_cnt_pwron = 0;
+                    self.rc_cnt_wdg = 0;
+                    self.rc_cnt_cmd = 0;
+                    self.rc_cnt_mcu = 0;
+                    self.rc_cnt_lowpwr = 0;
+                    (STAT_ACCEPTED, Vec::new())
+                }
+                Some(_) => (STAT_PARAM_INVALID, Vec::new()),
+            },
+            OUTPUT_BUS_GROUP_ON | OUTPUT_BUS_GROUP_OFF | OUTPUT_BUS_GROUP_STATE => {
+                let mask = match payload {
+                    [lo, hi, ..] => u16::from_le_bytes([*lo, *hi]) as u32,
+                    _ => return (STAT_PARAM_MISSING, Vec::new()),
+                };
+                if self.mode == EpsMode::Safety {
+                    return (STAT_UNAVAILABLE, Vec::new());
+                }
+                // The group commands cover channel 0 to 15, channel 16 keeps its state
+                self.switch_channels(match cc {
+                    OUTPUT_BUS_GROUP_ON => self.channels | mask,
+                    OUTPUT_BUS_GROUP_OFF => self.channels & !mask,
+                    _ => (self.channels & !0xFFFF) | mask,
+                });
+                (STAT_ACCEPTED, Vec::new())
+            }
+            OUTPUT_BUS_CHANNEL_ON | OUTPUT_BUS_CHANNEL_OFF => match payload.first() {
+                None => (STAT_PARAM_MISSING, Vec::new()),
+                Some(ch) if *ch >= CHANNELS => (STAT_PARAM_INVALID, Vec::new()),
+                Some(_) if self.mode == EpsMode::Safety => (STAT_UNAVAILABLE, Vec::new()),
+                Some(ch) => {
+                    if cc == OUTPUT_BUS_CHANNEL_ON {
+                        self.switch_channels(self.channels | 1 << ch);
+                    } else {
+                        self.switch_channels(self.channels & !(1 << ch));
+                    }
+                    (STAT_ACCEPTED, Vec::new())
+                }
+            },
+            SWITCH_TO_NOMINAL_MODE => {
+                self.mode = EpsMode::Nominal;
+                (STAT_ACCEPTED, Vec::new())
+            }
+            SWITCH_TO_SAFETY_MODE => {
+                self.mode = EpsMode::Safety;
+                (STAT_ACCEPTED, Vec::new())
+            }
+            GET_SYS_STATUS => (STAT_ACCEPTED, self.system_status()),
+            GET_PDU_OC_FAULT_STATE => {
+                let mut data = vec![0u8; 73];
+                data[1..3].copy_from_slice(&(self.channels as u16).to_le_bytes());
+                data[3..5].copy_from_slice(&((self.channels >> 16) as u16).to_le_bytes());
+                // Latches and fault counters come from the model, without one there are none
+                if let Some(oc) = self.model.as_ref().map(PowerModel::overcurrent_state) {
+                    data[5..7].copy_from_slice(&u16::from(oc.stat_ch_ocf).to_le_bytes());
+                    data[7..9].copy_from_slice(&u16::from(oc.stat_ch_ext_ocf).to_le_bytes());
+                    for (ch, cnt) in oc.ocf_cnt.iter().enumerate() {
+                        data[9 + 2 * ch..11 + 2 * ch].copy_from_slice(&cnt.to_le_bytes());
+                    }
+                }
+                (STAT_ACCEPTED, data)
+            }
+            GET_PBU_ABF_PLACED_STATE => (STAT_ACCEPTED, vec![0, self.abf[0], self.abf[1]]),
+            GET_PIU_HK_DATA_RAW | GET_PIU_HK_DATA_ENG | GET_PIU_HK_DATA_AVRG => {
+                // Offsets as in the PIUHk conversion, less the 5 byte header
+                let mut data = vec![0u8; 269];
+                data[17..19].copy_from_slice(&(self.channels as u16).to_le_bytes());
+                data[169..171].copy_from_slice(&((self.channels >> 16) as u16).to_le_bytes());
+                (STAT_ACCEPTED, data)
+            }
+            GET_PBU_HK_DATA_ENG | GET_PBU_HK_DATA_AVRG if self.model.is_some() => {
+                let hk = self.model.as_ref().map(PowerModel::pbu_hk);
+                (
+                    STAT_ACCEPTED,
+                    hk.map(|hk| pbu_hk_data(&hk)).unwrap_or_default(),
+                )
+            }
+            GET_PCU_HK_DATA_ENG | GET_PCU_HK_DATA_AVRG if self.model.is_some() => {
+                let hk = self.model.as_ref().map(PowerModel::pcu_hk);
+                (
+                    STAT_ACCEPTED,
+                    hk.map(|hk| pcu_hk_data(&hk)).unwrap_or_default(),
+                )
+            }
+            GET_PDU_HK_DATA_RAW | GET_PDU_HK_DATA_ENG | GET_PDU_HK_DATA_AVRG
+            | GET_PBU_HK_DATA_RAW | GET_PBU_HK_DATA_ENG | GET_PBU_HK_DATA_AVRG
+            | GET_PCU_HK_DATA_RAW | GET_PCU_HK_DATA_ENG | GET_PCU_HK_DATA_AVRG => {
+                (STAT_ACCEPTED, Vec::new())
+            }
+            GET_CONFIG_PARA => {
+                let id = match payload {
+                    [lo, hi, ..] => u16::from_le_bytes([*lo, *hi]),
+                    _ => return (STAT_PARAM_MISSING, Vec::new()),
+                };
+                match self.read_param(id) {
+                    Some(v) => (STAT_ACCEPTED, param_response(id, v)),
+                    None => (STAT_PARAM_INVALID, param_response(id, 0)),
+                }
+            }
+            SET_CONFIG_PARA => {
+                let (id, value) = match payload {
+                    [lo, hi, value @ ..] => (u16::from_le_bytes([*lo, *hi]), value),
+                    _ => return (STAT_PARAM_MISSING, Vec::new()),
+                };
+                let len = match ConfigParamWrite::from_id(id) {
+                    Some(p) => p.get_len(),
+                    None => return (STAT_PARAM_INVALID, param_response(id, 0)),
+                };
+                if value.len() < len {
+                    return (STAT_PARAM_MISSING, param_response(id, 0));
+                }
+                let mut v = [0u8; 4];
+                v[..len].copy_from_slice(&value[..len]);
+                let v = u32::from_le_bytes(v);
+                self.config.insert(id, v);
+                self.conf_changed = true;
+                (STAT_ACCEPTED, param_response(id, v))
+            }
+            RESET_CONFIG_PARA => {
+                let id = match payload {
+                    [lo, hi, ..] => u16::from_le_bytes([*lo, *hi]),
+                    _ => return (STAT_PARAM_MISSING, Vec::new()),
+                };
+                if ConfigParamWrite::from_id(id).is_none() {
+                    return (STAT_PARAM_INVALID, param_response(id, 0));
+                }
+                self.config.remove(&id);
+                self.conf_changed = true;
+                (STAT_ACCEPTED, param_response(id, 0))
+            }
+            RESET_CONFIG_ALL | LOAD_CONFIG | SAVE_CONFIG => match payload.first() {
+                None => (STAT_PARAM_MISSING, Vec::new()),
+                Some(&CONFIG_KEY) => {
+                    match cc {
+                        RESET_CONFIG_ALL => self.config.clear(),
+                        LOAD_CONFIG => self.config = self.saved.clone(),
+                        _ => {
+                            self.saved = self.config.clone();
+                            self.nvm_save_cnt = self.nvm_save_cnt.wrapping_add(1);
+                        }
+                    }
+                    self.conf_changed = cc == RESET_CONFIG_ALL;
+                    (STAT_ACCEPTED, Vec::new())
+                }
+                Some(_) => (STAT_PARAM_INVALID, Vec::new()),
+            },
+            _ => (STAT_INVALID_CC, Vec::new()),
+        }
+    }
+
+    // 0x40 response data after the header, see SystemStatus
+    fn system_status(&self) -> Vec<u8> {
+        let uptime = self.booted.elapsed().as_secs() as u32;
+        let prevcmd = self.last_command.elapsed().as_secs().min(u16::MAX as u64) as u16;
+        let unix = self.unix_time();
+        let (year, month, day) = civil_from_days(unix.div_euclid(86400));
+        let secs = unix.rem_euclid(86400);
+
+        let mut data = vec![
+            self.mode.clone() as u8,
+            self.conf_changed as u8,
+            self.reset_cause.clone() as u8,
+        ];
+        data.extend_from_slice(&uptime.to_le_bytes());
+        // Error code of the last internal error, the emulator has none
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&self.rc_cnt_pwron.to_le_bytes());
+        data.extend_from_slice(&self.rc_cnt_wdg.to_le_bytes());
+        data.extend_from_slice(&self.rc_cnt_cmd.to_le_bytes());
+        data.extend_from_slice(&self.rc_cnt_mcu.to_le_bytes());
+        data.extend_from_slice(&self.rc_cnt_lowpwr.to_le_bytes());
+        data.extend_from_slice(&prevcmd.to_le_bytes());
+        data.extend_from_slice(&(unix as u32).to_le_bytes());
+        data.extend_from_slice(&[
+            (year - 2000).clamp(0, 255) as u8,
+            month,
+            day,
+            (secs / 3600) as u8,
+            (secs / 60 % 60) as u8,
+            (secs % 60) as u8,
+        ]);
+        data
+    }
+}
+
+// Parameter responses carry a reserved byte, the ID and the value in the parameter's length
+fn param_response(id: u16, value: u32) -> Vec<u8> {
+    let len = match id & 0x7000 {
+        0x6000 => 4,
+        0x3000 | 0x4000 => 2,
+        _ => 1,
+    };
+    [
+        &[0u8][..],
+        &id.to_le_bytes()[..],
+        &value.to_le_bytes()[..len],
+    ]
+    .concat()
+}
+
+// HK data after the header as the unit sends it: a reserved byte, then the fields
+// in the order of the conversions in objects.rs, power in 10 mW steps
+fn vip_data(vip: &VIPData) -> Vec<u8> {
+    let pwr = (vip.pwr / 10).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    [
+        vip.volt.to_le_bytes(),
+        vip.curr.to_le_bytes(),
+        pwr.to_le_bytes(),
+    ]
+    .concat()
+}
+
+fn pbu_hk_data(hk: &PBUHk) -> Vec<u8> {
+    let mut data = vec![0u8];
+    data.extend_from_slice(&hk.volt_brdsup.to_le_bytes());
+    data.extend_from_slice(&hk.temp.to_le_bytes());
+    data.extend(vip_data(&hk.vip_input));
+    data.extend_from_slice(&u16::from(hk.stat_bu).to_le_bytes());
+    for bp in [Some(&hk.bp1), hk.bp2.as_ref(), hk.bp3.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        data.extend(vip_data(&bp.vip_bp_output));
+        data.extend_from_slice(&u16::from(bp.stat_bp).to_le_bytes());
+        for v in bp.cell_voltages() {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        for t in [bp.bat_temp1, bp.bat_temp2, bp.bat_temp3] {
+            data.extend_from_slice(&t.to_le_bytes());
+        }
+    }
+    data
+}
+
+fn pcu_hk_data(hk: &PCUHk) -> Vec<u8> {
+    let mut data = vec![0u8];
+    data.extend_from_slice(&hk.volt_brdsup.to_le_bytes());
+    data.extend_from_slice(&hk.temp.to_le_bytes());
+    data.extend(vip_data(&hk.vip_output));
+    for ccd in [&hk.ccd1, &hk.ccd2, &hk.ccd3, &hk.ccd4] {
+        data.extend(vip_data(&ccd.vip_cc_output));
+        for v in [
+            ccd.volt_in_mppt,
+            ccd.curr_in_mppt,
+            ccd.volt_out_mppt,
+            ccd.curr_out_mppt,
+        ] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    data
+}
+
+// Days since 1970-01-01 to (year, month, day) in the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+impl EpsTransport for MockEps {
+    fn transfer(
+        &self,
+        command: Command,
+        rx_len: usize,
+        _delay: Duration,
+    ) -> std::io::Result<Vec<u8>> {
+        let stid = command.cmd;
+        self.write(command)?;
+        self.read(stid, rx_len)
+    }
+
+    fn read(&self, stid: u8, rx_len: usize) -> std::io::Result<Vec<u8>> {
+        if stid != PIU_STID {
+            return Err(Error::new(ErrorKind::NotFound, "no unit with this STID"));
+        }
+        Ok(self.lock().read_response(rx_len))
+    }
+
+    fn write(&self, command: Command) -> std::io::Result<()> {
+        let (ivid, cc, bid, payload) = match command.data.as_slice() {
+            [ivid, cc, bid, payload @ ..] => (*ivid, *cc, *bid, payload),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "frame shorter than header",
+                ))
+            }
+        };
+        // Frames for another system type aren't answered, like on the bus
+        if command.cmd != PIU_STID {
+            return Err(Error::new(ErrorKind::NotFound, "no unit with this STID"));
+        }
+
+        let mut state = self.lock();
+        state.commands += 1;
+        state.check_watchdog();
+        let (stat, data) = if ivid != ALL_IVID || bid != OVERRIDE_BID {
+            (STAT_INVALID_SYSTEM, Vec::new())
+        } else {
+            match state.reject_next.take() {
+                Some(stat) => (stat, Vec::new()),
+                None => state.execute(cc, payload),
+            }
+        };
+        state.last_command = Instant::now();
+
+        let response = [
+            &[PIU_STID, ALL_IVID, cc.wrapping_add(1), OVERRIDE_BID, stat][..],
+            &data[..],
+        ]
+        .concat();
+        state.stale_left = std::mem::take(&mut state.stale_next);
+        state.pending = Some(response);
+        Ok(())
+    }
+}
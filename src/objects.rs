@@ -24,11 +24,28 @@
 // Input enumerations
 // System Type Identifier (STID)
 
+use crate::calibration::*;
 use crate::error::*;
+use bitflags::bitflags;
+#[cfg(feature = "serde")]
 use serde::*;
+use std::fmt;
+use std::time::Duration;
+#[cfg(feature = "strum")]
 use strum_macros::{Display, EnumIter, EnumString};
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, EnumIter, Display)]
+// Telemetry conversions check the length first instead of panicking on a short read
+pub(crate) fn check_len(v: &[u8], expected: usize) -> EpsResult<()> {
+    if v.len() < expected {
+        return Err(EpsError::ResponseTooShort(expected, v.len()));
+    }
+    Ok(())
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum StID {
     // Power Distribution Unit System Type Identifier
     PduStid,
@@ -43,19 +60,10 @@ pub enum StID {
 }
 
 // Output Bus Group
-#[derive(
-    Copy,
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum BusGroup {
     #[default]
     BusGroupOn,
@@ -63,7 +71,9 @@ pub enum BusGroup {
     BusGroupState,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BusChannelState {
     pub ch00: BusChannel,
     pub ch01: BusChannel,
@@ -187,89 +197,73 @@ impl BusChannelState {
         }
         u
     }
-    pub fn state(&self, mut u: u16) -> EpsResult<u16> {
-        // let mut u = 0u16;
-        match self.ch00 {
-            BusChannel::On => u |= 0x0001,
-            BusChannel::Off => u &= !0x0001,
-            BusChannel::Keep => (),
-        }
-        match self.ch01 {
-            BusChannel::On => u |= 0x0002,
-            BusChannel::Off => u &= !0x0002,
-            BusChannel::Keep => (),
-        }
-        match self.ch02 {
-            BusChannel::On => u |= 0x0004,
-            BusChannel::Off => u &= !0x0004,
-            BusChannel::Keep => (),
-        }
-        match self.ch03 {
-            BusChannel::On => u |= 0x0008,
-            BusChannel::Off => u &= !0x0008,
-            BusChannel::Keep => (),
-        }
-        match self.ch04 {
-            BusChannel::On => u |= 0x0010,
-            BusChannel::Off => u &= !0x0010,
-            BusChannel::Keep => (),
-        }
-        match self.ch05 {
-            BusChannel::On => u |= 0x0020,
-            BusChannel::Off => u &= !0x0020,
-            BusChannel::Keep => (),
-        }
-        match self.ch06 {
-            BusChannel::On => u |= 0x0040,
-            BusChannel::Off => u &= !0x0040,
-            BusChannel::Keep => (),
-        }
-        match self.ch07 {
-            BusChannel::On => u |= 0x0080,
-            BusChannel::Off => u &= !0x0080,
-            BusChannel::Keep => (),
-        }
-        match self.ch08 {
-            BusChannel::On => u |= 0x0100,
-            BusChannel::Off => u &= !0x0100,
-            BusChannel::Keep => (),
-        }
-        match self.ch09 {
-            BusChannel::On => u |= 0x0200,
-            BusChannel::Off => u &= !0x0200,
-            BusChannel::Keep => (),
-        }
-        match self.ch10 {
-            BusChannel::On => u |= 0x0400,
-            BusChannel::Off => u &= !0x0400,
-            BusChannel::Keep => (),
-        }
-        match self.ch11 {
-            BusChannel::On => u |= 0x0800,
-            BusChannel::Off => u &= !0x0800,
-            BusChannel::Keep => (),
-        }
-        match self.ch12 {
-            BusChannel::On => u |= 0x1000,
-            BusChannel::Off => u &= !0x1000,
-            BusChannel::Keep => (),
-        }
-        match self.ch13 {
-            BusChannel::On => u |= 0x2000,
-            BusChannel::Off => u &= !0x2000,
-            BusChannel::Keep => (),
-        }
-        match self.ch14 {
-            BusChannel::On => u |= 0x4000,
-            BusChannel::Off => u &= !0x4000,
-            BusChannel::Keep => (),
-        }
-        match self.ch15 {
-            BusChannel::On => u |= 0x8000,
-            BusChannel::Off => u &= !0x8000,
-            BusChannel::Keep => (),
-        }
-        Ok(u)
+    // Tri-state mask semantics for Output Bus Group State (0x14):
+    // on_mask bits are driven on, off_mask bits are driven off and keep_mask bits
+    // are left as they currently are. Every channel is in exactly one of the three.
+    pub fn on_mask(&self) -> u16 {
+        self.on()
+    }
+    pub fn off_mask(&self) -> u16 {
+        self.off()
+    }
+    pub fn keep_mask(&self) -> u16 {
+        !(self.on() | self.off())
+    }
+    // Build from explicit on/off masks, channels in neither are kept.
+    // A channel can't be both on and off.
+    pub fn from_masks(on_mask: u16, off_mask: u16) -> EpsResult<Self> {
+        if on_mask & off_mask != 0 {
+            return Err(EpsError::InvalidChannels(mask_channels(on_mask & off_mask)));
+        }
+        let mut bus_channel_state = BusChannelState::default();
+        for (i, ch) in bus_channel_state.channels_mut().into_iter().enumerate() {
+            if on_mask & (1 << i) != 0 {
+                *ch = BusChannel::On;
+            } else if off_mask & (1 << i) != 0 {
+                *ch = BusChannel::Off;
+            }
+        }
+        Ok(bus_channel_state)
+    }
+    // Check the channels can be commanded with typ_group: a group on command
+    // can't switch channels off and a group off command can't switch them on.
+    // The offending channels are returned in EpsError::InvalidChannels.
+    pub fn validate(&self, typ_group: BusGroup) -> EpsResult<()> {
+        let invalid = match typ_group {
+            BusGroup::BusGroupOn => self.off_mask(),
+            BusGroup::BusGroupOff => self.on_mask(),
+            BusGroup::BusGroupState => 0,
+        };
+        if invalid != 0 {
+            return Err(EpsError::InvalidChannels(mask_channels(invalid)));
+        }
+        Ok(())
+    }
+
+    // Merge with the current channel-on state, giving the full bitflag for 0x14
+    pub fn state(&self, u: u16) -> EpsResult<u16> {
+        Ok((u & self.keep_mask()) | self.on_mask())
+    }
+
+    fn channels_mut(&mut self) -> [&mut BusChannel; 16] {
+        [
+            &mut self.ch00,
+            &mut self.ch01,
+            &mut self.ch02,
+            &mut self.ch03,
+            &mut self.ch04,
+            &mut self.ch05,
+            &mut self.ch06,
+            &mut self.ch07,
+            &mut self.ch08,
+            &mut self.ch09,
+            &mut self.ch10,
+            &mut self.ch11,
+            &mut self.ch12,
+            &mut self.ch13,
+            &mut self.ch14,
+            &mut self.ch15,
+        ]
     }
 
     fn set_channel(typ_group: BusGroup) -> BusChannel {
@@ -280,32 +274,79 @@ impl BusChannelState {
         }
     }
 
-    pub fn set(typ_group: BusGroup, channels: Vec<u8>) -> EpsResult<Self> {
+    // State commanding channels with typ_group, the other channels are kept
+    pub fn from_group(typ_group: BusGroup, channels: Vec<Channel>) -> EpsResult<Self> {
+        // Channel 16 and up aren't covered by the group commands
+        let invalid: Vec<u8> = channels
+            .iter()
+            .map(|c| c.index())
+            .filter(|c| *c > 15)
+            .collect();
+        if !invalid.is_empty() {
+            return Err(EpsError::InvalidChannels(invalid));
+        }
         let mut bus_channel_state = BusChannelState::default();
         for c in channels.iter() {
-            match c {
-                0 => bus_channel_state.ch00 = Self::set_channel(typ_group),
-                1 => bus_channel_state.ch01 = Self::set_channel(typ_group),
-                2 => bus_channel_state.ch02 = Self::set_channel(typ_group),
-                3 => bus_channel_state.ch03 = Self::set_channel(typ_group),
-                4 => bus_channel_state.ch04 = Self::set_channel(typ_group),
-                5 => bus_channel_state.ch05 = Self::set_channel(typ_group),
-                6 => bus_channel_state.ch06 = Self::set_channel(typ_group),
-                7 => bus_channel_state.ch07 = Self::set_channel(typ_group),
-                8 => bus_channel_state.ch08 = Self::set_channel(typ_group),
-                9 => bus_channel_state.ch09 = Self::set_channel(typ_group),
-                10 => bus_channel_state.ch10 = Self::set_channel(typ_group),
-                11 => bus_channel_state.ch11 = Self::set_channel(typ_group),
-                12 => bus_channel_state.ch12 = Self::set_channel(typ_group),
-                13 => bus_channel_state.ch13 = Self::set_channel(typ_group),
-                14 => bus_channel_state.ch14 = Self::set_channel(typ_group),
-                15 => bus_channel_state.ch15 = Self::set_channel(typ_group),
-                _ => return Err(EpsError::InvalidBusChannelState),
-            }
+            *bus_channel_state.channels_mut()[c.index() as usize] = Self::set_channel(typ_group);
         }
         Ok(bus_channel_state)
     }
+
+    // State of channel idx (0 - 15)
+    pub fn get(&self, idx: u8) -> Option<BusChannel> {
+        self.channels().get(idx as usize).map(|ch| (*ch).clone())
+    }
+
+    // Set the state of channel idx (0 - 15)
+    pub fn set(&mut self, idx: u8, state: BusChannel) -> EpsResult<()> {
+        match self.channels_mut().into_iter().nth(idx as usize) {
+            Some(ch) => {
+                *ch = state;
+                Ok(())
+            }
+            None => Err(EpsError::InvalidChannels(vec![idx])),
+        }
+    }
+
+    // (index, state) of channel 0 - 15
+    pub fn iter(&self) -> std::array::IntoIter<(u8, BusChannel), 16> {
+        let channels = self.channels();
+        std::array::from_fn(|i| (i as u8, channels[i].clone())).into_iter()
+    }
+
+    fn channels(&self) -> [&BusChannel; 16] {
+        [
+            &self.ch00, &self.ch01, &self.ch02, &self.ch03, &self.ch04, &self.ch05, &self.ch06,
+            &self.ch07, &self.ch08, &self.ch09, &self.ch10, &self.ch11, &self.ch12, &self.ch13,
+            &self.ch14, &self.ch15,
+        ]
+    }
+}
+impl IntoIterator for BusChannelState {
+    type Item = (u8, BusChannel);
+    type IntoIter = std::array::IntoIter<(u8, BusChannel), 16>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl IntoIterator for &BusChannelState {
+    type Item = (u8, BusChannel);
+    type IntoIter = std::array::IntoIter<(u8, BusChannel), 16>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+// Channel-on bitflag, kept and off channels are 0
+impl From<BusChannelState> for u16 {
+    fn from(s: BusChannelState) -> u16 {
+        s.on()
+    }
+}
+// Channel indices of the bits set in mask
+fn mask_channels(mask: u16) -> Vec<u8> {
+    (0..16).filter(|i| mask & (1 << i) != 0).collect()
 }
+
 impl From<u16> for BusChannelState {
     fn from(u: u16) -> BusChannelState {
         BusChannelState {
@@ -394,19 +435,10 @@ impl From<u16> for BusChannelState {
 }
 
 // Output Bus Channel
-#[derive(
-    Clone,
-    Debug,
-    Default,
-    Serialize,
-    Deserialize,
-    PartialEq,
-    Eq,
-    EnumIter,
-    EnumString,
-    Display,
-    Hash,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum BusChannel {
     #[default]
     Keep,
@@ -428,10 +460,131 @@ impl BusChannel {
     }
 }
 
+// Output bus channel of the ICEPSv2, named after its voltage domain and nominal voltage
+#[allow(non_camel_case_types)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
+#[repr(u8)]
+pub enum Channel {
+    Ch0_VD0_3V3 = 0,
+    Ch1_VD1_5V = 1,
+    Ch2_VD1_5V = 2,
+    Ch3_VD1_5V = 3,
+    Ch4_VD1_3V3 = 4,
+    Ch5_VD2_3V3 = 5,
+    Ch6_VD2_3V3 = 6,
+    Ch7_VD2_3V3 = 7,
+    Ch8_VD2_3V3 = 8,
+    Ch9_VD0_3V3 = 9,
+    Ch10_VD0_3V3 = 10,
+    Ch11_VD0_3V3 = 11,
+    Ch12_VD3_5V4 = 12,
+    Ch13_VD3_5V4 = 13,
+    Ch14_VD4_12V = 14,
+    Ch15_VD4_12V = 15,
+    Ch16_VD5_28V = 16,
+}
+impl Channel {
+    pub const ALL: [Channel; 17] = [
+        Channel::Ch0_VD0_3V3,
+        Channel::Ch1_VD1_5V,
+        Channel::Ch2_VD1_5V,
+        Channel::Ch3_VD1_5V,
+        Channel::Ch4_VD1_3V3,
+        Channel::Ch5_VD2_3V3,
+        Channel::Ch6_VD2_3V3,
+        Channel::Ch7_VD2_3V3,
+        Channel::Ch8_VD2_3V3,
+        Channel::Ch9_VD0_3V3,
+        Channel::Ch10_VD0_3V3,
+        Channel::Ch11_VD0_3V3,
+        Channel::Ch12_VD3_5V4,
+        Channel::Ch13_VD3_5V4,
+        Channel::Ch14_VD4_12V,
+        Channel::Ch15_VD4_12V,
+        Channel::Ch16_VD5_28V,
+    ];
+
+    // Channel index as used in the commands and bitflags
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    // Voltage domain 0 - 5 feeding the channel
+    pub fn domain(self) -> u8 {
+        match self {
+            Channel::Ch0_VD0_3V3
+            | Channel::Ch9_VD0_3V3
+            | Channel::Ch10_VD0_3V3
+            | Channel::Ch11_VD0_3V3 => 0,
+            Channel::Ch1_VD1_5V
+            | Channel::Ch2_VD1_5V
+            | Channel::Ch3_VD1_5V
+            | Channel::Ch4_VD1_3V3 => 1,
+            Channel::Ch5_VD2_3V3
+            | Channel::Ch6_VD2_3V3
+            | Channel::Ch7_VD2_3V3
+            | Channel::Ch8_VD2_3V3 => 2,
+            Channel::Ch12_VD3_5V4 | Channel::Ch13_VD3_5V4 => 3,
+            Channel::Ch14_VD4_12V | Channel::Ch15_VD4_12V => 4,
+            Channel::Ch16_VD5_28V => 5,
+        }
+    }
+
+    // Position of the channel in its domain, e.g. 1 for VD2_1
+    pub fn domain_index(self) -> u8 {
+        match self {
+            Channel::Ch9_VD0_3V3 => 1,
+            Channel::Ch10_VD0_3V3 => 2,
+            Channel::Ch11_VD0_3V3 => 3,
+            _ => {
+                let first = Channel::ALL
+                    .iter()
+                    .position(|c| c.domain() == self.domain())
+                    .unwrap_or_default();
+                self.index() - first as u8
+            }
+        }
+    }
+
+    // Nominal output voltage in mV
+    pub fn nominal_mv(self) -> u16 {
+        match self {
+            Channel::Ch1_VD1_5V | Channel::Ch2_VD1_5V | Channel::Ch3_VD1_5V => 5000,
+            Channel::Ch12_VD3_5V4 | Channel::Ch13_VD3_5V4 => 5400,
+            Channel::Ch14_VD4_12V | Channel::Ch15_VD4_12V => 12000,
+            Channel::Ch16_VD5_28V => 28200,
+            _ => 3300,
+        }
+    }
+
+    // Bit of the channel in the channel bitflags
+    pub fn mask(self) -> u32 {
+        1 << self.index()
+    }
+}
+impl TryFrom<u8> for Channel {
+    type Error = EpsError;
+    fn try_from(v: u8) -> EpsResult<Channel> {
+        match Channel::ALL.get(v as usize) {
+            Some(c) => Ok(*c),
+            None => Err(EpsError::InvalidChannels(vec![v])),
+        }
+    }
+}
+impl From<Channel> for u8 {
+    fn from(c: Channel) -> u8 {
+        c.index()
+    }
+}
+
 // Used in ModeSwitch (0x30/0x31)
-#[derive(
-    Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, Display, EnumString,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, Display, EnumString))]
 pub enum ModeSwitch {
     #[default]
     Nominal,
@@ -439,7 +592,10 @@ pub enum ModeSwitch {
 }
 
 // Reset status, used in get system status (0x40)
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, Display, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum EpsMode {
     #[default]
     Startup,
@@ -461,7 +617,10 @@ impl TryFrom<u8> for EpsMode {
 }
 
 // Reset status, used in get system status (0x40)
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, Display, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum ResetCause {
     #[default]
     PowerOn,
@@ -484,7 +643,10 @@ impl TryFrom<u8> for ResetCause {
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, Display)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum PDUHkSel {
     PDURawHK,
     #[default]
@@ -492,7 +654,10 @@ pub enum PDUHkSel {
     PDUAvgHK,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, Display)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum PBUHkSel {
     PBURawHK,
     #[default]
@@ -500,7 +665,10 @@ pub enum PBUHkSel {
     PBUAvgHK,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, Display)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum PCUHkSel {
     PCURawHK,
     #[default]
@@ -508,9 +676,10 @@ pub enum PCUHkSel {
     PCUAvgHK,
 }
 
-#[derive(
-    Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, EnumString, Display,
-)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
 pub enum PIUHkSel {
     PIURawHK,
     #[default]
@@ -518,37 +687,122 @@ pub enum PIUHkSel {
     PIUAvgHK,
 }
 
+// Daughterboard with the extended output channels (CH9 - CH16) and conditioning chains 4 and 5
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
+pub enum DaughterboardPresence {
+    #[default]
+    Present,
+    Absent,
+}
+
+// Housekeeping products that can be requested in one sweep with collect_hk()
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumString, Display))]
+pub enum HkSelection {
+    SystemStatus,
+    Pdu(PDUHkSel),
+    Pbu(PBUHkSel),
+    Pcu(PCUHkSel),
+    Piu(PIUHkSel),
+}
+
+// Housekeeping data returned by collect_hk(), one entry per HkSelection
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(Display))]
+pub enum HkData {
+    SystemStatus(SystemStatus),
+    Pdu(PDUHk),
+    Pbu(PBUHk),
+    Pcu(PCUHk),
+    Piu(PIUHk),
+}
+
 // The voltage V - current I - power P datatype (VIPD) raw data.
 // Used in blocks across the HK telemetry.
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct VIPRawData {
-    volt_raw: i16,
-    curr_raw: i16,
-    pwr_raw: i16,
+    pub volt_raw: i16,
+    pub curr_raw: i16,
+    pub pwr_raw: i16,
 }
 
+impl TryFrom<&[u8]> for VIPRawData {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<VIPRawData> {
+        check_len(v, 6)?;
+        Ok(VIPRawData {
+            volt_raw: <i16>::from_le_bytes([v[0], v[1]]),
+            curr_raw: <i16>::from_le_bytes([v[2], v[3]]),
+            pwr_raw: <i16>::from_le_bytes([v[4], v[5]]),
+        })
+    }
+}
+impl VIPRawData {
+    // Convert to engineering data with the calibration of the unit
+    pub fn calibrate(&self, cal: &VipCalibration) -> VIPData {
+        cal.apply(self)
+    }
+}
+
+// Resolution of the power reading in VIPData (10 mW per LSB)
+const VIP_PWR_RESOLUTION_W: f32 = 0.01;
+
 // The voltage V - current I - power P datatype (VIPD) data.
 // Used in blocks across the HK telemetry.
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct VIPData {
     pub volt: i16,
     pub curr: i16,
-    pub pwr: i16,
+    // Wider than the raw reading, which is in 10 mW steps
+    pub pwr: i32,
 }
 
-impl From<Vec<u8>> for VIPData {
-    fn from(v: Vec<u8>) -> VIPData {
-        VIPData {
+impl TryFrom<&[u8]> for VIPData {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<VIPData> {
+        check_len(v, 6)?;
+        Ok(VIPData {
             volt: <i16>::from_le_bytes([v[0], v[1]]),
             curr: <i16>::from_le_bytes([v[2], v[3]]),
-            pwr: 10 * (<i16>::from_le_bytes([v[4], v[5]])),
-        }
+            pwr: 10 * i32::from(<i16>::from_le_bytes([v[4], v[5]])),
+        })
+    }
+}
+// Engineering data: voltage in mV, current in mA and power in mW
+impl VIPData {
+    pub fn volts(&self) -> f32 {
+        self.volt as f32 / 1000.0
+    }
+    pub fn amps(&self) -> f32 {
+        self.curr as f32 / 1000.0
+    }
+    pub fn watts(&self) -> f32 {
+        self.pwr as f32 / 1000.0
+    }
+    // Check the reported power against V x I.
+    // tolerance is relative (e.g. 0.1 = 10%), on top of the 10 mW resolution of the power reading.
+    pub fn is_consistent(&self, tolerance: f32) -> bool {
+        let expected = self.volts() * self.amps();
+        (self.watts() - expected).abs() <= expected.abs() * tolerance + VIP_PWR_RESOLUTION_W
     }
 }
 
 // The battery pack raw data (BPD).
 // Used in the PBU HK telemetry
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BattPackRawData {
     vip_bp_output_raw: VIPRawData,
     stat_bp_raw: u16,
@@ -561,42 +815,87 @@ pub struct BattPackRawData {
     bat_temp3_raw: u16,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
-pub struct BattPackStatus {
-    batt1_under: bool,
-    batt2_under: bool,
-    batt3_under: bool,
-    batt4_under: bool,
-    batt1_over: bool,
-    batt2_over: bool,
-    batt3_over: bool,
-    batt4_over: bool,
-    batt1_balancing: bool,
-    batt2_balancing: bool,
-    batt3_balancing: bool,
-    batt4_balancing: bool,
-    heater: bool,
-    enabled: bool,
-}
-impl From<Vec<u8>> for BattPackStatus {
-    fn from(v: Vec<u8>) -> BattPackStatus {
-        let b = <u16>::from_le_bytes([v[0], v[1]]);
-        BattPackStatus {
-            batt1_under: (b & 0x0001) != 0,
-            batt2_under: (b & 0x0002) != 0,
-            batt3_under: (b & 0x0004) != 0,
-            batt4_under: (b & 0x0008) != 0,
-            batt1_over: (b & 0x0010) != 0,
-            batt2_over: (b & 0x0020) != 0,
-            batt3_over: (b & 0x0040) != 0,
-            batt4_over: (b & 0x0080) != 0,
-            batt1_balancing: (b & 0x0100) != 0,
-            batt2_balancing: (b & 0x0200) != 0,
-            batt3_balancing: (b & 0x0400) != 0,
-            batt4_balancing: (b & 0x0800) != 0,
-            heater: (b & 0x1000) != 0,
-            enabled: (b & 0x8000) != 0,
-        }
+bitflags! {
+    // Battery pack status bitflag (STAT_BU/BAT_STAT). Unknown bits are preserved.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct BattPackStatus: u16 {
+        const BATT1_UNDER = 0x0001;
+        const BATT2_UNDER = 0x0002;
+        const BATT3_UNDER = 0x0004;
+        const BATT4_UNDER = 0x0008;
+        const BATT1_OVER = 0x0010;
+        const BATT2_OVER = 0x0020;
+        const BATT3_OVER = 0x0040;
+        const BATT4_OVER = 0x0080;
+        const BATT1_BALANCING = 0x0100;
+        const BATT2_BALANCING = 0x0200;
+        const BATT3_BALANCING = 0x0400;
+        const BATT4_BALANCING = 0x0800;
+        const HEATER = 0x1000;
+        const ENABLED = 0x8000;
+        const _ = !0;
+    }
+}
+impl TryFrom<&[u8]> for BattPackStatus {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<BattPackStatus> {
+        check_len(v, 2)?;
+        Ok(BattPackStatus::from(<u16>::from_le_bytes([v[0], v[1]])))
+    }
+}
+impl From<u16> for BattPackStatus {
+    fn from(b: u16) -> BattPackStatus {
+        BattPackStatus::from_bits_retain(b)
+    }
+}
+impl From<BattPackStatus> for u16 {
+    fn from(s: BattPackStatus) -> u16 {
+        s.bits()
+    }
+}
+impl BattPackStatus {
+    const UNDER: u16 = 0x000F;
+    const OVER: u16 = 0x00F0;
+    const BALANCING: u16 = 0x0F00;
+
+    // True if any cell is under- or overvoltage
+    pub fn any_fault(&self) -> bool {
+        self.bits() & (Self::UNDER | Self::OVER) != 0
+    }
+    // Cell numbers (1 - 4) currently being balanced
+    pub fn cells_balancing(&self) -> Vec<u8> {
+        self.cells(Self::BALANCING)
+    }
+    // Cell numbers (1 - 4) flagged undervoltage
+    pub fn undervoltage_cells(&self) -> Vec<u8> {
+        self.cells(Self::UNDER)
+    }
+    // Cell numbers (1 - 4) flagged overvoltage
+    pub fn overvoltage_cells(&self) -> Vec<u8> {
+        self.cells(Self::OVER)
+    }
+    fn cells(&self, group: u16) -> Vec<u8> {
+        let nibble = (self.bits() & group) >> group.trailing_zeros();
+        (1..=4).filter(|c| nibble & (1 << (c - 1)) != 0).collect()
+    }
+}
+impl fmt::Display for BattPackStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "under: {:?}, over: {:?}, balancing: {:?}, heater: {}, enabled: {}",
+            self.undervoltage_cells(),
+            self.overvoltage_cells(),
+            self.cells_balancing(),
+            if self.contains(BattPackStatus::HEATER) {
+                "on"
+            } else {
+                "off"
+            },
+            self.contains(BattPackStatus::ENABLED)
+        )
     }
 }
 
@@ -615,12 +914,36 @@ impl From<Vec<u8>> for BattPackStatus {
 //     SWCI_CH_CMD_DISA_BF = u32
 //     }
 
+// Battery pack 1 - 3 of the PBU
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
+pub enum BatteryPack {
+    #[default]
+    Bp1,
+    Bp2,
+    Bp3,
+}
+impl BatteryPack {
+    // Pack number 1 - 3 as used by the pack functions taking a u8
+    pub fn number(self) -> u8 {
+        match self {
+            BatteryPack::Bp1 => 1,
+            BatteryPack::Bp2 => 2,
+            BatteryPack::Bp3 => 3,
+        }
+    }
+}
+
 // The battery pack data (BPD).
 // Used in the PBU HK telemetry
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BattPackData {
     pub vip_bp_output: VIPData,
-    pub stat_bp: u16,
+    pub stat_bp: BattPackStatus,
     pub volt_cell1: i16,
     pub volt_cell2: i16,
     pub volt_cell3: i16,
@@ -630,11 +953,13 @@ pub struct BattPackData {
     pub bat_temp3: i16,
 }
 
-impl From<Vec<u8>> for BattPackData {
-    fn from(v: Vec<u8>) -> BattPackData {
-        BattPackData {
-            vip_bp_output: VIPData::from(v[0..6].to_vec()),
-            stat_bp: <u16>::from_le_bytes([v[6], v[7]]),
+impl TryFrom<&[u8]> for BattPackData {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<BattPackData> {
+        check_len(v, 22)?;
+        Ok(BattPackData {
+            vip_bp_output: VIPData::try_from(&v[0..6])?,
+            stat_bp: BattPackStatus::from(<u16>::from_le_bytes([v[6], v[7]])),
             volt_cell1: <i16>::from_le_bytes([v[8], v[9]]),
             volt_cell2: <i16>::from_le_bytes([v[10], v[11]]),
             volt_cell3: <i16>::from_le_bytes([v[12], v[13]]),
@@ -642,12 +967,32 @@ impl From<Vec<u8>> for BattPackData {
             bat_temp1: <i16>::from_le_bytes([v[16], v[17]]),
             bat_temp2: <i16>::from_le_bytes([v[18], v[19]]),
             bat_temp3: <i16>::from_le_bytes([v[20], v[21]]),
-        }
+        })
+    }
+}
+
+impl BattPackData {
+    pub fn cell_voltages(&self) -> [i16; 4] {
+        [
+            self.volt_cell1,
+            self.volt_cell2,
+            self.volt_cell3,
+            self.volt_cell4,
+        ]
+    }
+    // Difference between the highest and the lowest cell voltage in mV
+    pub fn cell_imbalance(&self) -> i16 {
+        let cells = self.cell_voltages();
+        let max = cells.iter().max().copied().unwrap_or_default();
+        let min = cells.iter().min().copied().unwrap_or_default();
+        max.saturating_sub(min)
     }
 }
 
 //CCD Raw data, the conditioning channel datatype (CCD) for each power conditioning chain
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CondChnRawData {
     vip_cc_output_raw: VIPData,
     volt_in_mppt_raw: u16,
@@ -657,29 +1002,57 @@ pub struct CondChnRawData {
 }
 
 //CCD data, the conditioning channel datatype for each power conditioning chain
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CondChnData {
-    vip_cc_output: VIPData,
-    volt_in_mppt: i16,
-    curr_in_mppt: i16,
-    volt_out_mppt: i16,
-    curr_out_mppt: i16,
+    pub vip_cc_output: VIPData,
+    pub volt_in_mppt: i16,
+    pub curr_in_mppt: i16,
+    pub volt_out_mppt: i16,
+    pub curr_out_mppt: i16,
 }
 
-impl From<Vec<u8>> for CondChnData {
-    fn from(v: Vec<u8>) -> CondChnData {
-        CondChnData {
-            vip_cc_output: VIPData::from(v[0..6].to_vec()),
+impl TryFrom<&[u8]> for CondChnData {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<CondChnData> {
+        check_len(v, 14)?;
+        Ok(CondChnData {
+            vip_cc_output: VIPData::try_from(&v[0..6])?,
             volt_in_mppt: <i16>::from_le_bytes([v[6], v[7]]),
             curr_in_mppt: <i16>::from_le_bytes([v[8], v[9]]),
             volt_out_mppt: <i16>::from_le_bytes([v[10], v[11]]),
             curr_out_mppt: <i16>::from_le_bytes([v[12], v[13]]),
-        }
+        })
+    }
+}
+impl CondChnData {
+    // Power delivered by the MPPT output in W
+    pub fn mppt_out_power(&self) -> f32 {
+        self.volt_out_mppt as f32 * self.curr_out_mppt as f32 / 1_000_000.0
+    }
+}
+impl fmt::Display for CondChnData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "in: {} mV {} mA, out: {} mV {} mA ({:.3} W), output: {} mV {} mA {} mW",
+            self.volt_in_mppt,
+            self.curr_in_mppt,
+            self.volt_out_mppt,
+            self.curr_out_mppt,
+            self.mppt_out_power(),
+            self.vip_cc_output.volt,
+            self.vip_cc_output.curr,
+            self.vip_cc_output.pwr
+        )
     }
 }
 
 //CCSD raw, Short for conditioning channel datatype (CCD), withou VIP data
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CondChnShortRawData {
     volt_in_mppt_raw: u16,
     curr_in_mppt_raw: u16,
@@ -688,22 +1061,45 @@ pub struct CondChnShortRawData {
 }
 
 //CCSD, Short for conditioning channel datatype (CCD), withou VIP data
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CondChnShortData {
-    volt_in_mppt: i16,
-    curr_in_mppt: i16,
-    volt_out_mppt: i16,
-    curr_out_mppt: i16,
+    pub volt_in_mppt: i16,
+    pub curr_in_mppt: i16,
+    pub volt_out_mppt: i16,
+    pub curr_out_mppt: i16,
 }
 
-impl From<Vec<u8>> for CondChnShortData {
-    fn from(v: Vec<u8>) -> CondChnShortData {
-        CondChnShortData {
+impl TryFrom<&[u8]> for CondChnShortData {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<CondChnShortData> {
+        check_len(v, 8)?;
+        Ok(CondChnShortData {
             volt_in_mppt: <i16>::from_le_bytes([v[0], v[1]]),
             curr_in_mppt: <i16>::from_le_bytes([v[2], v[3]]),
             volt_out_mppt: <i16>::from_le_bytes([v[4], v[5]]),
             curr_out_mppt: <i16>::from_le_bytes([v[6], v[7]]),
-        }
+        })
+    }
+}
+impl CondChnShortData {
+    // Power delivered by the MPPT output in W
+    pub fn mppt_out_power(&self) -> f32 {
+        self.volt_out_mppt as f32 * self.curr_out_mppt as f32 / 1_000_000.0
+    }
+}
+impl fmt::Display for CondChnShortData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "in: {} mV {} mA, out: {} mV {} mA ({:.3} W)",
+            self.volt_in_mppt,
+            self.curr_in_mppt,
+            self.volt_out_mppt,
+            self.curr_out_mppt,
+            self.mppt_out_power()
+        )
     }
 }
 
@@ -713,7 +1109,9 @@ Structure takes the 5th offset byte (0 to 4 are fixed) as the first byte of the
 */
 
 // System status information (0x40)
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SystemStatus {
     // 0 = startup; 1 = nominal; 2 = safety; 3 = emergency low power
     mode: EpsMode,
@@ -753,9 +1151,10 @@ pub struct SystemStatus {
     unix_second: u8,
 }
 
-impl TryFrom<Vec<u8>> for SystemStatus {
+impl TryFrom<&[u8]> for SystemStatus {
     type Error = EpsError;
-    fn try_from(v: Vec<u8>) -> EpsResult<SystemStatus> {
+    fn try_from(v: &[u8]) -> EpsResult<SystemStatus> {
+        check_len(v, 36)?;
         let mode = EpsMode::try_from(v[5])?;
         let reset_cause = ResetCause::try_from(v[7])?;
         Ok(SystemStatus {
@@ -781,161 +1180,468 @@ impl TryFrom<Vec<u8>> for SystemStatus {
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
-pub struct ChannelOverCurrentState {
-    ch00: bool,
-    ch01: bool,
-    ch02: bool,
-    ch03: bool,
-    ch04: bool,
-    ch05: bool,
-    ch06: bool,
-    ch07: bool,
-    ch08: bool,
-    ch09: bool,
-    ch10: bool,
-    ch11: bool,
-    ch12: bool,
-    ch13: bool,
-    ch14: bool,
-    ch15: bool,
+impl SystemStatus {
+    // Current operating mode
+    pub fn mode(&self) -> EpsMode {
+        self.mode.clone()
+    }
+    // Configuration parameters changed since the last load/save
+    pub fn conf(&self) -> bool {
+        self.conf
+    }
+    // Cause of the last reset
+    pub fn reset_cause(&self) -> ResetCause {
+        self.reset_cause.clone()
+    }
+    // Time since the last reset
+    pub fn uptime(&self) -> Duration {
+        Duration::from_secs(self.uptime as u64)
+    }
+    // Seconds since the last reset
+    pub fn uptime_secs(&self) -> u32 {
+        self.uptime
+    }
+    // First internal error of the system control cycle
+    pub fn error(&self) -> u16 {
+        self.error
+    }
+    pub fn rc_cnt_pwron(&self) -> u16 {
+        self.rc_cnt_pwron
+    }
+    pub fn rc_cnt_wdg(&self) -> u16 {
+        self.rc_cnt_wdg
+    }
+    pub fn rc_cnt_cmd(&self) -> u16 {
+        self.rc_cnt_cmd
+    }
+    pub fn rc_cnt_mcu(&self) -> u16 {
+        self.rc_cnt_mcu
+    }
+    pub fn rc_cnt_lowpwr(&self) -> u16 {
+        self.rc_cnt_lowpwr
+    }
+    // Reset counters since begin of life cycle, in the order
+    // power-on, watchdog, commanded, EPS upset, low power
+    pub fn reset_counters(&self) -> [(ResetCause, u16); 5] {
+        [
+            (ResetCause::PowerOn, self.rc_cnt_pwron),
+            (ResetCause::Watchdog, self.rc_cnt_wdg),
+            (ResetCause::Commanded, self.rc_cnt_cmd),
+            (ResetCause::EpsUpset, self.rc_cnt_mcu),
+            (ResetCause::EmergLowPwr, self.rc_cnt_lowpwr),
+        ]
+    }
+    // Time between the previous and this command, in ms
+    pub fn prevcmd_elapsed(&self) -> u16 {
+        self.prevcmd_elapsed
+    }
+    // Seconds elapsed since 1970-01-01 00:00:00 on the EPS clock
+    pub fn unix_time(&self) -> u32 {
+        self.unix_time
+    }
+    // Calendar date of unix_time as (year without century, month, day)
+    pub fn unix_date(&self) -> (u8, u8, u8) {
+        (self.unix_year, self.unix_month, self.unix_day)
+    }
+    // Time of day of unix_time as (hour, minute, second)
+    pub fn unix_clock(&self) -> (u8, u8, u8) {
+        (self.unix_hour, self.unix_minute, self.unix_second)
+    }
+    // EPS clock as UTC timestamp
+    #[cfg(feature = "chrono")]
+    pub fn timestamp(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(self.unix_time as i64, 0).unwrap_or_default()
+    }
+}
+
+// Output bus channel index, e.g. 0 represents channel 0 (CH0)
+pub type ChannelId = u8;
+
+// Highest output bus channel of the ICEPSv2, the bitfields have room up to 31
+pub(crate) const MAX_CHANNEL: ChannelId = Channel::ALL[Channel::ALL.len() - 1] as ChannelId;
+
+bitflags! {
+    // Channel-on status bitflag (STAT_CH_ON), bit n = channel n
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct ChannelOnState: u16 {
+        const CH00 = 0x0001;
+        const CH01 = 0x0002;
+        const CH02 = 0x0004;
+        const CH03 = 0x0008;
+        const CH04 = 0x0010;
+        const CH05 = 0x0020;
+        const CH06 = 0x0040;
+        const CH07 = 0x0080;
+        const CH08 = 0x0100;
+        const CH09 = 0x0200;
+        const CH10 = 0x0400;
+        const CH11 = 0x0800;
+        const CH12 = 0x1000;
+        const CH13 = 0x2000;
+        const CH14 = 0x4000;
+        const CH15 = 0x8000;
+    }
+}
+impl From<u16> for ChannelOnState {
+    fn from(u: u16) -> ChannelOnState {
+        ChannelOnState::from_bits_retain(u)
+    }
+}
+impl From<ChannelOnState> for u16 {
+    fn from(s: ChannelOnState) -> u16 {
+        s.bits()
+    }
+}
+impl ChannelOnState {
+    // True if any channel is enabled
+    pub fn any(&self) -> bool {
+        !self.is_empty()
+    }
+    // Number of enabled channels
+    pub fn count(&self) -> usize {
+        self.bits().count_ones() as usize
+    }
+    // Indices of the enabled channels, lowest first
+    pub fn iter_on(&self) -> impl Iterator<Item = ChannelId> {
+        let u = self.bits();
+        (0..16).filter(move |ch| (u & (1 << ch)) != 0)
+    }
+}
+
+bitflags! {
+    // Overcurrent latch-off status bitflag (STAT_CH_OCF), bit n = channel n
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct ChannelOverCurrentState: u16 {
+        const CH00 = 0x0001;
+        const CH01 = 0x0002;
+        const CH02 = 0x0004;
+        const CH03 = 0x0008;
+        const CH04 = 0x0010;
+        const CH05 = 0x0020;
+        const CH06 = 0x0040;
+        const CH07 = 0x0080;
+        const CH08 = 0x0100;
+        const CH09 = 0x0200;
+        const CH10 = 0x0400;
+        const CH11 = 0x0800;
+        const CH12 = 0x1000;
+        const CH13 = 0x2000;
+        const CH14 = 0x4000;
+        const CH15 = 0x8000;
+    }
 }
 impl From<u16> for ChannelOverCurrentState {
     fn from(u: u16) -> ChannelOverCurrentState {
-        ChannelOverCurrentState {
-            ch00: (u & 0x0001) != 0,
-            ch01: (u & 0x0002) != 0,
-            ch02: (u & 0x0004) != 0,
-            ch03: (u & 0x0008) != 0,
-            ch04: (u & 0x0010) != 0,
-            ch05: (u & 0x0020) != 0,
-            ch06: (u & 0x0040) != 0,
-            ch07: (u & 0x0080) != 0,
-            ch08: (u & 0x0100) != 0,
-            ch09: (u & 0x0200) != 0,
-            ch10: (u & 0x0400) != 0,
-            ch11: (u & 0x0800) != 0,
-            ch12: (u & 0x1000) != 0,
-            ch13: (u & 0x2000) != 0,
-            ch14: (u & 0x4000) != 0,
-            ch15: (u & 0x8000) != 0,
-        }
+        ChannelOverCurrentState::from_bits_retain(u)
+    }
+}
+impl From<ChannelOverCurrentState> for u16 {
+    fn from(s: ChannelOverCurrentState) -> u16 {
+        s.bits()
+    }
+}
+impl ChannelOverCurrentState {
+    // True if any channel is latched off
+    pub fn any(&self) -> bool {
+        !self.is_empty()
+    }
+    // Number of latched off channels
+    pub fn count(&self) -> usize {
+        self.bits().count_ones() as usize
+    }
+    // Indices of the latched off channels, lowest first
+    pub fn iter_faulted(&self) -> impl Iterator<Item = ChannelId> {
+        let u = self.bits();
+        (0..16).filter(move |ch| (u & (1 << ch)) != 0)
+    }
+}
+
+bitflags! {
+    // Extended channel-on status bitflag (STAT_CH_EXT_ON), bit n = channel 16 + n
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct ExtChannelOnState: u16 {
+        const CH16 = 0x0001;
+        const CH17 = 0x0002;
+        const CH18 = 0x0004;
+        const CH19 = 0x0008;
+        const CH20 = 0x0010;
+        const CH21 = 0x0020;
+        const CH22 = 0x0040;
+        const CH23 = 0x0080;
+        const CH24 = 0x0100;
+        const CH25 = 0x0200;
+        const CH26 = 0x0400;
+        const CH27 = 0x0800;
+        const CH28 = 0x1000;
+        const CH29 = 0x2000;
+        const CH30 = 0x4000;
+        const CH31 = 0x8000;
+    }
+}
+impl From<u16> for ExtChannelOnState {
+    fn from(u: u16) -> ExtChannelOnState {
+        ExtChannelOnState::from_bits_retain(u)
+    }
+}
+impl From<ExtChannelOnState> for u16 {
+    fn from(s: ExtChannelOnState) -> u16 {
+        s.bits()
+    }
+}
+impl ExtChannelOnState {
+    // True if any extended channel is enabled
+    pub fn any(&self) -> bool {
+        !self.is_empty()
+    }
+    // Number of enabled extended channels
+    pub fn count(&self) -> usize {
+        self.bits().count_ones() as usize
+    }
+    // Indices (16 - 31) of the enabled channels, lowest first
+    pub fn iter_on(&self) -> impl Iterator<Item = ChannelId> {
+        let u = self.bits();
+        (0..16)
+            .filter(move |ch| (u & (1 << ch)) != 0)
+            .map(|ch| ch + 16)
+    }
+}
+
+bitflags! {
+    // Extended overcurrent latch-off status bitflag (STAT_CH_EXT_OCF), bit n = channel 16 + n
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct ExtChannelOverCurrentState: u16 {
+        const CH16 = 0x0001;
+        const CH17 = 0x0002;
+        const CH18 = 0x0004;
+        const CH19 = 0x0008;
+        const CH20 = 0x0010;
+        const CH21 = 0x0020;
+        const CH22 = 0x0040;
+        const CH23 = 0x0080;
+        const CH24 = 0x0100;
+        const CH25 = 0x0200;
+        const CH26 = 0x0400;
+        const CH27 = 0x0800;
+        const CH28 = 0x1000;
+        const CH29 = 0x2000;
+        const CH30 = 0x4000;
+        const CH31 = 0x8000;
+    }
+}
+impl From<u16> for ExtChannelOverCurrentState {
+    fn from(u: u16) -> ExtChannelOverCurrentState {
+        ExtChannelOverCurrentState::from_bits_retain(u)
+    }
+}
+impl From<ExtChannelOverCurrentState> for u16 {
+    fn from(s: ExtChannelOverCurrentState) -> u16 {
+        s.bits()
+    }
+}
+impl ExtChannelOverCurrentState {
+    // True if any extended channel is latched off
+    pub fn any(&self) -> bool {
+        !self.is_empty()
+    }
+    // Number of latched off extended channels
+    pub fn count(&self) -> usize {
+        self.bits().count_ones() as usize
+    }
+    // Indices (16 - 31) of the latched off channels, lowest first
+    pub fn iter_faulted(&self) -> impl Iterator<Item = ChannelId> {
+        let u = self.bits();
+        (0..16)
+            .filter(move |ch| (u & (1 << ch)) != 0)
+            .map(|ch| ch + 16)
+    }
+}
+
+// Channel-on states only, the first part of the 0x42 response
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChannelStates {
+    // Channel-on status for output 0 through 15
+    pub on: BusChannelState,
+    // Channel-on status for output 16 through 31
+    pub ext_on: ExtChannelOnState,
+}
+impl TryFrom<&[u8]> for ChannelStates {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<ChannelStates> {
+        check_len(v, 10)?;
+        Ok(ChannelStates {
+            on: BusChannelState::from(<u16>::from_le_bytes([v[6], v[7]])),
+            ext_on: ExtChannelOnState::from(<u16>::from_le_bytes([v[8], v[9]])),
+        })
     }
 }
 
 // Overcurrent Fault State （0x42）
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OverCurrentFaultState {
     // One reseved byte. Starting from the 6th byte
     // Length of useful data for ICEPSv2 (17 channels), 50bytes
     // Bitflag field indicating channel-on status. 1 means corresponding output bus is enabled
-    pub stat_ch_on: BusChannelState,
+    pub stat_ch_on: ChannelOnState,
     // Bitflag field indicating channel-on fault status
-    pub stat_ch_ext_on: BusChannelState,
+    pub stat_ch_ext_on: ExtChannelOnState,
     // Bitflag field indicating overcurrent status. 1 means corresponding output bus is latched off
-    stat_ch_ocf: ChannelOverCurrentState,
+    pub stat_ch_ocf: ChannelOverCurrentState,
     // Bitflag field indicating overcurrent fault status
-    stat_ch_ext_ocf: ChannelOverCurrentState,
-    // VD0_0, 3.3V
-    ocf_cnt_ch00: u16,
-    // VD1_0, 5V
-    ocf_cnt_ch01: u16,
-    // VD1_1, 5V
-    ocf_cnt_ch02: u16,
-    // VD1_2, 5V
-    ocf_cnt_ch03: u16,
-    // VD1_3, 3.3V
-    ocf_cnt_ch04: u16,
-    // VD2_0, 3.3V
-    ocf_cnt_ch05: u16,
-    // VD2_1, 3.3V
-    ocf_cnt_ch06: u16,
-    // VD2_2, 3.3V
-    ocf_cnt_ch07: u16,
-    // VD2_3, 3.3V
-    ocf_cnt_ch08: u16,
-    // VD0_1, 3.3V
-    ocf_cnt_ch09: u16, //CubeADCS 3-Axi
-    // VD0_2, 3.3V
-    ocf_cnt_ch10: u16,
-    // VD0_3, 3.3V
-    ocf_cnt_ch11: u16,
-    // VD3_0, 5.4V (customized)
-    ocf_cnt_ch12: u16,
-    // VD3_1, 5.4V (customized)
-    ocf_cnt_ch13: u16,
-    // VD4_0, 12V (customized)
-    ocf_cnt_ch14: u16,
-    // VD4_1, 12V (customized)
-    ocf_cnt_ch15: u16,
-    // VD5_0, 28.2V
-    ocf_cnt_ch16: u16,
+    pub stat_ch_ext_ocf: ExtChannelOverCurrentState,
+    // Overcurrent fault counters, index n = channel n (0 - 16)
+    pub ocf_cnt: [u16; 17],
 }
 
-impl From<Vec<u8>> for OverCurrentFaultState {
-    fn from(v: Vec<u8>) -> OverCurrentFaultState {
-        OverCurrentFaultState {
-            stat_ch_on: BusChannelState::from(<u16>::from_le_bytes([v[6], v[7]])),
-            stat_ch_ext_on: BusChannelState::from(<u16>::from_le_bytes([v[8], v[9]])),
+impl TryFrom<&[u8]> for OverCurrentFaultState {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<OverCurrentFaultState> {
+        check_len(v, 48)?;
+        Ok(OverCurrentFaultState {
+            stat_ch_on: ChannelOnState::from(<u16>::from_le_bytes([v[6], v[7]])),
+            stat_ch_ext_on: ExtChannelOnState::from(<u16>::from_le_bytes([v[8], v[9]])),
             stat_ch_ocf: ChannelOverCurrentState::from(<u16>::from_le_bytes([v[10], v[11]])),
-            stat_ch_ext_ocf: ChannelOverCurrentState::from(<u16>::from_le_bytes([v[12], v[13]])),
-            ocf_cnt_ch00: <u16>::from_le_bytes([v[14], v[15]]),
-            ocf_cnt_ch01: <u16>::from_le_bytes([v[16], v[17]]),
-            ocf_cnt_ch02: <u16>::from_le_bytes([v[18], v[19]]),
-            ocf_cnt_ch03: <u16>::from_le_bytes([v[20], v[21]]),
-            ocf_cnt_ch04: <u16>::from_le_bytes([v[22], v[23]]),
-            ocf_cnt_ch05: <u16>::from_le_bytes([v[24], v[25]]),
-            ocf_cnt_ch06: <u16>::from_le_bytes([v[26], v[27]]),
-            ocf_cnt_ch07: <u16>::from_le_bytes([v[28], v[29]]),
-            ocf_cnt_ch08: <u16>::from_le_bytes([v[30], v[31]]),
-            ocf_cnt_ch09: <u16>::from_le_bytes([v[32], v[33]]),
-            ocf_cnt_ch10: <u16>::from_le_bytes([v[34], v[35]]),
-            ocf_cnt_ch11: <u16>::from_le_bytes([v[36], v[37]]),
-            ocf_cnt_ch12: <u16>::from_le_bytes([v[38], v[39]]),
-            ocf_cnt_ch13: <u16>::from_le_bytes([v[40], v[41]]),
-            ocf_cnt_ch14: <u16>::from_le_bytes([v[42], v[43]]),
-            ocf_cnt_ch15: <u16>::from_le_bytes([v[44], v[45]]),
-            ocf_cnt_ch16: <u16>::from_le_bytes([v[46], v[47]]),
-        }
-    }
-}
-// // PBU ABF Placed State (0x44)
-// #[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
-// pub struct ABFState {
-//     // One reseved byte. Starting from the 6th byte
-//     // 0xAB = ABF is placed, 0x00 = ABF is not placed
-//     pub abf_placed_0: u8,
-//     // 0xAB = ABF is placed, 0x00 = ABF is not placed
-//     pub abf_placed_1: u8,
-// }
-
-// impl From<Vec<u8>> for ABFState {
-//     fn from(v: Vec<u8>) -> ABFState {
-//         ABFState{
-//             abf_placed_0: v[6],
-//             abf_placed_1: v[7],
-//         }
-//     }
-// }
+            stat_ch_ext_ocf: ExtChannelOverCurrentState::from(<u16>::from_le_bytes([v[12], v[13]])),
+            ocf_cnt: core::array::from_fn(|ch| {
+                <u16>::from_le_bytes([v[14 + 2 * ch], v[15 + 2 * ch]])
+            }),
+        })
+    }
+}
+impl OverCurrentFaultState {
+    // Overcurrent fault counters of channel 0 - 16
+    pub fn counters(&self) -> [u16; 17] {
+        self.ocf_cnt
+    }
+    // Fault counter of a channel
+    pub fn count(&self, ch: Channel) -> u16 {
+        self.ocf_cnt[ch.index() as usize]
+    }
+    // Fault counter of a channel index, None outside of 0 - 16
+    pub fn get(&self, ch: ChannelId) -> Option<u16> {
+        self.ocf_cnt.get(ch as usize).copied()
+    }
+    // Latch-off state and fault counter of a channel
+    pub fn fault(&self, ch: Channel) -> ChannelFault {
+        let idx = ch.index();
+        ChannelFault {
+            latched: idx < 16 && self.stat_ch_ocf.bits() & (1 << idx) != 0,
+            ext_latched: idx >= 16 && self.stat_ch_ext_ocf.bits() & (1 << (idx - 16)) != 0,
+            count: self.count(ch),
+        }
+    }
+    // Channels whose fault counter incremented since the previous sample.
+    // Counter wrap-around is handled, a counter reset (e.g. after a reset of the EPS) is not reported.
+    pub fn diff(&self, previous: &OverCurrentFaultState) -> Vec<ChannelId> {
+        let prev = previous.counters();
+        self.counters()
+            .iter()
+            .zip(prev.iter())
+            .enumerate()
+            .filter(|(_, (cur, prev))| (1..0x8000).contains(&cur.wrapping_sub(**prev)))
+            .map(|(ch, _)| ch as ChannelId)
+            .collect()
+    }
+}
+// Overcurrent fault state of a single channel, see Eps::channel_fault()
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChannelFault {
+    // Latched off, from STAT_CH_OCF (channel 0 - 15)
+    pub latched: bool,
+    // Latched off, from STAT_CH_EXT_OCF (channel 16 - 31)
+    pub ext_latched: bool,
+    // Overcurrent faults since the last counter reset
+    pub count: u16,
+}
+impl ChannelFault {
+    pub fn is_latched(&self) -> bool {
+        self.latched || self.ext_latched
+    }
+}
+
+// Arm-before-flight pin of one PBU connector
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
+pub enum AbfState {
+    Placed,
+    NotPlaced,
+    // Any other value than the two defined by the ICD
+    #[default]
+    Unknown,
+}
+impl From<u8> for AbfState {
+    fn from(v: u8) -> AbfState {
+        match v {
+            0xAB => AbfState::Placed,
+            0x00 => AbfState::NotPlaced,
+            _ => AbfState::Unknown,
+        }
+    }
+}
+
+// PBU ABF Placed State (0x44)
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ABFState {
+    // One reseved byte. Starting from the 6th byte
+    // 0xAB = ABF is placed, 0x00 = ABF is not placed
+    pub abf_placed_0: AbfState,
+    // 0xAB = ABF is placed, 0x00 = ABF is not placed
+    pub abf_placed_1: AbfState,
+}
+impl TryFrom<&[u8]> for ABFState {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<ABFState> {
+        check_len(v, 8)?;
+        Ok(ABFState {
+            abf_placed_0: AbfState::from(v[6]),
+            abf_placed_1: AbfState::from(v[7]),
+        })
+    }
+}
+impl ABFState {
+    // Both connectors report the pin placed
+    pub fn all_placed(&self) -> bool {
+        self.abf_placed_0 == AbfState::Placed && self.abf_placed_1 == AbfState::Placed
+    }
+}
 
 // PDU Housekeeping Engineering/Average Data (0x52 and 0x54)
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PDUHk {
     // One reseved byte. Starting from the 6th byte
     // Voltage of internal board supply.
-    volt_brdsup: i16,
+    pub(crate) volt_brdsup: i16,
     // Measured temperature of the MCU
     temp: i16,
     // Input V, I and P data for the unit.
     vip_input: VIPData,
     // Bitflag field indicating channel-on status for output 0 through 15.
-    stat_ch_on: BusChannelState,
+    stat_ch_on: ChannelOnState,
     // Bitflag field indicating channel-on status for output 16 through 31.
-    stat_ch_ext_on: BusChannelState,
+    stat_ch_ext_on: ExtChannelOnState,
     // Bitflag field indicating overcurrent latch-off fault for output 0 through 15.
     stat_ch_ocf: ChannelOverCurrentState,
     // Bitflag field indicating overcurrent latch-off fault for output 16 through 31.
-    stat_ch_ext_ocf: ChannelOverCurrentState,
+    stat_ch_ext_ocf: ExtChannelOverCurrentState,
     // VIPData Output V, I and P of voltage domain 0 - 6
     vip_vd0: VIPData,
     vip_vd1: VIPData,
@@ -978,45 +1684,77 @@ pub struct PDUHk {
     // VD4_1, 12V (customized)
     vip_cnt_ch15: VIPData,
 }
-impl From<Vec<u8>> for PDUHk {
-    fn from(v: Vec<u8>) -> PDUHk {
-        PDUHk {
+impl TryFrom<&[u8]> for PDUHk {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<PDUHk> {
+        check_len(v, 156)?;
+        Ok(PDUHk {
             volt_brdsup: <i16>::from_le_bytes([v[0], v[1]]),
             temp: <i16>::from_le_bytes([v[2], v[3]]),
-            vip_input: VIPData::from(v[4..10].to_vec()),
-            stat_ch_on: BusChannelState::from(<u16>::from_le_bytes([v[10], v[11]])),
-            stat_ch_ext_on: BusChannelState::from(<u16>::from_le_bytes([v[12], v[13]])),
+            vip_input: VIPData::try_from(&v[4..10])?,
+            stat_ch_on: ChannelOnState::from(<u16>::from_le_bytes([v[10], v[11]])),
+            stat_ch_ext_on: ExtChannelOnState::from(<u16>::from_le_bytes([v[12], v[13]])),
             stat_ch_ocf: ChannelOverCurrentState::from(<u16>::from_le_bytes([v[14], v[15]])),
-            stat_ch_ext_ocf: ChannelOverCurrentState::from(<u16>::from_le_bytes([v[16], v[17]])),
-            vip_vd0: VIPData::from(v[18..24].to_vec()),
-            vip_vd1: VIPData::from(v[24..30].to_vec()),
-            vip_vd2: VIPData::from(v[30..36].to_vec()),
-            vip_vd3: VIPData::from(v[36..42].to_vec()),
-            vip_vd4: VIPData::from(v[42..48].to_vec()),
-            vip_vd5: VIPData::from(v[48..54].to_vec()),
-            vip_vd6: VIPData::from(v[54..60].to_vec()),
-            vip_cnt_ch00: VIPData::from(v[60..66].to_vec()),
-            vip_cnt_ch01: VIPData::from(v[66..72].to_vec()),
-            vip_cnt_ch02: VIPData::from(v[72..78].to_vec()),
-            vip_cnt_ch03: VIPData::from(v[78..84].to_vec()),
-            vip_cnt_ch04: VIPData::from(v[84..90].to_vec()),
-            vip_cnt_ch05: VIPData::from(v[90..96].to_vec()),
-            vip_cnt_ch06: VIPData::from(v[96..102].to_vec()),
-            vip_cnt_ch07: VIPData::from(v[102..108].to_vec()),
-            vip_cnt_ch08: VIPData::from(v[108..114].to_vec()),
-            vip_cnt_ch09: VIPData::from(v[114..120].to_vec()),
-            vip_cnt_ch10: VIPData::from(v[120..126].to_vec()),
-            vip_cnt_ch11: VIPData::from(v[126..132].to_vec()),
-            vip_cnt_ch12: VIPData::from(v[132..138].to_vec()),
-            vip_cnt_ch13: VIPData::from(v[138..144].to_vec()),
-            vip_cnt_ch14: VIPData::from(v[144..150].to_vec()),
-            vip_cnt_ch15: VIPData::from(v[150..156].to_vec()),
+            stat_ch_ext_ocf: ExtChannelOverCurrentState::from(<u16>::from_le_bytes([v[16], v[17]])),
+            vip_vd0: VIPData::try_from(&v[18..24])?,
+            vip_vd1: VIPData::try_from(&v[24..30])?,
+            vip_vd2: VIPData::try_from(&v[30..36])?,
+            vip_vd3: VIPData::try_from(&v[36..42])?,
+            vip_vd4: VIPData::try_from(&v[42..48])?,
+            vip_vd5: VIPData::try_from(&v[48..54])?,
+            vip_vd6: VIPData::try_from(&v[54..60])?,
+            vip_cnt_ch00: VIPData::try_from(&v[60..66])?,
+            vip_cnt_ch01: VIPData::try_from(&v[66..72])?,
+            vip_cnt_ch02: VIPData::try_from(&v[72..78])?,
+            vip_cnt_ch03: VIPData::try_from(&v[78..84])?,
+            vip_cnt_ch04: VIPData::try_from(&v[84..90])?,
+            vip_cnt_ch05: VIPData::try_from(&v[90..96])?,
+            vip_cnt_ch06: VIPData::try_from(&v[96..102])?,
+            vip_cnt_ch07: VIPData::try_from(&v[102..108])?,
+            vip_cnt_ch08: VIPData::try_from(&v[108..114])?,
+            vip_cnt_ch09: VIPData::try_from(&v[114..120])?,
+            vip_cnt_ch10: VIPData::try_from(&v[120..126])?,
+            vip_cnt_ch11: VIPData::try_from(&v[126..132])?,
+            vip_cnt_ch12: VIPData::try_from(&v[132..138])?,
+            vip_cnt_ch13: VIPData::try_from(&v[138..144])?,
+            vip_cnt_ch14: VIPData::try_from(&v[144..150])?,
+            vip_cnt_ch15: VIPData::try_from(&v[150..156])?,
+        })
+    }
+}
+
+impl PDUHk {
+    pub fn vip_input(&self) -> VIPData {
+        self.vip_input.clone()
+    }
+    // Output V, I and P of channel 0 - 15
+    pub fn vip_cnt_ch(&self, ch: u8) -> EpsResult<VIPData> {
+        match ch {
+            0 => Ok(self.vip_cnt_ch00.clone()),
+            1 => Ok(self.vip_cnt_ch01.clone()),
+            2 => Ok(self.vip_cnt_ch02.clone()),
+            3 => Ok(self.vip_cnt_ch03.clone()),
+            4 => Ok(self.vip_cnt_ch04.clone()),
+            5 => Ok(self.vip_cnt_ch05.clone()),
+            6 => Ok(self.vip_cnt_ch06.clone()),
+            7 => Ok(self.vip_cnt_ch07.clone()),
+            8 => Ok(self.vip_cnt_ch08.clone()),
+            9 => Ok(self.vip_cnt_ch09.clone()),
+            10 => Ok(self.vip_cnt_ch10.clone()),
+            11 => Ok(self.vip_cnt_ch11.clone()),
+            12 => Ok(self.vip_cnt_ch12.clone()),
+            13 => Ok(self.vip_cnt_ch13.clone()),
+            14 => Ok(self.vip_cnt_ch14.clone()),
+            15 => Ok(self.vip_cnt_ch15.clone()),
+            _ => Err(EpsError::InvalidInput),
         }
     }
 }
 
 // PBU Housekeeping Engineering/Average Data (0x62 and 0x64)
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PBUHk {
     // One reseved byte. Starting from the 6th byte
     // Voltage of internal board supply.
@@ -1028,25 +1766,41 @@ pub struct PBUHk {
     pub stat_bu: BattPackStatus,
     // Battery pack channel information.
     pub bp1: BattPackData,
-    // pub bp2: BattPackData,
-    // pub bp3: BattPackData,
+    // Battery packs 2 and 3, present if the response covers them
+    pub bp2: Option<BattPackData>,
+    pub bp3: Option<BattPackData>,
 }
-impl From<Vec<u8>> for PBUHk {
-    fn from(v: Vec<u8>) -> PBUHk {
-        PBUHk {
+impl TryFrom<&[u8]> for PBUHk {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<PBUHk> {
+        check_len(v, 34)?;
+        Ok(PBUHk {
             volt_brdsup: <i16>::from_le_bytes([v[0], v[1]]),
             temp: <i16>::from_le_bytes([v[2], v[3]]),
-            vip_input: VIPData::from(v[4..10].to_vec()),
-            stat_bu: BattPackStatus::from([v[10], v[11]].to_vec()),
-            bp1: BattPackData::from(v[12..34].to_vec()),
-            // bp2: BattPackData::from(v[34..56].to_vec()),
-            // bp3: BattPackData::from(v[56..78].to_vec()),
+            vip_input: VIPData::try_from(&v[4..10])?,
+            stat_bu: BattPackStatus::from(<u16>::from_le_bytes([v[10], v[11]])),
+            bp1: BattPackData::try_from(&v[12..34])?,
+            bp2: v.get(34..56).map(BattPackData::try_from).transpose()?,
+            bp3: v.get(56..78).map(BattPackData::try_from).transpose()?,
+        })
+    }
+}
+impl PBUHk {
+    // Data of battery pack 1 - 3
+    pub fn battery_pack(&self, pack: u8) -> Option<&BattPackData> {
+        match pack {
+            1 => Some(&self.bp1),
+            2 => self.bp2.as_ref(),
+            3 => self.bp3.as_ref(),
+            _ => None,
         }
     }
 }
 
 // PCU Housekeeping Engineering/Average Data (0x72 and 0x74)
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PCUHk {
     // One reseved byte. Starting from the 6th byte
     // Voltage of internal board supply.
@@ -1061,22 +1815,26 @@ pub struct PCUHk {
     pub ccd3: CondChnData,
     pub ccd4: CondChnData,
 }
-impl From<Vec<u8>> for PCUHk {
-    fn from(v: Vec<u8>) -> PCUHk {
-        PCUHk {
+impl TryFrom<&[u8]> for PCUHk {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<PCUHk> {
+        check_len(v, 66)?;
+        Ok(PCUHk {
             volt_brdsup: <i16>::from_le_bytes([v[0], v[1]]),
             temp: <i16>::from_le_bytes([v[2], v[3]]),
-            vip_output: VIPData::from(v[4..10].to_vec()),
-            ccd1: CondChnData::from(v[10..22].to_vec()),
-            ccd2: CondChnData::from(v[22..34].to_vec()),
-            ccd3: CondChnData::from(v[34..46].to_vec()),
-            ccd4: CondChnData::from(v[46..58].to_vec()),
-        }
+            vip_output: VIPData::try_from(&v[4..10])?,
+            ccd1: CondChnData::try_from(&v[10..24])?,
+            ccd2: CondChnData::try_from(&v[24..38])?,
+            ccd3: CondChnData::try_from(&v[38..52])?,
+            ccd4: CondChnData::try_from(&v[52..66])?,
+        })
     }
 }
 
 // PIU Housekeeping Engineering/Average Data (0xA2 and 0xA4)
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PIUHk {
     // One reseved byte. Starting from the 6th byte
     // Voltage of internal board supply.
@@ -1088,11 +1846,11 @@ pub struct PIUHk {
     // Input V, I and P input of the battery part of the unit
     pub vip_batt_input: VIPData,
     // Bitflag field indicating channel-on status for output 0 through 15.
-    pub stat_ch_on: u16,
+    pub stat_ch_on: ChannelOnState,
     // Bitflag field indicating overcurrent latch-off fault for output 0 through 15.
-    pub stat_ch_ocf: u16,
+    pub stat_ch_ocf: ChannelOverCurrentState,
     // Bitflag field indicating BP board status.
-    pub batt_stat: u16,
+    pub batt_stat: BattPackStatus,
     // 2 and 4 cell battery pack
     pub batt_temp2: i16,
     // 2 cell battery pack not used, temp for 4 cell battery pack:
@@ -1142,53 +1900,368 @@ pub struct PIUHk {
     pub ccd4: CondChnShortData,
     pub ccd5: CondChnShortData,
     // Bitflag field indicating channel-on status for the extended output bus channels
-    pub stat_ch_ext_on: u16,
+    pub stat_ch_ext_on: ExtChannelOnState,
     // Bitflag field indicating overcurrent latch-off fault status for the extended output bus channels
-    pub stat_ch_ext_ocf: u16,
+    pub stat_ch_ext_ocf: ExtChannelOverCurrentState,
     // VD5_0, 28.2V (default)
     pub vip_cnt_ch16: VIPData,
     // Stop at 184 byte for the ICEPSv2
 }
 
-impl From<Vec<u8>> for PIUHk {
-    fn from(v: Vec<u8>) -> PIUHk {
-        PIUHk {
+// Length of the PIU HK frame decoded into PIUHk, response header included
+pub(crate) const PIU_HK_LEN: usize = 184;
+
+impl TryFrom<&[u8]> for PIUHk {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<PIUHk> {
+        check_len(v, PIU_HK_LEN)?;
+        Ok(PIUHk {
+            volt_brdsup: <i16>::from_le_bytes([v[6], v[7]]),
+            temp: <i16>::from_le_bytes([v[8], v[9]]),
+            vip_dist_input: VIPData::try_from(&v[10..16])?,
+            vip_batt_input: VIPData::try_from(&v[16..22])?,
+            stat_ch_on: ChannelOnState::from(<u16>::from_le_bytes([v[22], v[23]])),
+            stat_ch_ocf: ChannelOverCurrentState::from(<u16>::from_le_bytes([v[24], v[25]])),
+            batt_stat: BattPackStatus::from(<u16>::from_le_bytes([v[26], v[27]])),
+            batt_temp2: <i16>::from_le_bytes([v[28], v[29]]),
+            batt_temp3: <i16>::from_le_bytes([v[30], v[31]]),
+            volt_vd0: <i16>::from_le_bytes([v[32], v[33]]),
+            volt_vd1: <i16>::from_le_bytes([v[34], v[35]]),
+            volt_vd2: <i16>::from_le_bytes([v[36], v[37]]),
+            vip_cnt_ch00: VIPData::try_from(&v[38..44])?,
+            vip_cnt_ch01: VIPData::try_from(&v[44..50])?,
+            vip_cnt_ch02: VIPData::try_from(&v[50..56])?,
+            vip_cnt_ch03: VIPData::try_from(&v[56..62])?,
+            vip_cnt_ch04: VIPData::try_from(&v[62..68])?,
+            vip_cnt_ch05: VIPData::try_from(&v[68..74])?,
+            vip_cnt_ch06: VIPData::try_from(&v[74..80])?,
+            vip_cnt_ch07: VIPData::try_from(&v[80..86])?,
+            vip_cnt_ch08: VIPData::try_from(&v[86..92])?,
+            ccd1: CondChnShortData::try_from(&v[92..100])?,
+            ccd2: CondChnShortData::try_from(&v[100..108])?,
+            ccd3: CondChnShortData::try_from(&v[108..116])?,
+            vip_cnt_ch09: VIPData::try_from(&v[116..122])?,
+            vip_cnt_ch10: VIPData::try_from(&v[122..128])?,
+            vip_cnt_ch11: VIPData::try_from(&v[128..134])?,
+            vip_cnt_ch12: VIPData::try_from(&v[134..140])?,
+            vip_cnt_ch13: VIPData::try_from(&v[140..146])?,
+            vip_cnt_ch14: VIPData::try_from(&v[146..152])?,
+            vip_cnt_ch15: VIPData::try_from(&v[152..158])?,
+            ccd4: CondChnShortData::try_from(&v[158..166])?,
+            ccd5: CondChnShortData::try_from(&v[166..174])?,
+            stat_ch_ext_on: ExtChannelOnState::from(<u16>::from_le_bytes([v[174], v[175]])),
+            stat_ch_ext_ocf: ExtChannelOverCurrentState::from(<u16>::from_le_bytes([
+                v[176], v[177],
+            ])),
+            vip_cnt_ch16: VIPData::try_from(&v[178..184])?,
+        })
+    }
+}
+
+// PIU Housekeeping Data of a unit without daughterboard (0xA0, 0xA2 and 0xA4)
+// The first 116 bytes of the PIUHk frame, up to the third conditioning chain.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PIUHkShort {
+    // One reseved byte. Starting from the 6th byte
+    // Voltage of internal board supply.
+    pub volt_brdsup: i16,
+    // Measured temperature of the MCU
+    pub temp: i16,
+    // Input V, I and P input of the distribution part of the unit in raw form.
+    pub vip_dist_input: VIPData,
+    // Input V, I and P input of the battery part of the unit
+    pub vip_batt_input: VIPData,
+    // Bitflag field indicating channel-on status for output 0 through 15.
+    pub stat_ch_on: ChannelOnState,
+    // Bitflag field indicating overcurrent latch-off fault for output 0 through 15.
+    pub stat_ch_ocf: ChannelOverCurrentState,
+    // Bitflag field indicating BP board status.
+    pub batt_stat: BattPackStatus,
+    // 2 and 4 cell battery pack
+    pub batt_temp2: i16,
+    // 2 cell battery pack not used, temp for 4 cell battery pack:
+    pub batt_temp3: i16,
+    // Voltage level for domain 0 - 2
+    pub volt_vd0: i16,
+    pub volt_vd1: i16,
+    pub volt_vd2: i16,
+    // VIPData output for channel 0 - 8
+    pub vip_cnt_ch00: VIPData,
+    pub vip_cnt_ch01: VIPData,
+    pub vip_cnt_ch02: VIPData,
+    pub vip_cnt_ch03: VIPData,
+    pub vip_cnt_ch04: VIPData,
+    pub vip_cnt_ch05: VIPData,
+    pub vip_cnt_ch06: VIPData,
+    pub vip_cnt_ch07: VIPData,
+    pub vip_cnt_ch08: VIPData,
+    // Data on conditioning chain
+    pub ccd1: CondChnShortData,
+    pub ccd2: CondChnShortData,
+    pub ccd3: CondChnShortData,
+}
+
+// Length of the PIU HK frame without daughterboard, response header included
+pub(crate) const PIU_HK_SHORT_LEN: usize = 116;
+
+impl TryFrom<&[u8]> for PIUHkShort {
+    type Error = EpsError;
+    fn try_from(v: &[u8]) -> EpsResult<PIUHkShort> {
+        check_len(v, PIU_HK_SHORT_LEN)?;
+        Ok(PIUHkShort {
             volt_brdsup: <i16>::from_le_bytes([v[6], v[7]]),
             temp: <i16>::from_le_bytes([v[8], v[9]]),
-            vip_dist_input: VIPData::from(v[10..16].to_vec()),
-            vip_batt_input: VIPData::from(v[16..22].to_vec()),
-            stat_ch_on: <u16>::from_le_bytes([v[22], v[23]]),
-            stat_ch_ocf: <u16>::from_le_bytes([v[24], v[25]]),
-            batt_stat: <u16>::from_le_bytes([v[26], v[27]]),
+            vip_dist_input: VIPData::try_from(&v[10..16])?,
+            vip_batt_input: VIPData::try_from(&v[16..22])?,
+            stat_ch_on: ChannelOnState::from(<u16>::from_le_bytes([v[22], v[23]])),
+            stat_ch_ocf: ChannelOverCurrentState::from(<u16>::from_le_bytes([v[24], v[25]])),
+            batt_stat: BattPackStatus::from(<u16>::from_le_bytes([v[26], v[27]])),
             batt_temp2: <i16>::from_le_bytes([v[28], v[29]]),
             batt_temp3: <i16>::from_le_bytes([v[30], v[31]]),
             volt_vd0: <i16>::from_le_bytes([v[32], v[33]]),
             volt_vd1: <i16>::from_le_bytes([v[34], v[35]]),
             volt_vd2: <i16>::from_le_bytes([v[36], v[37]]),
-            vip_cnt_ch00: VIPData::from(v[38..44].to_vec()),
-            vip_cnt_ch01: VIPData::from(v[44..50].to_vec()),
-            vip_cnt_ch02: VIPData::from(v[50..56].to_vec()),
-            vip_cnt_ch03: VIPData::from(v[56..62].to_vec()),
-            vip_cnt_ch04: VIPData::from(v[62..68].to_vec()),
-            vip_cnt_ch05: VIPData::from(v[68..74].to_vec()),
-            vip_cnt_ch06: VIPData::from(v[74..80].to_vec()),
-            vip_cnt_ch07: VIPData::from(v[80..86].to_vec()),
-            vip_cnt_ch08: VIPData::from(v[86..92].to_vec()),
-            ccd1: CondChnShortData::from(v[92..100].to_vec()),
-            ccd2: CondChnShortData::from(v[100..108].to_vec()),
-            ccd3: CondChnShortData::from(v[108..116].to_vec()),
-            vip_cnt_ch09: VIPData::from(v[116..122].to_vec()),
-            vip_cnt_ch10: VIPData::from(v[122..128].to_vec()),
-            vip_cnt_ch11: VIPData::from(v[128..134].to_vec()),
-            vip_cnt_ch12: VIPData::from(v[134..140].to_vec()),
-            vip_cnt_ch13: VIPData::from(v[140..146].to_vec()),
-            vip_cnt_ch14: VIPData::from(v[146..152].to_vec()),
-            vip_cnt_ch15: VIPData::from(v[152..158].to_vec()),
-            ccd4: CondChnShortData::from(v[158..166].to_vec()),
-            ccd5: CondChnShortData::from(v[166..174].to_vec()),
-            stat_ch_ext_on: <u16>::from_le_bytes([v[174], v[175]]),
-            stat_ch_ext_ocf: <u16>::from_le_bytes([v[176], v[177]]),
-            vip_cnt_ch16: VIPData::from(v[178..184].to_vec()),
+            vip_cnt_ch00: VIPData::try_from(&v[38..44])?,
+            vip_cnt_ch01: VIPData::try_from(&v[44..50])?,
+            vip_cnt_ch02: VIPData::try_from(&v[50..56])?,
+            vip_cnt_ch03: VIPData::try_from(&v[56..62])?,
+            vip_cnt_ch04: VIPData::try_from(&v[62..68])?,
+            vip_cnt_ch05: VIPData::try_from(&v[68..74])?,
+            vip_cnt_ch06: VIPData::try_from(&v[74..80])?,
+            vip_cnt_ch07: VIPData::try_from(&v[80..86])?,
+            vip_cnt_ch08: VIPData::try_from(&v[86..92])?,
+            ccd1: CondChnShortData::try_from(&v[92..100])?,
+            ccd2: CondChnShortData::try_from(&v[100..108])?,
+            ccd3: CondChnShortData::try_from(&v[108..116])?,
+        })
+    }
+}
+
+impl PIUHk {
+    // Output V, I and P of channel 0 - 16
+    pub fn vip_cnt_ch(&self, ch: u8) -> EpsResult<VIPData> {
+        match ch {
+            0 => Ok(self.vip_cnt_ch00.clone()),
+            1 => Ok(self.vip_cnt_ch01.clone()),
+            2 => Ok(self.vip_cnt_ch02.clone()),
+            3 => Ok(self.vip_cnt_ch03.clone()),
+            4 => Ok(self.vip_cnt_ch04.clone()),
+            5 => Ok(self.vip_cnt_ch05.clone()),
+            6 => Ok(self.vip_cnt_ch06.clone()),
+            7 => Ok(self.vip_cnt_ch07.clone()),
+            8 => Ok(self.vip_cnt_ch08.clone()),
+            9 => Ok(self.vip_cnt_ch09.clone()),
+            10 => Ok(self.vip_cnt_ch10.clone()),
+            11 => Ok(self.vip_cnt_ch11.clone()),
+            12 => Ok(self.vip_cnt_ch12.clone()),
+            13 => Ok(self.vip_cnt_ch13.clone()),
+            14 => Ok(self.vip_cnt_ch14.clone()),
+            15 => Ok(self.vip_cnt_ch15.clone()),
+            16 => Ok(self.vip_cnt_ch16.clone()),
+            _ => Err(EpsError::InvalidInput),
+        }
+    }
+    // Data on conditioning chain 1 - 5
+    pub fn ccd(&self, chain: u8) -> EpsResult<CondChnShortData> {
+        match chain {
+            1 => Ok(self.ccd1.clone()),
+            2 => Ok(self.ccd2.clone()),
+            3 => Ok(self.ccd3.clone()),
+            4 => Ok(self.ccd4.clone()),
+            5 => Ok(self.ccd5.clone()),
+            _ => Err(EpsError::InvalidInput),
+        }
+    }
+}
+
+// Full layout with the daughterboard channels and conditioning chains zeroed and switched off
+impl From<PIUHkShort> for PIUHk {
+    fn from(hk: PIUHkShort) -> PIUHk {
+        PIUHk {
+            volt_brdsup: hk.volt_brdsup,
+            temp: hk.temp,
+            vip_dist_input: hk.vip_dist_input,
+            vip_batt_input: hk.vip_batt_input,
+            stat_ch_on: hk.stat_ch_on,
+            stat_ch_ocf: hk.stat_ch_ocf,
+            batt_stat: hk.batt_stat,
+            batt_temp2: hk.batt_temp2,
+            batt_temp3: hk.batt_temp3,
+            volt_vd0: hk.volt_vd0,
+            volt_vd1: hk.volt_vd1,
+            volt_vd2: hk.volt_vd2,
+            vip_cnt_ch00: hk.vip_cnt_ch00,
+            vip_cnt_ch01: hk.vip_cnt_ch01,
+            vip_cnt_ch02: hk.vip_cnt_ch02,
+            vip_cnt_ch03: hk.vip_cnt_ch03,
+            vip_cnt_ch04: hk.vip_cnt_ch04,
+            vip_cnt_ch05: hk.vip_cnt_ch05,
+            vip_cnt_ch06: hk.vip_cnt_ch06,
+            vip_cnt_ch07: hk.vip_cnt_ch07,
+            vip_cnt_ch08: hk.vip_cnt_ch08,
+            ccd1: hk.ccd1,
+            ccd2: hk.ccd2,
+            ccd3: hk.ccd3,
+            ..Default::default()
+        }
+    }
+}
+
+// Undecoded PIU Housekeeping frame (0xA0, 0xA2 and 0xA4)
+// Keeps the received bytes and only decodes the fields that are accessed,
+// offsets are identical to the PIUHk conversion (response header included).
+// Frames are only built through TryFrom<Vec<u8>>, which checks they hold at
+// least PIU_HK_LEN bytes, deserializing included.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<u8>", into = "Vec<u8>"))]
+pub struct HkFrameRaw {
+    data: Vec<u8>,
+}
+impl TryFrom<Vec<u8>> for HkFrameRaw {
+    type Error = EpsError;
+    fn try_from(data: Vec<u8>) -> EpsResult<HkFrameRaw> {
+        check_len(&data, PIU_HK_LEN)?;
+        Ok(HkFrameRaw { data })
+    }
+}
+impl From<HkFrameRaw> for Vec<u8> {
+    fn from(frame: HkFrameRaw) -> Vec<u8> {
+        frame.data
+    }
+}
+impl HkFrameRaw {
+    // The received bytes, response header included
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    fn i16_at(&self, offset: usize) -> i16 {
+        <i16>::from_le_bytes([self.data[offset], self.data[offset + 1]])
+    }
+    fn u16_at(&self, offset: usize) -> u16 {
+        <u16>::from_le_bytes([self.data[offset], self.data[offset + 1]])
+    }
+    fn vip_at(&self, offset: usize) -> VIPData {
+        VIPData {
+            volt: self.i16_at(offset),
+            curr: self.i16_at(offset + 2),
+            pwr: 10 * i32::from(self.i16_at(offset + 4)),
+        }
+    }
+    fn ccd_at(&self, offset: usize) -> CondChnShortData {
+        CondChnShortData {
+            volt_in_mppt: self.i16_at(offset),
+            curr_in_mppt: self.i16_at(offset + 2),
+            volt_out_mppt: self.i16_at(offset + 4),
+            curr_out_mppt: self.i16_at(offset + 6),
+        }
+    }
+    pub fn volt_brdsup(&self) -> i16 {
+        self.i16_at(6)
+    }
+    pub fn temp(&self) -> i16 {
+        self.i16_at(8)
+    }
+    pub fn vip_dist_input(&self) -> VIPData {
+        self.vip_at(10)
+    }
+    pub fn vip_batt_input(&self) -> VIPData {
+        self.vip_at(16)
+    }
+    pub fn stat_ch_on(&self) -> ChannelOnState {
+        ChannelOnState::from(self.u16_at(22))
+    }
+    pub fn stat_ch_ocf(&self) -> ChannelOverCurrentState {
+        ChannelOverCurrentState::from(self.u16_at(24))
+    }
+    pub fn batt_stat(&self) -> BattPackStatus {
+        BattPackStatus::from(self.u16_at(26))
+    }
+    pub fn batt_temp2(&self) -> i16 {
+        self.i16_at(28)
+    }
+    pub fn batt_temp3(&self) -> i16 {
+        self.i16_at(30)
+    }
+    // Voltage level of domain 0 - 2
+    pub fn volt_vd(&self, vd: u8) -> EpsResult<i16> {
+        match vd {
+            0..=2 => Ok(self.i16_at(32 + 2 * vd as usize)),
+            _ => Err(EpsError::InvalidInput),
+        }
+    }
+    // Output V, I and P of channel 0 - 16
+    pub fn vip_cnt_ch(&self, ch: u8) -> EpsResult<VIPData> {
+        match ch {
+            0..=8 => Ok(self.vip_at(38 + 6 * ch as usize)),
+            9..=15 => Ok(self.vip_at(116 + 6 * (ch as usize - 9))),
+            16 => Ok(self.vip_at(178)),
+            _ => Err(EpsError::InvalidInput),
+        }
+    }
+    // Data on conditioning chain 1 - 5
+    pub fn ccd(&self, chain: u8) -> EpsResult<CondChnShortData> {
+        match chain {
+            1..=3 => Ok(self.ccd_at(92 + 8 * (chain as usize - 1))),
+            4..=5 => Ok(self.ccd_at(158 + 8 * (chain as usize - 4))),
+            _ => Err(EpsError::InvalidInput),
+        }
+    }
+    pub fn stat_ch_ext_on(&self) -> ExtChannelOnState {
+        ExtChannelOnState::from(self.u16_at(174))
+    }
+    pub fn stat_ch_ext_ocf(&self) -> ExtChannelOverCurrentState {
+        ExtChannelOverCurrentState::from(self.u16_at(176))
+    }
+    // Decode the complete frame
+    pub fn decode(&self) -> EpsResult<PIUHk> {
+        PIUHk::try_from(self.data.as_slice())
+    }
+}
+
+#[cfg(all(test, any(feature = "serde", feature = "strum")))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "strum")]
+    #[test]
+    fn selectors_from_strings() {
+        use std::str::FromStr;
+        use strum::IntoEnumIterator;
+        assert_eq!(PIUHkSel::from_str("PIUAvgHK"), Ok(PIUHkSel::PIUAvgHK));
+        assert_eq!(StID::from_str("PiuStid"), Ok(StID::PiuStid));
+        assert!(StID::from_str("Piu").is_err());
+        for sel in PIUHkSel::iter() {
+            assert_eq!(PIUHkSel::from_str(&sel.to_string()), Ok(sel));
+        }
+        for stid in StID::iter() {
+            assert_eq!(StID::from_str(&stid.to_string()), Ok(stid));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn selectors_serde_round_trip() {
+        let sel: PIUHkSel = serde_json::from_str("\"PIURawHK\"").unwrap();
+        assert_eq!(sel, PIUHkSel::PIURawHK);
+        let stid: StID = serde_json::from_str("\"PduStid\"").unwrap();
+        assert_eq!(stid, StID::PduStid);
+        for sel in [PIUHkSel::PIURawHK, PIUHkSel::PIUEngHK, PIUHkSel::PIUAvgHK] {
+            let json = serde_json::to_string(&sel).unwrap();
+            assert_eq!(serde_json::from_str::<PIUHkSel>(&json).unwrap(), sel);
+        }
+        for stid in [
+            StID::PduStid,
+            StID::PbuStid,
+            StID::PcuStid,
+            StID::PiuStid,
+            StID::OverrideStid,
+        ] {
+            let json = serde_json::to_string(&stid).unwrap();
+            assert_eq!(serde_json::from_str::<StID>(&json).unwrap(), stid);
         }
     }
 }
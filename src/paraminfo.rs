@@ -0,0 +1,459 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Configuration parameter metadata
+// Unit, valid range, default and a short description of every configuration
+// parameter, as documented in the ICD. Ranges not narrowed by the ICD are the
+// range of the parameter type. Defaults are None where the value is set per
+// unit at delivery (calibration, thresholds, board ID).
+//
+// The set_config_para_* functions check the value against the range before
+// transmitting, values outside of it are rejected with EpsError::InvalidInput.
+
+use crate::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParamInfo {
+    // Parameter name in the ICD, x and y stand for the battery pack, sensor or voltage domain
+    pub name: &'static str,
+    // Unit of the raw value, empty for plain numbers
+    pub unit: &'static str,
+    pub min: i64,
+    pub max: i64,
+    pub default: Option<i64>,
+    pub description: &'static str,
+}
+
+impl ParamInfo {
+    const fn new(
+        name: &'static str,
+        unit: &'static str,
+        (min, max): (i64, i64),
+        default: Option<i64>,
+        description: &'static str,
+    ) -> Self {
+        ParamInfo {
+            name,
+            unit,
+            min,
+            max,
+            default,
+            description,
+        }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    // InvalidInput for values outside of the range
+    pub fn check(&self, value: i64) -> EpsResult<()> {
+        if !self.contains(value) {
+            #[cfg(feature = "debug")]
+            eps_log! {"{} out of range: {} not in {}..={}",self.name,value,self.min,self.max};
+            return Err(EpsError::InvalidInput);
+        }
+        Ok(())
+    }
+}
+
+// Ranges of the parameter types
+const U32: (i64, i64) = (0, u32::MAX as i64);
+const U16: (i64, i64) = (0, u16::MAX as i64);
+const I16: (i64, i64) = (i16::MIN as i64, i16::MAX as i64);
+const U8: (i64, i64) = (0, u8::MAX as i64);
+const FLAG: (i64, i64) = (0, 1);
+// Battery operating temperature range, in 0.01 degC
+const BATT_TEMP: (i64, i64) = (-4000, 8500);
+// Cell voltage difference in mV
+const CELL_DIFF: (i64, i64) = (0, i16::MAX as i64);
+// Divisor of the temperature conversion
+const POS_DIV: (i64, i64) = (1, i16::MAX as i64);
+
+impl ConfigParamWrite {
+    pub fn info(&self) -> ParamInfo {
+        use ConfigParamWrite::*;
+        match self {
+            ChStartupEnaBf => ParamInfo::new(
+                "CH_STARTUP_ENA_BF",
+                "bitflag",
+                U32,
+                None,
+                "Channels switched on at startup, bit n = channel n",
+            ),
+            ChStartupKey => ParamInfo::new(
+                "CH_STARTUP_KEY",
+                "key",
+                U32,
+                Some(0),
+                "Key unlocking changes of CH_STARTUP_ENA_BF",
+            ),
+            ChLatchoffEnaBf => ParamInfo::new(
+                "CH_LATCHOFF_ENA_BF",
+                "bitflag",
+                U32,
+                None,
+                "Channels latched off on overcurrent, bit n = channel n",
+            ),
+            ChLatchoffKey => ParamInfo::new(
+                "CH_LATCHOFF_KEY",
+                "key",
+                U32,
+                Some(0),
+                "Key unlocking changes of CH_LATCHOFF_ENA_BF",
+            ),
+            TtcWdgTimeout => ParamInfo::new(
+                "TTC_WDG_TIMEOUT",
+                "s",
+                U16,
+                None,
+                "Communication watchdog timeout, 0 disables the watchdog",
+            ),
+            TtcWdgTimeoutKey => ParamInfo::new(
+                "TTC_WDG_TIMEOUT_KEY",
+                "key",
+                U16,
+                Some(0),
+                "Key unlocking changes of TTC_WDG_TIMEOUT",
+            ),
+            ChStartupDelay(_) => ParamInfo::new(
+                "CH_STARTUP_DELAY",
+                "s",
+                U16,
+                Some(0),
+                "Delay of the channel switching on at startup",
+            ),
+            ChLatchoffDelay(_) => ParamInfo::new(
+                "CH_LATCHOFF_DELAY",
+                "s",
+                U16,
+                Some(0),
+                "Delay before the channel is switched on again after an overcurrent latch-off",
+            ),
+            SafetyVoltLoThr => ParamInfo::new(
+                "SAFETY_VOLT_LOTHR",
+                "mV",
+                U16,
+                None,
+                "Battery voltage entering safety mode",
+            ),
+            SafetyVoltHiThr => ParamInfo::new(
+                "SAFETY_VOLT_HITHR",
+                "mV",
+                U16,
+                None,
+                "Battery voltage leaving safety mode",
+            ),
+            LoThrBp1Heater | LoThrBp2Heater | LoThrBp3Heater => ParamInfo::new(
+                "LOTHR_BPx_HEATER",
+                "0.01 degC",
+                BATT_TEMP,
+                None,
+                "Battery pack temperature switching the heater on",
+            ),
+            HiThrBp1Heater | HiThrBp2Heater | HiThrBp3Heater => ParamInfo::new(
+                "HITHR_BPx_HEATER",
+                "0.01 degC",
+                BATT_TEMP,
+                None,
+                "Battery pack temperature switching the heater off",
+            ),
+            LoThrBp1Unbal | LoThrBp2Unbal | LoThrBp3Unbal => ParamInfo::new(
+                "LOTHR_BPx_UNBAL",
+                "mV",
+                CELL_DIFF,
+                None,
+                "Cell voltage difference stopping the cell balancing",
+            ),
+            HiThrBp1Unbal | HiThrBp2Unbal | HiThrBp3Unbal => ParamInfo::new(
+                "HITHR_BPx_UNBAL",
+                "mV",
+                CELL_DIFF,
+                None,
+                "Cell voltage difference starting the cell balancing",
+            ),
+            McuTempBias => ParamInfo::new(
+                "MCU_TEMP_BIAS",
+                "",
+                I16,
+                None,
+                "Bias of the MCU temperature conversion",
+            ),
+            McuTempPremul => ParamInfo::new(
+                "MCU_TEMP_PREMUL",
+                "",
+                I16,
+                None,
+                "Multiplier of the MCU temperature conversion",
+            ),
+            McuTempPosDiv => ParamInfo::new(
+                "MCU_TEMP_POSDIV",
+                "",
+                POS_DIV,
+                None,
+                "Divisor of the MCU temperature conversion",
+            ),
+            Bp1Temp1Bias | Bp1Temp2Bias | Bp1Temp3Bias | Bp2Temp1Bias | Bp2Temp2Bias
+            | Bp2Temp3Bias | Bp3Temp1Bias | Bp3Temp2Bias | Bp3Temp3Bias => ParamInfo::new(
+                "BPx_TEMPy_BIAS",
+                "",
+                I16,
+                None,
+                "Bias of the battery pack temperature sensor conversion",
+            ),
+            Bp1Temp1Premul | Bp1Temp2Premul | Bp1Temp3Premul | Bp2Temp1Premul | Bp2Temp2Premul
+            | Bp2Temp3Premul | Bp3Temp1Premul | Bp3Temp2Premul | Bp3Temp3Premul => ParamInfo::new(
+                "BPx_TEMPy_PREMUL",
+                "",
+                I16,
+                None,
+                "Multiplier of the battery pack temperature sensor conversion",
+            ),
+            Bp1Temp1PosDiv | Bp1Temp2PosDiv | Bp1Temp3PosDiv | Bp2Temp1PosDiv | Bp2Temp2PosDiv
+            | Bp2Temp3PosDiv | Bp3Temp1PosDiv | Bp3Temp2PosDiv | Bp3Temp3PosDiv => ParamInfo::new(
+                "BPx_TEMPy_POSDIV",
+                "",
+                POS_DIV,
+                None,
+                "Divisor of the battery pack temperature sensor conversion",
+            ),
+            BoardId => ParamInfo::new("BOARD_ID", "", U8, None, "Board ID of the unit"),
+            BoardIdKey => ParamInfo::new(
+                "BOARD_ID_KEY",
+                "key",
+                U8,
+                Some(0),
+                "Key unlocking changes of BOARD_ID",
+            ),
+            RavgStrengthP2 => ParamInfo::new(
+                "RAVG_STRENGTH_P2",
+                "2^n",
+                U8,
+                None,
+                "Strength of the running average of the HK data, as power of 2",
+            ),
+            AutoHeatEnaBP1 | AutoHeatEnaBP2 | AutoHeatEnaBP3 => ParamInfo::new(
+                "AUTO_HEAT_ENA_BPx",
+                "flag",
+                FLAG,
+                Some(1),
+                "Automatic battery pack heater control",
+            ),
+            AutoBalEnaBP1 | AutoBalEnaBP2 | AutoBalEnaBP3 => ParamInfo::new(
+                "AUTO_BAL_ENA_BPx",
+                "flag",
+                FLAG,
+                Some(1),
+                "Automatic battery pack cell balancing",
+            ),
+            Vd1AlwaysEna | Vd2AlwaysEna | Vd3AlwaysEna | Vd4AlwaysEna | Vd5AlwaysEna
+            | Vd6AlwaysEna => ParamInfo::new(
+                "VDx_ALWAYS_ENA",
+                "flag",
+                FLAG,
+                Some(0),
+                "Voltage domain kept on regardless of its channels",
+            ),
+            Vd1AlwaysDisa | Vd2AlwaysDisa | Vd3AlwaysDisa | Vd4AlwaysDisa | Vd5AlwaysDisa
+            | Vd6AlwaysDisa => ParamInfo::new(
+                "VDx_ALWAYS_DISA",
+                "flag",
+                FLAG,
+                Some(0),
+                "Voltage domain kept off regardless of its channels",
+            ),
+        }
+    }
+}
+
+impl ConfigParamRead {
+    pub fn info(&self) -> ParamInfo {
+        use ConfigParamRead::*;
+        match self {
+            ChForceEnaUseBf => ParamInfo::new(
+                "CH_FORCE_ENA_USE_BF",
+                "bitflag",
+                U32,
+                None,
+                "Channels forced on, bit n = channel n",
+            ),
+            ChStartUpEnaUseBf => ParamInfo::new(
+                "CH_STARTUP_ENA_USE_BF",
+                "bitflag",
+                U32,
+                None,
+                "Channels switched on at startup in use",
+            ),
+            ChLatchoffEnaUseBf => ParamInfo::new(
+                "CH_LATCHOFF_ENA_USE_BF",
+                "bitflag",
+                U32,
+                None,
+                "Channels latched off on overcurrent in use",
+            ),
+            Vd1AllocChBf | Vd2AllocChBf | Vd3AllocChBf | Vd4AllocChBf | Vd5AllocChBf
+            | Vd6AllocChBf => ParamInfo::new(
+                "VDx_ALLOC_CH_BF",
+                "bitflag",
+                U32,
+                None,
+                "Channels allocated to the voltage domain",
+            ),
+            SwciChCmdEnaBf => ParamInfo::new(
+                "SWCI_CH_CMD_ENA_BF",
+                "bitflag",
+                U32,
+                None,
+                "Channels that can be switched on by command",
+            ),
+            SwciChCmdDisaBf => ParamInfo::new(
+                "SWCI_CH_CMD_DISA_BF",
+                "bitflag",
+                U32,
+                None,
+                "Channels that can be switched off by command",
+            ),
+            TtcI2cSlaveAddr => ParamInfo::new(
+                "TTC_I2C_SLAVE_ADDR",
+                "",
+                U16,
+                None,
+                "I2C address of the unit",
+            ),
+            ConfNvmSaveCntr => ParamInfo::new(
+                "CONF_NVM_SAVE_CNTR",
+                "",
+                U16,
+                None,
+                "Number of configuration saves to NVM",
+            ),
+            ConfNvmSaveChks => ParamInfo::new(
+                "CONF_NVM_SAVE_CHKS",
+                "",
+                U16,
+                None,
+                "Checksum of the configuration saved in NVM",
+            ),
+            RstCause => ParamInfo::new("RST_CAUSE", "", U16, None, "Cause of the last reset"),
+            RstCntrPwron => {
+                ParamInfo::new("RST_CNTR_PWRON", "", U16, None, "Number of power-on resets")
+            }
+            RstCntrWdg => {
+                ParamInfo::new("RST_CNTR_WDG", "", U16, None, "Number of watchdog resets")
+            }
+            RstCntrCmd => {
+                ParamInfo::new("RST_CNTR_CMD", "", U16, None, "Number of commanded resets")
+            }
+            RstCntrMcu => ParamInfo::new("RST_CNTR_MCU", "", U16, None, "Number of MCU resets"),
+            RstCntrEmlopo => ParamInfo::new(
+                "RST_CNTR_EMLOPO",
+                "",
+                U16,
+                None,
+                "Number of emergency low power resets",
+            ),
+            RstCntrMcuRaw => {
+                ParamInfo::new("RST_CNTR_MCU_RAW", "", U16, None, "Raw MCU reset counter")
+            }
+            EmlopoVoltLoThr => ParamInfo::new(
+                "EMLOPO_VOLT_LOTHR",
+                "mV",
+                U16,
+                None,
+                "Battery voltage entering emergency low power mode",
+            ),
+            EmlopoVoltHiThr => ParamInfo::new(
+                "EMLOPO_VOLT_HITHR",
+                "mV",
+                U16,
+                None,
+                "Battery voltage leaving emergency low power mode",
+            ),
+            EmlopoPeriod => ParamInfo::new(
+                "EMLOPO_PERIOD",
+                "s",
+                U16,
+                None,
+                "Period of the emergency low power mode",
+            ),
+            SafetyVoltLoThrUsed => ParamInfo::new(
+                "SAFETY_VOLT_LOTHR_USED",
+                "mV",
+                U16,
+                None,
+                "Safety mode entry voltage in use",
+            ),
+            SafetyVoltHiThrUsed => ParamInfo::new(
+                "SAFETY_VOLT_HITHR_USED",
+                "mV",
+                U16,
+                None,
+                "Safety mode exit voltage in use",
+            ),
+            SafetyLinger => ParamInfo::new(
+                "SAFETY_LINGER",
+                "s",
+                U16,
+                None,
+                "Minimum time spent in safety mode",
+            ),
+            TtcWdgTimeoutUsed => ParamInfo::new(
+                "TTC_WDG_TIMEOUT_USED",
+                "s",
+                U16,
+                None,
+                "Communication watchdog timeout in use",
+            ),
+            TtcPevCmdElapsed => ParamInfo::new(
+                "TTC_PREV_CMD_ELAPSED",
+                "s",
+                U16,
+                None,
+                "Time since the last command",
+            ),
+            AdcMcuTempV25T30 => ParamInfo::new(
+                "ADC_MCU_TEMP_V25_T30",
+                "",
+                I16,
+                None,
+                "MCU temperature sensor calibration at 30 degC",
+            ),
+            AdcMcuTempV25T85 => ParamInfo::new(
+                "ADC_MCU_TEMP_V25_T85",
+                "",
+                I16,
+                None,
+                "MCU temperature sensor calibration at 85 degC",
+            ),
+            Stid => ParamInfo::new("STID", "", U8, None, "System type ID of the unit"),
+            Ivid => ParamInfo::new("IVID", "", U8, None, "Interface version ID of the unit"),
+            BidUsed => ParamInfo::new("BID_USED", "", U8, None, "Board ID in use"),
+            BootResumeShort => ParamInfo::new(
+                "BOOT_RESUME_SHORT",
+                "flag",
+                FLAG,
+                None,
+                "Last boot kept the output state instead of running the startup sequence",
+            ),
+            ConfParamChanged => ParamInfo::new(
+                "CONF_PARAM_CHANGED",
+                "flag",
+                FLAG,
+                None,
+                "Active configuration differs from the one saved in NVM",
+            ),
+        }
+    }
+}
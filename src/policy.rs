@@ -0,0 +1,167 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel switching policy
+// Hard rules on the output channels, checked by set_single_output() and the
+// bus group commands before anything is sent to the EPS:
+// - requirements, e.g. "CH14 requires CH0 on"
+// - exclusive groups, e.g. "never CH12 and CH13 on together"
+// A violation is refused with EpsError::PolicyViolation(channel, conflicting
+// channel). Unlike the channel dependencies (dependency.rs) the policy has no
+// override, clear it with set_policy(ChannelPolicy::new()) in a contingency.
+
+use crate::dependency::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChannelPolicy {
+    requires: ChannelDependencies,
+    // Bitmasks of channels of which at most one may be on
+    exclusive: Vec<u32>,
+}
+
+impl ChannelPolicy {
+    pub fn new() -> Self {
+        ChannelPolicy::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requires.is_empty() && self.exclusive.is_empty()
+    }
+
+    // channel may only be switched on while prerequisite is on
+    pub fn require(&mut self, channel: ChannelId, prerequisite: ChannelId) -> EpsResult<()> {
+        self.requires.require(channel, prerequisite)
+    }
+
+    // At most one of channels may be on at a time
+    pub fn exclusive(&mut self, channels: &[ChannelId]) -> EpsResult<()> {
+        if channels.len() < 2 || channels.iter().any(|c| *c > MAX_CHANNEL) {
+            return Err(EpsError::InvalidInput);
+        }
+        self.exclusive
+            .push(channels.iter().fold(0, |acc, c| acc | 1 << c));
+        Ok(())
+    }
+
+    pub fn requirements(&self) -> &ChannelDependencies {
+        &self.requires
+    }
+
+    pub fn exclusive_groups(&self) -> &[u32] {
+        &self.exclusive
+    }
+
+    // Check switching on/off (bitmasks) with the channels in current on.
+    // Returns the first (channel, conflicting channel) pair.
+    pub fn check(&self, on: u32, off: u32, current: u32) -> Result<(), (ChannelId, ChannelId)> {
+        self.requires.check(on, off, current)?;
+        let after = (current | on) & !off;
+        for group in &self.exclusive {
+            if on & group != 0 && (after & group).count_ones() > 1 {
+                let channel = (on & group).trailing_zeros();
+                let other = (after & group & !(1 << channel)).trailing_zeros();
+                return Err((channel as ChannelId, other as ChannelId));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Eps {
+    pub fn policy(&self) -> &ChannelPolicy {
+        &self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: ChannelPolicy) {
+        self.policy = policy;
+    }
+
+    // Check switching on/off (bitmasks) against the dependencies and the policy,
+    // reading the channel states once if there is anything to check
+    pub(crate) fn check_switching(&self, on: u32, off: u32) -> EpsResult<()> {
+        let dependencies = !self.dependency_override && !self.dependencies.is_empty();
+        if !dependencies && self.policy.is_empty() {
+            return Ok(());
+        }
+        let states = self.channel_states()?;
+        let current = states.on.on() as u32 | (states.ext_on.bits() as u32) << 16;
+        if dependencies {
+            self.check_dependencies(on, off, current)?;
+        }
+        match self.policy.check(on, off, current) {
+            Ok(()) => Ok(()),
+            Err((channel, other)) => {
+                #[cfg(feature = "debug")]
+                eps_log! {"Channel {} conflicts with channel {} by policy",channel,other};
+                Err(EpsError::PolicyViolation(channel, other))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::*;
+
+    #[test]
+    fn exclusive_groups() {
+        let mut policy = ChannelPolicy::new();
+        assert_eq!(policy.exclusive(&[12]), Err(EpsError::InvalidInput));
+        assert_eq!(
+            policy.exclusive(&[12, MAX_CHANNEL + 1]),
+            Err(EpsError::InvalidInput)
+        );
+        policy.exclusive(&[12, 13]).unwrap();
+        assert_eq!(policy.check(1 << 13, 0, 1 << 12), Err((13, 12)));
+        assert_eq!(policy.check(1 << 12 | 1 << 13, 0, 0), Err((12, 13)));
+        // Swapping in one command is fine, as is switching off
+        assert_eq!(policy.check(1 << 13, 1 << 12, 1 << 12), Ok(()));
+        assert_eq!(policy.check(0, 1 << 13, 1 << 12 | 1 << 13), Ok(()));
+        assert_eq!(policy.check(1 << 12, 0, 1 << 5), Ok(()));
+    }
+
+    #[test]
+    fn requirements() {
+        let mut policy = ChannelPolicy::new();
+        policy.require(14, 0).unwrap();
+        assert_eq!(policy.require(0, 14), Err(EpsError::InvalidInput));
+        assert_eq!(policy.check(1 << 14, 0, 0), Err((14, 0)));
+        assert_eq!(policy.check(1 << 14, 0, 1 << 0), Ok(()));
+        assert_eq!(policy.check(0, 1 << 0, 1 << 0 | 1 << 14), Err((14, 0)));
+    }
+
+    #[test]
+    fn switching_refused() {
+        let mock = MockEps::new();
+        let mut eps = Eps::with_transport(mock.clone()).unwrap();
+        let mut policy = ChannelPolicy::new();
+        policy.exclusive(&[12, 13]).unwrap();
+        eps.set_policy(policy);
+        eps.set_single_output(BusChannel::On, Channel::Ch12_VD3_5V4)
+            .unwrap();
+        assert_eq!(
+            eps.set_single_output(BusChannel::On, Channel::Ch13_VD3_5V4),
+            Err(EpsError::PolicyViolation(13, 12))
+        );
+        assert_eq!(mock.channels(), 1 << 12);
+    }
+}
@@ -0,0 +1,256 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Housekeeping poller
+// Reads data products in a background thread, each at its own rate, and sends
+// the results through a channel. Products are scheduled on a fixed grid per
+// product (start + offset + n * period), so the read time doesn't drift with
+// bus latency. Offsets are spread by the minimum spacing and reads are kept at
+// least that far apart, so products with commensurate rates don't all hit the
+// bus on the same tick. A read that is more than one period late is skipped, as
+// are the grid points passed while a read was still running, so a slow bus
+// doesn't build up a backlog of back-to-back reads. Failed reads are retried
+// after the spacing while there is time left before the next grid point.
+// Samples are delivered through a channel, a callback or an HkWatch.
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+use crate::watch::*;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PollProduct {
+    Hk(HkSelection),
+    // Overcurrent fault state (0x42)
+    OverCurrent,
+    // Checksum over all config parameters, to watch for config changes
+    ConfigChecksum,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PollData {
+    Hk(HkData),
+    OverCurrent(OverCurrentFaultState),
+    ConfigChecksum(u16),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollSample {
+    pub product: PollProduct,
+    // When the read was scheduled and how late it started
+    pub due: Instant,
+    pub late: Duration,
+    // Number of reads it took, more than 1 if failed reads were retried
+    pub attempts: u32,
+    pub data: EpsResult<PollData>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PollRate {
+    pub product: PollProduct,
+    pub period: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HkPoller {
+    rates: Vec<PollRate>,
+    // Minimum time between the end of one read and the start of the next
+    spacing: Duration,
+    // Retries of a failed read
+    retries: u32,
+}
+
+impl HkPoller {
+    pub fn new(spacing: Duration) -> Self {
+        HkPoller {
+            rates: Vec::new(),
+            spacing,
+            retries: 0,
+        }
+    }
+
+    // Poll product every period, e.g. Duration::from_secs(1) for 1 Hz
+    pub fn add(mut self, product: PollProduct, period: Duration) -> Self {
+        if !period.is_zero() {
+            self.rates.push(PollRate { product, period });
+        }
+        self
+    }
+
+    // Retry a failed read up to retries times (none by default)
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn rates(&self) -> &[PollRate] {
+        &self.rates
+    }
+
+    // Start polling in a background thread, every sample is sent to tx
+    pub fn spawn(&self, eps: Arc<Mutex<Eps>>, tx: Sender<PollSample>) -> HkPollerHandle {
+        self.spawn_with(eps, move |sample| tx.send(sample).is_ok())
+    }
+
+    // Start polling in a background thread, callback is called with every sample
+    pub fn spawn_callback<F>(&self, eps: Arc<Mutex<Eps>>, mut callback: F) -> HkPollerHandle
+    where
+        F: FnMut(PollSample) + Send + 'static,
+    {
+        self.spawn_with(eps, move |sample| {
+            callback(sample);
+            true
+        })
+    }
+
+    // Start polling in a background thread, the latest successful read of
+    // each product is published on the returned HkWatch
+    pub fn spawn_watched(&self, eps: Arc<Mutex<Eps>>) -> (HkPollerHandle, HkWatch) {
+        let watches: HashMap<PollProduct, Watch<PollData>> = self
+            .rates
+            .iter()
+            .map(|r| (r.product.clone(), Watch::new()))
+            .collect();
+        let watch = HkWatch::new(
+            watches
+                .iter()
+                .map(|(p, w)| (p.clone(), w.subscribe()))
+                .collect(),
+        );
+        let handle = self.spawn_with(eps, move |sample| {
+            if let (Some(w), Ok(data)) = (watches.get(&sample.product), sample.data) {
+                w.send(data);
+            }
+            true
+        });
+        (handle, watch)
+    }
+
+    // Polling stops when sink returns false
+    fn spawn_with<F>(&self, eps: Arc<Mutex<Eps>>, mut sink: F) -> HkPollerHandle
+    where
+        F: FnMut(PollSample) -> bool + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel();
+        let rates = self.rates.clone();
+        let spacing = self.spacing;
+        let retries = self.retries;
+        let thread = thread::spawn(move || {
+            let start = Instant::now();
+            let mut due: Vec<Instant> = (0..rates.len())
+                .map(|i| start + spacing * i as u32)
+                .collect();
+            let mut skipped = 0u64;
+            let mut next_free = start;
+            'poll: while let Some(i) = (0..due.len()).min_by_key(|i| due[*i]) {
+                let at = due[i].max(next_free);
+                let wait = at.saturating_duration_since(Instant::now());
+                match stopped.recv_timeout(wait) {
+                    Err(RecvTimeoutError::Timeout) => (),
+                    _ => break,
+                }
+                let now = Instant::now();
+                let period = rates[i].period;
+                if now.duration_since(due[i]) > period {
+                    // Missed, continue at the next grid point
+                    skipped += 1;
+                    let n = now.duration_since(due[i]).as_nanos() / period.as_nanos();
+                    due[i] += period * n as u32;
+                    continue;
+                }
+                let mut attempts = 0;
+                let data = loop {
+                    attempts += 1;
+                    let data = read(
+                        &eps.lock().unwrap_or_else(PoisonError::into_inner),
+                        &rates[i].product,
+                    );
+                    if data.is_ok()
+                        || attempts > retries
+                        || Instant::now() + spacing >= due[i] + period
+                    {
+                        break data;
+                    }
+                    match stopped.recv_timeout(spacing) {
+                        Err(RecvTimeoutError::Timeout) => (),
+                        _ => break 'poll,
+                    }
+                };
+                let sample = PollSample {
+                    product: rates[i].product.clone(),
+                    due: due[i],
+                    late: now.duration_since(due[i]),
+                    attempts,
+                    data,
+                };
+                if !sink(sample) {
+                    break;
+                }
+                due[i] += period;
+                let end = Instant::now();
+                // Grid points passed during the read overlap it
+                while due[i] <= end {
+                    skipped += 1;
+                    due[i] += period;
+                }
+                next_free = end + spacing;
+            }
+            skipped
+        });
+        HkPollerHandle {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+fn read(eps: &Eps, product: &PollProduct) -> EpsResult<PollData> {
+    match product {
+        PollProduct::Hk(sel) => match eps.collect_hk(std::slice::from_ref(sel))?.pop() {
+            Some(hk) => Ok(PollData::Hk(hk)),
+            None => Err(EpsError::Err),
+        },
+        PollProduct::OverCurrent => Ok(PollData::OverCurrent(eps.overcurrent_state()?)),
+        PollProduct::ConfigChecksum => Ok(PollData::ConfigChecksum(eps.calculate_checksum()?)),
+    }
+}
+
+// Handle of a running HkPoller, the thread is stopped when the handle is dropped
+pub struct HkPollerHandle {
+    stop: Sender<()>,
+    thread: Option<JoinHandle<u64>>,
+}
+impl HkPollerHandle {
+    // Stop polling, returns the number of reads skipped because they were too late or overlapped
+    pub fn stop(mut self) -> u64 {
+        self.shutdown().unwrap_or_default()
+    }
+
+    fn shutdown(&mut self) -> Option<u64> {
+        let _ = self.stop.send(());
+        self.thread.take().and_then(|t| t.join().ok())
+    }
+}
+impl Drop for HkPollerHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
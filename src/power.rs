@@ -0,0 +1,104 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Power budget summary
+// Condenses one engineering HK read into the power flows of the system, in W:
+// generation is the MPPT output of the conditioning chains, consumption the
+// input of the distribution part and battery_net_w the battery input power,
+// positive when charging. The integrated ICEPSv2 (PIU target) is read with a
+// single PIU HK command; modular systems read PDU and PCU HK, which carry no
+// battery data, so battery_net_w is the balance of generation and consumption.
+
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PowerSummary {
+    pub generation_w: f32,
+    pub consumption_w: f32,
+    pub battery_net_w: f32,
+    // Output power of each channel reported by the HK
+    pub per_channel: Vec<(Channel, f32)>,
+}
+
+// MPPT output power of a conditioning chain, mV x mA
+fn ccd_watts(ccd: &CondChnShortData) -> f32 {
+    ccd.volt_out_mppt as f32 * ccd.curr_out_mppt as f32 / 1_000_000.0
+}
+
+impl From<&PIUHk> for PowerSummary {
+    fn from(hk: &PIUHk) -> PowerSummary {
+        let generation_w = [&hk.ccd1, &hk.ccd2, &hk.ccd3, &hk.ccd4, &hk.ccd5]
+            .into_iter()
+            .map(ccd_watts)
+            .sum();
+        let per_channel = Channel::ALL
+            .iter()
+            .filter_map(|ch| hk.vip_cnt_ch(ch.index()).ok().map(|vip| (*ch, vip.watts())))
+            .collect();
+        PowerSummary {
+            generation_w,
+            consumption_w: hk.vip_dist_input.watts(),
+            battery_net_w: hk.vip_batt_input.watts(),
+            per_channel,
+        }
+    }
+}
+
+impl PowerSummary {
+    // Summary of a modular system from its PDU and PCU HK
+    pub fn from_pdu_pcu(pdu: &PDUHk, pcu: &PCUHk) -> PowerSummary {
+        let generation_w = pcu.vip_output.watts();
+        let consumption_w = pdu.vip_input().watts();
+        // The PDU reports channels 0 - 15
+        let per_channel = Channel::ALL
+            .iter()
+            .filter_map(|ch| {
+                pdu.vip_cnt_ch(ch.index())
+                    .ok()
+                    .map(|vip| (*ch, vip.watts()))
+            })
+            .collect();
+        PowerSummary {
+            generation_w,
+            consumption_w,
+            battery_net_w: generation_w - consumption_w,
+            per_channel,
+        }
+    }
+}
+
+impl Eps {
+    // Power flows from one engineering HK read, PIU HK for the integrated
+    // system and PDU + PCU HK otherwise
+    pub fn power_summary(&self) -> EpsResult<PowerSummary> {
+        match self.target() {
+            StID::PiuStid => {
+                let hk = self.piu_hk(PIUHkSel::PIUEngHK)?;
+                Ok(PowerSummary::from(&hk))
+            }
+            _ => {
+                let pdu = self.pdu_hk(PDUHkSel::PDUEngHK)?;
+                let pcu = self.pcu_hk(PCUHkSel::PCUEngHK)?;
+                Ok(PowerSummary::from_pdu_pcu(&pdu, &pcu))
+            }
+        }
+    }
+}
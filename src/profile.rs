@@ -0,0 +1,129 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mission profiles
+// A mission profile is a JSON file with named channel sets, schedules and
+// threshold sets, so mission specific behaviour can be changed without
+// rebuilding the service, e.g.
+// {
+//     "name": "nominal",
+//     "channel_sets": { "payload": [4, 5], "adcs": [2] },
+//     "schedules": [
+//         { "name": "imaging", "start": 600, "period": 5400, "channel_set": "payload", "action": "On" }
+//     ],
+//     "thresholds": { "low_power": { "batt_volt_mv": 6800.0 } }
+// }
+
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+// Named thresholds, e.g. "batt_volt_mv" -> 6800.0
+pub type ThresholdSet = BTreeMap<String, f64>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScheduleEntry {
+    pub name: String,
+    // Seconds after the schedule epoch chosen by the user (e.g. mission start)
+    pub start: u64,
+    // Repeat period in seconds, None for a single shot
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub period: Option<u64>,
+    // Name of the channel set to switch
+    pub channel_set: String,
+    // On or Off
+    pub action: BusChannel,
+}
+impl ScheduleEntry {
+    // Next start at or after t (seconds after the schedule epoch)
+    pub fn next_after(&self, t: u64) -> Option<u64> {
+        if t <= self.start {
+            return Some(self.start);
+        }
+        match self.period {
+            Some(p) if p > 0 => Some(self.start + (t - self.start).div_ceil(p) * p),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MissionProfile {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub channel_sets: BTreeMap<String, Vec<ChannelId>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub schedules: Vec<ScheduleEntry>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub thresholds: BTreeMap<String, ThresholdSet>,
+}
+impl MissionProfile {
+    pub fn load<P: AsRef<Path>>(path: P) -> EpsResult<Self> {
+        match fs::read_to_string(path) {
+            Ok(x) => MissionProfile::from_json(&x),
+            Err(e) => Err(EpsError::Profile(e.kind())),
+        }
+    }
+
+    pub fn from_json(json: &str) -> EpsResult<Self> {
+        let profile: MissionProfile = match serde_json::from_str(json) {
+            Ok(x) => x,
+            Err(_) => return Err(EpsError::Profile(ErrorKind::InvalidData)),
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    // Channel indices have to be valid output bus channels (0 - 15) and
+    // schedules have to switch an existing channel set
+    pub fn validate(&self) -> EpsResult<()> {
+        let invalid_channel = self
+            .channel_sets
+            .values()
+            .any(|set| set.iter().any(|ch| *ch > 15));
+        let invalid_schedule = self.schedules.iter().any(|s| {
+            !self.channel_sets.contains_key(&s.channel_set) || s.action == BusChannel::Keep
+        });
+        if invalid_channel || invalid_schedule {
+            return Err(EpsError::Profile(ErrorKind::InvalidData));
+        }
+        Ok(())
+    }
+
+    pub fn channel_set(&self, name: &str) -> Option<&[ChannelId]> {
+        self.channel_sets.get(name).map(|s| s.as_slice())
+    }
+
+    // Channel set as bitflag, bit n = channel n
+    pub fn channel_mask(&self, name: &str) -> Option<u16> {
+        self.channel_set(name)
+            .map(|s| s.iter().fold(0u16, |m, ch| m | (1 << ch)))
+    }
+
+    pub fn threshold_set(&self, name: &str) -> Option<&ThresholdSet> {
+        self.thresholds.get(name)
+    }
+
+    pub fn threshold(&self, set: &str, name: &str) -> Option<f64> {
+        self.threshold_set(set).and_then(|s| s.get(name).copied())
+    }
+}
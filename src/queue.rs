@@ -0,0 +1,184 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command queue with priority lanes
+// Commands from several users of one EPS are executed in order by a worker
+// thread. Watchdog kicks, mode switches and shutdowns go into the high
+// priority lane, which is always served before queued bulk HK and config
+// traffic. A high priority command waits at most for the one transfer already
+// in progress, whatever the length of the normal lane.
+// Telemetry polls can be queued in the low lane, operational commands
+// (channel switching, config) in the normal lane overtake them. Gaps between
+// the commands are left to the pacing profile and rate limit of the Eps.
+
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+use crate::shared::*;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Priority {
+    High,
+    Normal,
+    // Telemetry polls, served when no other command is queued
+    Low,
+}
+
+type Job = Box<dyn FnOnce(&Eps) + Send>;
+
+#[derive(Default)]
+struct Lanes {
+    high: VecDeque<Job>,
+    normal: VecDeque<Job>,
+    low: VecDeque<Job>,
+    stopped: bool,
+}
+
+pub struct CommandQueue {
+    lanes: Arc<(Mutex<Lanes>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CommandQueue {
+    // Start the worker thread
+    pub fn spawn(eps: SharedEps) -> Self {
+        let lanes = Arc::new((Mutex::new(Lanes::default()), Condvar::new()));
+        let worker = lanes.clone();
+        let thread = thread::spawn(move || loop {
+            let job = {
+                let (lock, cvar) = &*worker;
+                let mut l = match lock.lock() {
+                    Ok(l) => l,
+                    Err(_) => return,
+                };
+                loop {
+                    if let Some(job) = l.high.pop_front() {
+                        break job;
+                    }
+                    if l.stopped {
+                        return;
+                    }
+                    if let Some(job) = l.normal.pop_front() {
+                        break job;
+                    }
+                    if let Some(job) = l.low.pop_front() {
+                        break job;
+                    }
+                    l = match cvar.wait(l) {
+                        Ok(l) => l,
+                        Err(_) => return,
+                    };
+                }
+            };
+            job(&eps.lock());
+        });
+        CommandQueue {
+            lanes,
+            thread: Some(thread),
+        }
+    }
+
+    // Queue f, the result is delivered on the returned receiver.
+    // The receiver is disconnected without a result if the queue is stopped first.
+    pub fn submit<R, F>(&self, priority: Priority, f: F) -> Receiver<EpsResult<R>>
+    where
+        R: Send + 'static,
+        F: FnOnce(&Eps) -> EpsResult<R> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let job: Job = Box::new(move |eps| {
+            let _ = tx.send(f(eps));
+        });
+        let (lock, cvar) = &*self.lanes;
+        if let Ok(mut l) = lock.lock() {
+            if !l.stopped {
+                match priority {
+                    Priority::High => l.high.push_back(job),
+                    Priority::Normal => l.normal.push_back(job),
+                    Priority::Low => l.low.push_back(job),
+                }
+                cvar.notify_one();
+            }
+        }
+        rx
+    }
+
+    // Queued (high, normal, low) commands, not counting the one in progress
+    pub fn pending(&self) -> (usize, usize, usize) {
+        match self.lanes.0.lock() {
+            Ok(l) => (l.high.len(), l.normal.len(), l.low.len()),
+            Err(_) => (0, 0, 0),
+        }
+    }
+
+    // Queue a telemetry poll, e.g. queue.poll(|eps| eps.pdu_hk(PDUHkSel::PDURawHK))
+    pub fn poll<R, F>(&self, f: F) -> Receiver<EpsResult<R>>
+    where
+        R: Send + 'static,
+        F: FnOnce(&Eps) -> EpsResult<R> + Send + 'static,
+    {
+        self.submit(Priority::Low, f)
+    }
+
+    pub fn set_single_output(
+        &self,
+        typ_channel: BusChannel,
+        channel: Channel,
+    ) -> Receiver<EpsResult<()>> {
+        self.submit(Priority::Normal, move |eps| {
+            eps.set_single_output(typ_channel, channel)
+        })
+    }
+
+    pub fn watchdog_reset(&self) -> Receiver<EpsResult<()>> {
+        self.submit(Priority::High, |eps| eps.watchdog_reset())
+    }
+
+    pub fn mode_switch(&self, mode: ModeSwitch) -> Receiver<EpsResult<()>> {
+        self.submit(Priority::High, move |eps| eps.mode_switch(mode))
+    }
+
+    // Needs the EPS to be armed, see Eps::arm()
+    pub fn shutdown_all(&self) -> Receiver<EpsResult<()>> {
+        self.submit(Priority::High, |eps| eps.shutdown_all())
+    }
+
+    // Stop the worker after the high priority lane, dropping queued normal and low commands
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        let (lock, cvar) = &*self.lanes;
+        if let Ok(mut l) = lock.lock() {
+            l.stopped = true;
+            l.normal.clear();
+            l.low.clear();
+            cvar.notify_all();
+        }
+        if let Some(t) = self.thread.take() {
+            let _ = t.join();
+        }
+    }
+}
+impl Drop for CommandQueue {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
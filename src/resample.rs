@@ -0,0 +1,107 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resampling of housekeeping history onto a fixed time grid
+// Samples are (time, value) pairs sorted by time, the time base is up to the
+// caller (e.g. UNIX seconds of the OBC). Grid points outside of the sampled
+// span are None, no extrapolation is done.
+
+#[cfg(feature = "serde")]
+use serde::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Interpolation {
+    Nearest,
+    Linear,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeGrid {
+    pub start: f64,
+    pub step: f64,
+    pub len: usize,
+}
+impl TimeGrid {
+    // Grid covering start..=end with the given step
+    pub fn span(start: f64, end: f64, step: f64) -> Self {
+        let len = if step > 0.0 && end >= start {
+            ((end - start) / step).floor() as usize + 1
+        } else {
+            0
+        };
+        TimeGrid { start, step, len }
+    }
+
+    pub fn times(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..self.len).map(move |i| self.start + i as f64 * self.step)
+    }
+}
+
+// Named accessor of a numeric field of a housekeeping struct
+pub type FieldFn<T> = (&'static str, fn(&T) -> f64);
+
+// Resample one series onto the grid
+pub fn resample(
+    samples: &[(f64, f64)],
+    grid: &TimeGrid,
+    interp: Interpolation,
+) -> Vec<Option<f64>> {
+    grid.times()
+        .map(|t| sample_at(samples, t, interp))
+        .collect()
+}
+
+// Resample selected fields of a history of housekeeping structs, one series per field
+pub fn resample_fields<T>(
+    history: &[(f64, T)],
+    fields: &[FieldFn<T>],
+    grid: &TimeGrid,
+    interp: Interpolation,
+) -> Vec<(String, Vec<Option<f64>>)> {
+    fields
+        .iter()
+        .map(|(name, field)| {
+            let series: Vec<(f64, f64)> = history.iter().map(|(t, x)| (*t, field(x))).collect();
+            (name.to_string(), resample(&series, grid, interp))
+        })
+        .collect()
+}
+
+fn sample_at(samples: &[(f64, f64)], t: f64, interp: Interpolation) -> Option<f64> {
+    let first = samples.first()?;
+    let last = samples.last()?;
+    if t < first.0 || t > last.0 {
+        return None;
+    }
+    // First sample at or after t
+    let i = samples.partition_point(|(ts, _)| *ts < t);
+    let (t1, v1) = samples[i];
+    if i == 0 || t1 == t {
+        return Some(v1);
+    }
+    let (t0, v0) = samples[i - 1];
+    match interp {
+        Interpolation::Nearest => {
+            if t - t0 <= t1 - t {
+                Some(v0)
+            } else {
+                Some(v1)
+            }
+        }
+        Interpolation::Linear => Some(v0 + (v1 - v0) * (t - t0) / (t1 - t0)),
+    }
+}
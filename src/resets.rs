@@ -0,0 +1,94 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reset statistics
+// Collects the reset cause and counters of the system status (0x40) and the
+// RST_* read-only config parameters into one report for ground tooling.
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResetStats {
+    // Cause of the last reset, from the system status
+    pub cause: ResetCause,
+    // RST_CAUSE as stored in the config
+    pub cause_raw: u16,
+    // Seconds since the last reset
+    pub uptime: u32,
+    // Reset counters since begin of life cycle (RST_CNTR_*)
+    pub power_on: u16,
+    pub watchdog: u16,
+    pub commanded: u16,
+    pub mcu: u16,
+    pub low_power: u16,
+    // MCU resets including the ones not attributed to an EPS upset (RST_CNTR_MCU_RAW)
+    pub mcu_raw: u16,
+}
+impl ResetStats {
+    // Resets of all causes since begin of life cycle
+    pub fn total(&self) -> u32 {
+        [
+            self.power_on,
+            self.watchdog,
+            self.commanded,
+            self.mcu,
+            self.low_power,
+        ]
+        .iter()
+        .map(|c| *c as u32)
+        .sum()
+    }
+    // Counter of a reset cause
+    pub fn count(&self, cause: &ResetCause) -> u16 {
+        match cause {
+            ResetCause::PowerOn => self.power_on,
+            ResetCause::Watchdog => self.watchdog,
+            ResetCause::Commanded => self.commanded,
+            ResetCause::EpsUpset => self.mcu,
+            ResetCause::EmergLowPwr => self.low_power,
+        }
+    }
+}
+
+impl Eps {
+    // Reset cause, uptime and all reset counters in one report
+    pub fn reset_statistics(&self) -> EpsResult<ResetStats> {
+        let status = self.system_status()?;
+        let read = |param: ConfigParamRead| -> EpsResult<u16> {
+            match self.get_config_para_read(param)? {
+                Output::U16(x) => Ok(x),
+                _ => Err(EpsError::InvalidInput),
+            }
+        };
+        Ok(ResetStats {
+            cause: status.reset_cause(),
+            cause_raw: read(ConfigParamRead::RstCause)?,
+            uptime: status.uptime_secs(),
+            power_on: read(ConfigParamRead::RstCntrPwron)?,
+            watchdog: read(ConfigParamRead::RstCntrWdg)?,
+            commanded: read(ConfigParamRead::RstCntrCmd)?,
+            mcu: read(ConfigParamRead::RstCntrMcu)?,
+            low_power: read(ConfigParamRead::RstCntrEmlopo)?,
+            mcu_raw: read(ConfigParamRead::RstCntrMcuRaw)?,
+        })
+    }
+}
@@ -0,0 +1,99 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Retries of failed transfers
+// A single NACK or arbitration loss on the bus shouldn't reach mission code
+// as TransferError. With a RetryPolicy set, Eps::transfer() repeats a failed
+// transfer after a backoff that doubles with every attempt. Commands that
+// must not execute twice (reset, relative time correction) are never
+// repeated, the unit may have executed them before the transfer failed.
+
+use crate::eps::*;
+use crate::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+// Error classes a transfer is retried on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RetryOn {
+    // Bus errors of the transport (NACK, arbitration loss, timeout)
+    pub bus: bool,
+    // Responses that are too short or deviate from the ICD (strict mode)
+    pub response: bool,
+}
+impl Default for RetryOn {
+    fn default() -> Self {
+        RetryOn {
+            bus: true,
+            response: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RetryPolicy {
+    // Total number of attempts, including the first one
+    pub attempts: u32,
+    // Wait before the second attempt, doubled for every further attempt
+    pub backoff: Duration,
+    pub retry_on: RetryOn,
+}
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(10),
+            retry_on: RetryOn::default(),
+        }
+    }
+}
+impl RetryPolicy {
+    // Wait before attempt n (1 is the first retry)
+    pub fn backoff(&self, n: u32) -> Duration {
+        self.backoff
+            .saturating_mul(1u32.checked_shl(n.saturating_sub(1)).unwrap_or(u32::MAX))
+    }
+
+    // Whether a transfer of command code cc failing with e is tried again
+    pub fn retries(&self, cc: u8, e: &Error) -> bool {
+        if !is_repeatable(cc) {
+            return false;
+        }
+        match e.kind() {
+            ErrorKind::InvalidData | ErrorKind::UnexpectedEof => self.retry_on.response,
+            _ => self.retry_on.bus,
+        }
+    }
+}
+
+// Commands with an effect that adds up when executed twice
+fn is_repeatable(cc: u8) -> bool {
+    !matches!(cc, SYS_RESET | CORRECT_TIME)
+}
+
+impl Eps {
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry.as_ref()
+    }
+
+    // None disables retries (default)
+    pub fn set_retry_policy(&mut self, retry: Option<RetryPolicy>) {
+        self.retry = retry;
+    }
+}
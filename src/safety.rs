@@ -0,0 +1,124 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Safety mode workflow
+// enter_safety() switches the EPS to safety mode and waits for
+// system_status() to report it, optionally remembering the channels that were
+// on. exit_safety() returns to nominal the same way and switches the
+// remembered channels back on, prerequisites first (see switch_on_ordered).
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Interval of the system status reads while waiting for the mode change
+const MODE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SafetyConfirm {
+    // Time for system_status() to report the new mode
+    pub timeout: Duration,
+    // Remember the channels that are on, exit_safety() restores them
+    pub restore_channels: bool,
+}
+impl Default for SafetyConfirm {
+    fn default() -> Self {
+        SafetyConfirm {
+            timeout: Duration::from_secs(2),
+            restore_channels: false,
+        }
+    }
+}
+impl SafetyConfirm {
+    // Confirm the mode change and restore the channels on exit
+    pub fn restoring() -> Self {
+        SafetyConfirm {
+            restore_channels: true,
+            ..SafetyConfirm::default()
+        }
+    }
+}
+
+// Recorded by enter_safety()
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct SafetyEntry {
+    confirm: SafetyConfirm,
+    // Channel-on mask of channel 0 - 31 before the switch, if recorded
+    channels: Option<u32>,
+}
+
+impl Eps {
+    // Switch to safety mode and wait until the EPS reports it
+    pub fn enter_safety(&self, confirm: SafetyConfirm) -> EpsResult<()> {
+        let channels = if confirm.restore_channels {
+            let states = self.channel_states()?;
+            Some(states.on.on() as u32 | (states.ext_on.bits() as u32) << 16)
+        } else {
+            None
+        };
+        self.mode_switch(ModeSwitch::Safety)?;
+        self.await_mode(EpsMode::Safety, confirm.timeout)?;
+        self.safety_entry
+            .set(Some(SafetyEntry { confirm, channels }));
+        Ok(())
+    }
+
+    // Return to nominal mode and switch the channels recorded by
+    // enter_safety() back on. Returns the mask of the restored channels.
+    pub fn exit_safety(&self) -> EpsResult<u32> {
+        let entry = self.safety_entry.get();
+        let timeout = entry.map_or(SafetyConfirm::default().timeout, |e| e.confirm.timeout);
+        self.mode_switch(ModeSwitch::Nominal)?;
+        self.await_mode(EpsMode::Nominal, timeout)?;
+        self.safety_entry.set(None);
+        match entry.and_then(|e| e.channels) {
+            Some(mask) => {
+                self.switch_on_ordered(mask)?;
+                Ok(mask)
+            }
+            None => Ok(0),
+        }
+    }
+
+    // Channels recorded by enter_safety() to be restored, as mask
+    pub fn safety_restore_mask(&self) -> Option<u32> {
+        self.safety_entry.get().and_then(|e| e.channels)
+    }
+
+    fn await_mode(&self, mode: EpsMode, timeout: Duration) -> EpsResult<()> {
+        let start = Instant::now();
+        loop {
+            let actual = self.system_status()?.mode();
+            if actual == mode {
+                return Ok(());
+            }
+            if start.elapsed() >= timeout {
+                #[cfg(feature = "debug")]
+                eps_log! {"EPS in {:?} instead of {:?}",actual,mode};
+                return Err(EpsError::VerificationFailed {
+                    expected: Output::U8(mode as u8),
+                    actual: Output::U8(actual as u8),
+                });
+            }
+            thread::sleep(MODE_POLL_INTERVAL);
+        }
+    }
+}
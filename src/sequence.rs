@@ -0,0 +1,157 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel power-up and shutdown sequences
+// A Sequence lists channel switching steps and waits, e.g.
+// Sequence::new("payload").on(Channel::Ch1_VD1_5V).wait_ms(500).on(Channel::Ch5_VD2_3V3),
+// and can be kept in data (JSON with the serde feature) instead of code.
+// Channels already in the desired state are not switched again. On an error
+// the sequence stops (Abort), switches the channels it changed back in reverse
+// order (Rollback), or carries on with the next step (Continue). Waits are
+// not repeated on a rollback.
+
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::thread;
+use std::time::Duration;
+#[cfg(feature = "strum")]
+use strum_macros::{Display, EnumIter, EnumString};
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SequenceStep {
+    On(Channel),
+    Off(Channel),
+    WaitMs(u64),
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "strum", derive(EnumIter, EnumString, Display))]
+pub enum OnError {
+    #[default]
+    Abort,
+    Rollback,
+    Continue,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sequence {
+    pub name: String,
+    pub steps: Vec<SequenceStep>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub on_error: OnError,
+}
+
+impl Sequence {
+    pub fn new(name: &str) -> Self {
+        Sequence {
+            name: name.to_string(),
+            ..Sequence::default()
+        }
+    }
+
+    pub fn on(mut self, channel: Channel) -> Self {
+        self.steps.push(SequenceStep::On(channel));
+        self
+    }
+
+    pub fn off(mut self, channel: Channel) -> Self {
+        self.steps.push(SequenceStep::Off(channel));
+        self
+    }
+
+    pub fn wait_ms(mut self, ms: u64) -> Self {
+        self.steps.push(SequenceStep::WaitMs(ms));
+        self
+    }
+
+    pub fn on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+}
+
+// Outcome of a sequence that ran to its end
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SequenceReport {
+    // Channels switched, in order
+    pub switched: Vec<SequenceStep>,
+    // Failed steps (index into steps) with OnError::Continue
+    pub failed: Vec<(usize, EpsError)>,
+}
+
+impl SequenceReport {
+    pub fn is_clean(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl Eps {
+    // Run the steps of a sequence in order. Returns the error of the failed
+    // step with OnError::Abort and OnError::Rollback.
+    pub fn run_sequence(&self, sequence: &Sequence) -> EpsResult<SequenceReport> {
+        let mut report = SequenceReport::default();
+        for (i, step) in sequence.steps.iter().enumerate() {
+            let result = match step {
+                SequenceStep::On(ch) => self.ensure_channel(*ch, BusChannel::On),
+                SequenceStep::Off(ch) => self.ensure_channel(*ch, BusChannel::Off),
+                SequenceStep::WaitMs(ms) => {
+                    thread::sleep(Duration::from_millis(*ms));
+                    Ok(false)
+                }
+            };
+            match result {
+                Ok(true) => report.switched.push(step.clone()),
+                Ok(false) => (),
+                Err(e) => {
+                    #[cfg(feature = "debug")]
+                    eps_log! {"Sequence {} failed at step {}: {:?}",sequence.name,i,e};
+                    match sequence.on_error {
+                        OnError::Abort => return Err(e),
+                        OnError::Rollback => {
+                            self.roll_back(&report.switched);
+                            return Err(e);
+                        }
+                        OnError::Continue => report.failed.push((i, e)),
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    // Undo switched steps, newest first. Best effort, failures are skipped.
+    fn roll_back(&self, switched: &[SequenceStep]) {
+        for step in switched.iter().rev() {
+            let _result = match step {
+                SequenceStep::On(ch) => self.set_single_output(BusChannel::Off, *ch),
+                SequenceStep::Off(ch) => self.set_single_output(BusChannel::On, *ch),
+                SequenceStep::WaitMs(_) => Ok(()),
+            };
+            #[cfg(feature = "debug")]
+            if let Err(e) = _result {
+                eps_log! {"Rollback of {:?} failed: {:?}",step,e};
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Thread-safe EPS handle
+// Eps is Send but not Sync, commands from several threads on a shared Eps would
+// interleave their I2C transactions. SharedEps puts the Eps behind a mutex so
+// each command (all of its request/response pairs) runs uninterrupted. It wraps
+// the Arc<Mutex<Eps>> taken by the HK poller, watchdog and time sync, so these
+// can run alongside the service threads.
+
+use crate::eps::*;
+use crate::error::*;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+
+#[derive(Clone)]
+pub struct SharedEps {
+    eps: Arc<Mutex<Eps>>,
+}
+impl SharedEps {
+    pub fn new(eps: Eps) -> Self {
+        SharedEps {
+            eps: Arc::new(Mutex::new(eps)),
+        }
+    }
+
+    // Wait for the bus and hold it until the guard is dropped.
+    // A thread panicking while holding the lock doesn't leave the EPS in an
+    // intermediate state (transactions are atomic), so the lock is recovered.
+    pub fn lock(&self) -> MutexGuard<'_, Eps> {
+        match self.eps.lock() {
+            Ok(eps) => eps,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    // Non-blocking variant of lock(), EpsError::Busy while another thread holds the bus
+    pub fn try_lock(&self) -> EpsResult<MutexGuard<'_, Eps>> {
+        match self.eps.try_lock() {
+            Ok(eps) => Ok(eps),
+            Err(TryLockError::Poisoned(poisoned)) => Ok(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => Err(EpsError::Busy),
+        }
+    }
+
+    // Run one or more commands without other threads in between,
+    // e.g. shared.with(|eps| eps.system_status())
+    pub fn with<T, F>(&self, f: F) -> EpsResult<T>
+    where
+        F: FnOnce(&Eps) -> EpsResult<T>,
+    {
+        f(&self.lock())
+    }
+
+    // As with(), but EpsError::Busy instead of waiting for the bus
+    pub fn try_with<T, F>(&self, f: F) -> EpsResult<T>
+    where
+        F: FnOnce(&Eps) -> EpsResult<T>,
+    {
+        let eps = self.try_lock()?;
+        f(&eps)
+    }
+
+    // The underlying handle, for HkPoller::spawn, WatchdogGuard::spawn and TimeSync::spawn
+    pub fn handle(&self) -> Arc<Mutex<Eps>> {
+        self.eps.clone()
+    }
+}
+impl From<Eps> for SharedEps {
+    fn from(eps: Eps) -> Self {
+        SharedEps::new(eps)
+    }
+}
+impl From<Arc<Mutex<Eps>>> for SharedEps {
+    fn from(eps: Arc<Mutex<Eps>>) -> Self {
+        SharedEps { eps }
+    }
+}
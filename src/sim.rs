@@ -0,0 +1,298 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Battery and solar power model for simulation
+// A simple energy balance for mission rehearsals: the battery state of charge
+// decays with the load of the channels switched on and recovers with solar
+// input in sunlight, cell voltages follow the state of charge, and channels
+// drawing more than their configured limit are latched off like the EPS
+// overcurrent protection does. The model produces the HK frames of the real
+// unit, so telemetry consumers see changing values instead of static frames.
+
+use crate::objects::*;
+use crate::vd::*;
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+// Battery parameters, voltages per cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatteryModel {
+    pub capacity_mah: f32,
+    pub cells_series: u8,
+    pub cell_empty_mv: f32,
+    pub cell_full_mv: f32,
+    // Pack internal resistance, for the voltage sag under load
+    pub internal_mohm: f32,
+    pub temp_c: f32,
+}
+impl Default for BatteryModel {
+    fn default() -> Self {
+        BatteryModel {
+            capacity_mah: 5200.0,
+            cells_series: 2,
+            cell_empty_mv: 3000.0,
+            cell_full_mv: 4150.0,
+            internal_mohm: 80.0,
+            temp_c: 20.0,
+        }
+    }
+}
+
+// Solar input, a half sine over the sunlit part of each orbit
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolarModel {
+    pub peak_mw: f32,
+    pub orbit: Duration,
+    // Part of the orbit in sunlight, 0 - 1
+    pub sunlit_fraction: f32,
+    // MPPT input voltage of the conditioning chains
+    pub mppt_mv: f32,
+}
+impl Default for SolarModel {
+    fn default() -> Self {
+        SolarModel {
+            peak_mw: 7000.0,
+            orbit: Duration::from_secs(5400),
+            sunlit_fraction: 0.62,
+            mppt_mv: 16000.0,
+        }
+    }
+}
+
+// Current drawn by a channel when on, and the current at which it is latched off
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelLoad {
+    pub current_ma: f32,
+    pub limit_ma: Option<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PowerModel {
+    pub battery: BatteryModel,
+    pub solar: SolarModel,
+    rails: VdMap,
+    loads: BTreeMap<ChannelId, ChannelLoad>,
+    // State of charge 0 - 1
+    soc: f32,
+    elapsed: Duration,
+    on: u32,
+    ocf: u32,
+    ocf_cnt: [u16; 17],
+}
+
+impl PowerModel {
+    pub fn new(battery: BatteryModel, solar: SolarModel, soc: f32) -> Self {
+        PowerModel {
+            battery,
+            solar,
+            rails: VdMap::default(),
+            loads: BTreeMap::new(),
+            soc: soc.clamp(0.0, 1.0),
+            elapsed: Duration::ZERO,
+            on: 0,
+            ocf: 0,
+            ocf_cnt: [0; 17],
+        }
+    }
+
+    // Channels outside of 0..=31 are ignored, like in switch()
+    pub fn set_load(&mut self, channel: ChannelId, load: ChannelLoad) {
+        if channel > 31 {
+            return;
+        }
+        self.loads.insert(channel, load);
+    }
+
+    // Switching a channel on clears its overcurrent latch
+    pub fn switch(&mut self, channel: ChannelId, on: bool) {
+        if channel > 31 {
+            return;
+        }
+        if on {
+            self.on |= 1 << channel;
+            self.ocf &= !(1 << channel);
+        } else {
+            self.on &= !(1 << channel);
+        }
+    }
+
+    pub fn channels_on(&self) -> u32 {
+        self.on
+    }
+
+    pub fn soc(&self) -> f32 {
+        self.soc
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn solar_mw(&self) -> f32 {
+        let orbit = self.solar.orbit.as_secs_f32();
+        if orbit <= 0.0 || self.solar.sunlit_fraction <= 0.0 {
+            return 0.0;
+        }
+        let phase = (self.elapsed.as_secs_f32() % orbit) / orbit;
+        if phase < self.solar.sunlit_fraction {
+            self.solar.peak_mw * (PI * phase / self.solar.sunlit_fraction).sin()
+        } else {
+            0.0
+        }
+    }
+
+    fn channel_mw(&self, channel: ChannelId, load: &ChannelLoad) -> f32 {
+        let mv = self
+            .rails
+            .rail(channel)
+            .and_then(|r| r.nominal_mv)
+            .unwrap_or(3300);
+        load.current_ma * mv as f32 / 1000.0
+    }
+
+    pub fn load_mw(&self) -> f32 {
+        self.loads
+            .iter()
+            .filter(|(c, _)| self.on & (1 << **c) != 0)
+            .map(|(c, l)| self.channel_mw(*c, l))
+            .sum()
+    }
+
+    // Open circuit cell voltage at the current state of charge
+    fn cell_ocv_mv(&self) -> f32 {
+        let b = &self.battery;
+        b.cell_empty_mv + (b.cell_full_mv - b.cell_empty_mv) * self.soc
+    }
+
+    // Discharge current of the pack, negative while charging
+    pub fn battery_ma(&self) -> f32 {
+        let pack_mv = self.cell_ocv_mv() * self.battery.cells_series.max(1) as f32;
+        (self.load_mw() - self.solar_mw()) / pack_mv * 1000.0
+    }
+
+    pub fn cell_mv(&self) -> f32 {
+        let sag = self.battery_ma() * self.battery.internal_mohm / 1000.0;
+        self.cell_ocv_mv() - sag / self.battery.cells_series.max(1) as f32
+    }
+
+    // Advance the model by dt
+    pub fn step(&mut self, dt: Duration) {
+        // Overcurrent protection acts before the energy is drawn
+        let tripped: Vec<ChannelId> = self
+            .loads
+            .iter()
+            .filter(|(c, l)| {
+                self.on & (1 << **c) != 0 && l.limit_ma.is_some_and(|lim| l.current_ma > lim)
+            })
+            .map(|(c, _)| *c)
+            .collect();
+        for c in tripped {
+            self.on &= !(1 << c);
+            self.ocf |= 1 << c;
+            if let Some(n) = self.ocf_cnt.get_mut(c as usize) {
+                *n = n.saturating_add(1);
+            }
+        }
+        let hours = dt.as_secs_f32() / 3600.0;
+        let capacity = self.battery.capacity_mah.max(1.0);
+        self.soc = (self.soc - self.battery_ma() * hours / capacity).clamp(0.0, 1.0);
+        self.elapsed += dt;
+    }
+
+    pub fn pbu_hk(&self) -> PBUHk {
+        let cell = self.cell_mv() as i16;
+        // Cells beyond cells_series aren't fitted and read 0
+        let cell_used = |n: u8| {
+            if n <= self.battery.cells_series {
+                cell
+            } else {
+                0
+            }
+        };
+        let pack_mv = self.cell_mv() * self.battery.cells_series as f32;
+        let ma = self.battery_ma();
+        let temp = (self.battery.temp_c * 100.0) as i16;
+        let mut stat = BattPackStatus::ENABLED;
+        if self.battery_ma() < 0.0 && self.soc < 1.0 {
+            stat |= BattPackStatus::BATT1_BALANCING;
+        }
+        let bp1 = BattPackData {
+            vip_bp_output: VIPData {
+                volt: pack_mv as i16,
+                curr: ma as i16,
+                pwr: (pack_mv * ma / 1000.0) as i32,
+            },
+            stat_bp: stat,
+            volt_cell1: cell_used(1),
+            volt_cell2: cell_used(2),
+            volt_cell3: cell_used(3),
+            volt_cell4: cell_used(4),
+            bat_temp1: temp,
+            bat_temp2: temp,
+            bat_temp3: temp,
+        };
+        PBUHk {
+            volt_brdsup: 3300,
+            temp,
+            vip_input: bp1.vip_bp_output.clone(),
+            stat_bu: stat,
+            bp1,
+            bp2: None,
+            bp3: None,
+        }
+    }
+
+    pub fn pcu_hk(&self) -> PCUHk {
+        let chain_mw = self.solar_mw() / 4.0;
+        let in_ma = chain_mw / self.solar.mppt_mv * 1000.0;
+        let out_mv = self.cell_mv() * self.battery.cells_series.max(1) as f32;
+        let out_ma = chain_mw / out_mv * 1000.0;
+        let ccd = CondChnData {
+            vip_cc_output: VIPData {
+                volt: out_mv as i16,
+                curr: out_ma as i16,
+                pwr: chain_mw as i32,
+            },
+            volt_in_mppt: self.solar.mppt_mv as i16,
+            curr_in_mppt: in_ma as i16,
+            volt_out_mppt: out_mv as i16,
+            curr_out_mppt: out_ma as i16,
+        };
+        PCUHk {
+            volt_brdsup: 3300,
+            temp: (self.battery.temp_c * 100.0) as i16,
+            vip_output: VIPData {
+                volt: out_mv as i16,
+                curr: (out_ma * 4.0) as i16,
+                pwr: self.solar_mw() as i32,
+            },
+            ccd1: ccd.clone(),
+            ccd2: ccd.clone(),
+            ccd3: ccd.clone(),
+            ccd4: ccd,
+        }
+    }
+
+    pub fn overcurrent_state(&self) -> OverCurrentFaultState {
+        OverCurrentFaultState {
+            stat_ch_on: ChannelOnState::from(self.on as u16),
+            stat_ch_ext_on: ExtChannelOnState::from((self.on >> 16) as u16),
+            stat_ch_ocf: ChannelOverCurrentState::from(self.ocf as u16),
+            stat_ch_ext_ocf: ExtChannelOverCurrentState::from((self.ocf >> 16) as u16),
+            ocf_cnt: self.ocf_cnt,
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Snapshot of the complete EPS configuration
+// All writable and read-only parameters read in one go, keyed by parameter ID
+// so the snapshot keeps the ICD order and serializes to a flat map for
+// downlink. The write and read-only ID ranges don't overlap.
+//
+// Serialized, the IDs are hex strings ("0x4000") as TOML only allows string
+// keys. apply() writes a snapshot back to the EPS: the key parameters first,
+// so the parameters they protect are accepted, then all other writable
+// parameters, each verified by reading it back, and finally saves the
+// configuration to NVM. Read-only parameters in the snapshot are ignored.
+// diff() compares a reference snapshot with another one, e.g. read from the
+// live unit with Eps::diff_against().
+
+use crate::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use std::io::ErrorKind;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConfigSnapshot {
+    #[cfg_attr(feature = "serde", serde(with = "hex_ids"))]
+    pub params: BTreeMap<u16, Output>,
+}
+
+// Parameter whose value differs between two snapshots
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConfigDelta {
+    pub id: u16,
+    // Parameter name, e.g. "ChStartupDelay(3)", the hex ID for unknown parameters
+    pub name: String,
+    // None if the parameter is missing in the snapshot
+    pub expected: Option<Output>,
+    pub actual: Option<Output>,
+}
+
+fn param_name(id: u16) -> String {
+    match (ConfigParamWrite::from_id(id), ConfigParamRead::from_id(id)) {
+        (Some(p), _) => format!("{:?}", p),
+        (None, Some(p)) => format!("{:?}", p),
+        (None, None) => format!("{:#06x}", id),
+    }
+}
+
+// Parameters unlocking changes of another parameter, they don't read back the written value
+fn is_key(param: &ConfigParamWrite) -> bool {
+    matches!(
+        param,
+        ConfigParamWrite::ChStartupKey
+            | ConfigParamWrite::ChLatchoffKey
+            | ConfigParamWrite::TtcWdgTimeoutKey
+            | ConfigParamWrite::BoardIdKey
+    )
+}
+
+impl ConfigSnapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&self, param: &ConfigParamWrite) -> Option<&Output> {
+        self.params.get(&param.get_id())
+    }
+
+    pub fn read(&self, param: &ConfigParamRead) -> Option<&Output> {
+        self.params.get(&param.get_id())
+    }
+
+    pub fn insert_write(&mut self, param: &ConfigParamWrite, value: Output) {
+        self.params.insert(param.get_id(), value);
+    }
+
+    pub fn insert_read(&mut self, param: &ConfigParamRead, value: Output) {
+        self.params.insert(param.get_id(), value);
+    }
+
+    // Values of the writable parameters, in ID order
+    pub fn writable(&self) -> impl Iterator<Item = (ConfigParamWrite, &Output)> {
+        self.params
+            .iter()
+            .filter_map(|(id, v)| ConfigParamWrite::from_id(*id).map(|p| (p, v)))
+    }
+
+    // Values of the read-only parameters, in ID order
+    pub fn read_only(&self) -> impl Iterator<Item = (ConfigParamRead, &Output)> {
+        self.params
+            .iter()
+            .filter_map(|(id, v)| ConfigParamRead::from_id(*id).map(|p| (p, v)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    // Parameters differing from self (expected) in other (actual), in ID order
+    pub fn diff(&self, other: &ConfigSnapshot) -> Vec<ConfigDelta> {
+        let mut ids: Vec<u16> = self
+            .params
+            .keys()
+            .chain(other.params.keys())
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids.into_iter()
+            .filter_map(|id| {
+                let expected = self.params.get(&id);
+                let actual = other.params.get(&id);
+                if expected == actual {
+                    return None;
+                }
+                Some(ConfigDelta {
+                    id,
+                    name: param_name(id),
+                    expected: expected.cloned(),
+                    actual: actual.cloned(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl ConfigSnapshot {
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> EpsResult<String> {
+        match serde_json::to_string_pretty(self) {
+            Ok(x) => Ok(x),
+            Err(_) => Err(EpsError::Snapshot(ErrorKind::InvalidData)),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> EpsResult<Self> {
+        match serde_json::from_str(json) {
+            Ok(x) => Ok(x),
+            Err(_) => Err(EpsError::Snapshot(ErrorKind::InvalidData)),
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> EpsResult<String> {
+        match toml::to_string(self) {
+            Ok(x) => Ok(x),
+            Err(_) => Err(EpsError::Snapshot(ErrorKind::InvalidData)),
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> EpsResult<Self> {
+        match toml::from_str(s) {
+            Ok(x) => Ok(x),
+            Err(_) => Err(EpsError::Snapshot(ErrorKind::InvalidData)),
+        }
+    }
+
+    // Write the writable parameters to the EPS, verify them and save the configuration.
+    // Values with another width than their parameter are rejected (TypeMismatch) before anything is written.
+    pub fn apply(&self, eps: &Eps) -> EpsResult<()> {
+        let (keys, params): (Vec<_>, Vec<_>) = self.writable().partition(|(p, _)| is_key(p));
+        if params
+            .iter()
+            .chain(keys.iter())
+            .any(|(p, v)| v.width() != p.get_len())
+        {
+            return Err(EpsError::TypeMismatch);
+        }
+        for (param, value) in keys.iter() {
+            eps.set_config_para_output(param, value)?;
+        }
+        for (param, value) in params.iter() {
+            eps.set_config_para_verified(param.clone(), (*value).clone())?;
+        }
+        eps.save_config()
+    }
+}
+
+// (De)serialize the parameter map with hex string keys
+#[cfg(feature = "serde")]
+mod hex_ids {
+    use crate::*;
+    use serde::de::Error;
+    use serde::*;
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S: Serializer>(
+        params: &BTreeMap<u16, Output>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        params
+            .iter()
+            .map(|(id, v)| (format!("{:#06x}", id), v))
+            .collect::<BTreeMap<String, &Output>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<u16, Output>, D::Error> {
+        let mut params = BTreeMap::new();
+        for (k, v) in BTreeMap::<String, Output>::deserialize(deserializer)? {
+            let id = match u16::from_str_radix(k.trim_start_matches("0x"), 16) {
+                Ok(x) => x,
+                Err(_) => return Err(D::Error::custom(format!("invalid parameter ID {}", k))),
+            };
+            params.insert(id, v);
+        }
+        Ok(params)
+    }
+}
+
+impl Eps {
+    // Parameters of the reference differing on the EPS. Only the parameters in
+    // the reference are read, unknown IDs are reported without actual value.
+    pub fn diff_against(&self, reference: &ConfigSnapshot) -> EpsResult<Vec<ConfigDelta>> {
+        let mut live = ConfigSnapshot::new();
+        for id in reference.params.keys() {
+            if let Some(param) = ConfigParamWrite::from_id(*id) {
+                let value = self.get_config_para_write(param.clone())?;
+                live.insert_write(&param, value);
+            } else if let Some(param) = ConfigParamRead::from_id(*id) {
+                let value = self.get_config_para_read(param.clone())?;
+                live.insert_read(&param, value);
+            }
+        }
+        Ok(reference.diff(&live))
+    }
+}
@@ -0,0 +1,173 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Battery state of charge
+// Estimates the state of charge of the battery packs from the cell voltages of
+// the PBU engineering HK. The open circuit voltage of a cell is looked up on a
+// piecewise linear curve of the cell chemistry. The pack is only as charged as
+// its weakest cell, so the lowest cell gives the pack SoC.
+// Voltage based estimation is only accurate at rest, under load the cells read
+// lower and the SoC is underestimated.
+
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+// Open circuit voltage (mV) to state of charge (%) of one cell
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SocCurve {
+    points: Vec<(i16, f32)>,
+}
+impl SocCurve {
+    // Points (mV, %) with strictly increasing voltage and non-decreasing SoC, at least two
+    pub fn new(points: Vec<(i16, f32)>) -> EpsResult<Self> {
+        let valid = points.len() >= 2
+            && points
+                .windows(2)
+                .all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1)
+            && points.iter().all(|(_, soc)| (0.0..=100.0).contains(soc));
+        if !valid {
+            return Err(EpsError::InvalidInput);
+        }
+        Ok(SocCurve { points })
+    }
+
+    // Typical Li-ion (NMC) cell, 3.0 V empty to 4.2 V full
+    pub fn li_ion() -> Self {
+        SocCurve {
+            points: vec![
+                (3000, 0.0),
+                (3300, 5.0),
+                (3500, 10.0),
+                (3600, 20.0),
+                (3700, 40.0),
+                (3800, 55.0),
+                (3900, 70.0),
+                (4000, 80.0),
+                (4100, 90.0),
+                (4200, 100.0),
+            ],
+        }
+    }
+
+    pub fn points(&self) -> &[(i16, f32)] {
+        &self.points
+    }
+
+    // SoC in % of a cell voltage in mV, clamped to the ends of the curve
+    pub fn soc(&self, mv: i16) -> f32 {
+        let first = self.points[0];
+        let last = self.points[self.points.len() - 1];
+        if mv <= first.0 {
+            return first.1;
+        }
+        if mv >= last.0 {
+            return last.1;
+        }
+        let i = self.points.partition_point(|(v, _)| *v <= mv);
+        let (v0, s0) = self.points[i - 1];
+        let (v1, s1) = self.points[i];
+        s0 + (s1 - s0) * (mv - v0) as f32 / (v1 - v0) as f32
+    }
+}
+impl Default for SocCurve {
+    fn default() -> Self {
+        SocCurve::li_ion()
+    }
+}
+
+// Cells in series in a battery pack, the voltages of unused cell inputs are ignored
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PackTopology {
+    // Cells 1 and 2
+    S2,
+    // Cells 1 - 4
+    #[default]
+    S4,
+}
+impl PackTopology {
+    pub fn cells(self) -> usize {
+        match self {
+            PackTopology::S2 => 2,
+            PackTopology::S4 => 4,
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SocEstimator {
+    pub curve: SocCurve,
+    pub topology: PackTopology,
+}
+impl SocEstimator {
+    pub fn new(curve: SocCurve, topology: PackTopology) -> Self {
+        SocEstimator { curve, topology }
+    }
+
+    // SoC of a battery pack from its engineering HK data
+    pub fn estimate(&self, pack: BatteryPack, data: &BattPackData) -> BatterySoc {
+        let cells = &data.cell_voltages()[..self.topology.cells()];
+        let lowest = cells.iter().min().copied().unwrap_or_default();
+        BatterySoc {
+            pack,
+            percent: self.curve.soc(lowest),
+            cell_voltages: cells.iter().map(|mv| *mv as f32 / 1000.0).collect(),
+        }
+    }
+}
+
+// State of charge of one battery pack
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BatterySoc {
+    pub pack: BatteryPack,
+    // 0 - 100 %
+    pub percent: f32,
+    // Voltages of the cells in series in V
+    pub cell_voltages: Vec<f32>,
+}
+
+impl Eps {
+    pub fn soc_estimator(&self) -> &SocEstimator {
+        &self.soc
+    }
+
+    // Cell chemistry and pack topology used by battery_soc()
+    pub fn set_soc_estimator(&mut self, soc: SocEstimator) {
+        self.soc = soc;
+    }
+
+    // SoC of the battery packs covered by Eps::battery_packs(), from one PBU engineering HK read
+    pub fn battery_soc(&self) -> EpsResult<Vec<BatterySoc>> {
+        let hk = self.pbu_hk(PBUHkSel::PBUEngHK)?;
+        let packs = [BatteryPack::Bp1, BatteryPack::Bp2, BatteryPack::Bp3];
+        Ok(packs
+            .iter()
+            .filter_map(|p| {
+                hk.battery_pack(p.number())
+                    .map(|data| self.soc.estimate(*p, data))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,73 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! STAT byte of the response frames
+// Kept free of std, it is built without std too. EpsError::Stat carries it,
+// StatKind::code() lives in error.rs.
+
+use crate::STAT_NEW;
+use core::fmt;
+
+// Meaning of a STAT rejection (ICD response status)
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatKind {
+    Rejected,
+    InvalidCommandCode,
+    ParameterMissing,
+    ParameterInvalid,
+    UnavailableMode,
+    InvalidSystemType,
+    // Any other code, including the reserved bits
+    InternalProcessing,
+}
+
+impl StatKind {
+    // Kind of a STAT byte, None if the command was accepted.
+    // The NEW flag (0x80) doesn't change the meaning of the code.
+    pub fn from_stat(stat: u8) -> Option<StatKind> {
+        match stat & !STAT_NEW {
+            0x00 => None,
+            0x01 => Some(StatKind::Rejected),
+            0x02 => Some(StatKind::InvalidCommandCode),
+            0x03 => Some(StatKind::ParameterMissing),
+            0x04 => Some(StatKind::ParameterInvalid),
+            0x05 => Some(StatKind::UnavailableMode),
+            0x06 => Some(StatKind::InvalidSystemType),
+            _ => Some(StatKind::InternalProcessing),
+        }
+    }
+}
+
+impl fmt::Display for StatKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatKind::Rejected => write!(f, "Rejected"),
+            StatKind::InvalidCommandCode => write!(f, "Rejected: Invalid command code"),
+            StatKind::ParameterMissing => write!(f, "Rejected: Parameter missing"),
+            StatKind::ParameterInvalid => write!(f, "Rejected: Parameter invalid"),
+            StatKind::UnavailableMode => {
+                write!(f, "Rejected: Unavailable in current mode/configuration")
+            }
+            StatKind::InvalidSystemType => {
+                write!(
+                    f,
+                    "Rejected: Invalid system type, interface version, or BID"
+                )
+            }
+            StatKind::InternalProcessing => write!(f, "Internal error occurred during processing"),
+        }
+    }
+}
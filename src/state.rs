@@ -0,0 +1,254 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistent last-known-state store
+// Keeps what the EPS is supposed to look like (last commanded channel states,
+// mode and config values) in a JSON file on the OBC, so a restarted service
+// can pick up where it left off.
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LastKnownState {
+    // Last commanded channel-on state, None if no channel was commanded yet
+    pub channels_on: Option<ChannelOnState>,
+    // The same for channel 16 - 31, missing in files written before it was kept
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub channels_ext_on: Option<ExtChannelOnState>,
+    // Last commanded mode
+    pub mode: Option<ModeSwitch>,
+    // Last written config values
+    pub config: Vec<(ConfigParamWrite, Output)>,
+}
+impl LastKnownState {
+    // Merge a group command into the commanded channel state
+    pub fn record_group(&mut self, channels: &BusChannelState) {
+        let current = self.channels_on.map(|c| c.bits()).unwrap_or_default();
+        // state() can't fail
+        if let Ok(u) = channels.state(current) {
+            self.channels_on = Some(ChannelOnState::from(u));
+        }
+    }
+    pub fn record_channel(&mut self, channel: BusChannel, eps_ch_idx: u8) {
+        if eps_ch_idx > 31 || channel == BusChannel::Keep {
+            return;
+        }
+        let on = channel == BusChannel::On;
+        if eps_ch_idx > 15 {
+            let mut state = self.channels_ext_on.unwrap_or_default();
+            state.set(ExtChannelOnState::from(1 << (eps_ch_idx - 16)), on);
+            self.channels_ext_on = Some(state);
+        } else {
+            let mut state = self.channels_on.unwrap_or_default();
+            state.set(ChannelOnState::from(1 << eps_ch_idx), on);
+            self.channels_on = Some(state);
+        }
+    }
+    pub fn record_mode(&mut self, mode: ModeSwitch) {
+        self.mode = Some(mode);
+    }
+    pub fn record_config(&mut self, param: ConfigParamWrite, value: Output) {
+        match self.config.iter_mut().find(|(p, _)| *p == param) {
+            Some(entry) => entry.1 = value,
+            None => self.config.push((param, value)),
+        }
+    }
+    pub fn config_value(&self, param: &ConfigParamWrite) -> Option<&Output> {
+        self.config.iter().find(|(p, _)| p == param).map(|(_, v)| v)
+    }
+}
+
+// LastKnownState backed by a file
+pub struct StateStore {
+    path: PathBuf,
+    pub state: LastKnownState,
+}
+impl StateStore {
+    // Load the state from path, a missing file gives an empty state
+    pub fn load<P: AsRef<Path>>(path: P) -> EpsResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let state = match fs::read(&path) {
+            Ok(x) => match serde_json::from_slice(&x) {
+                Ok(s) => s,
+                Err(_) => return Err(EpsError::StateFile(ErrorKind::InvalidData)),
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => LastKnownState::default(),
+            Err(e) => return Err(EpsError::StateFile(e.kind())),
+        };
+        Ok(StateStore { path, state })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Write the state to the file. Written to a temporary file first and renamed,
+    // so a reset while saving can't leave a truncated file behind.
+    pub fn save(&self) -> EpsResult<()> {
+        let json = match serde_json::to_vec_pretty(&self.state) {
+            Ok(x) => x,
+            Err(_) => return Err(EpsError::StateFile(ErrorKind::InvalidData)),
+        };
+        let tmp = self.path.with_extension("tmp");
+        if let Err(e) = fs::write(&tmp, json) {
+            return Err(EpsError::StateFile(e.kind()));
+        }
+        match fs::rename(&tmp, &self.path) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(EpsError::StateFile(e.kind())),
+        }
+    }
+
+    // Apply a change to the state and save it
+    pub fn update<F: FnOnce(&mut LastKnownState)>(&mut self, f: F) -> EpsResult<()> {
+        f(&mut self.state);
+        self.save()
+    }
+}
+
+// What reconcile() does with differences between desired and actual state
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ReconcilePolicy {
+    // Only report the differences
+    Report,
+    // Report the differences and command the EPS back to the desired state
+    Reapply,
+}
+
+// A difference between the persisted desired state and the actual EPS state
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Discrepancy {
+    Channels {
+        desired: ChannelOnState,
+        actual: ChannelOnState,
+    },
+    // Channel 16 - 31
+    ExtChannels {
+        desired: ExtChannelOnState,
+        actual: ExtChannelOnState,
+    },
+    Mode {
+        desired: ModeSwitch,
+        actual: EpsMode,
+    },
+    Config {
+        param: ConfigParamWrite,
+        desired: Output,
+        actual: Output,
+    },
+}
+
+impl Eps {
+    // Compare the desired state (e.g. loaded from a StateStore after a reset) with
+    // the actual EPS state. With ReconcilePolicy::Reapply the differences are
+    // commanded back in the order mode, channels, config.
+    // Returns the differences found before any reapplication.
+    pub fn reconcile(
+        &self,
+        desired: &LastKnownState,
+        policy: ReconcilePolicy,
+    ) -> EpsResult<Vec<Discrepancy>> {
+        let mut found = Vec::new();
+
+        if let Some(mode) = &desired.mode {
+            let actual = self.system_status()?.mode();
+            let matches = matches!(
+                (mode, &actual),
+                (ModeSwitch::Nominal, EpsMode::Nominal) | (ModeSwitch::Safety, EpsMode::Safety)
+            );
+            if !matches {
+                found.push(Discrepancy::Mode {
+                    desired: mode.clone(),
+                    actual,
+                });
+            }
+        }
+
+        if desired.channels_on.is_some() || desired.channels_ext_on.is_some() {
+            let actual = self.channel_states()?;
+            let actual_on = ChannelOnState::from(u16::from(actual.on));
+            match desired.channels_on {
+                Some(channels) if channels != actual_on => found.push(Discrepancy::Channels {
+                    desired: channels,
+                    actual: actual_on,
+                }),
+                _ => (),
+            }
+            match desired.channels_ext_on {
+                Some(channels) if channels != actual.ext_on => {
+                    found.push(Discrepancy::ExtChannels {
+                        desired: channels,
+                        actual: actual.ext_on,
+                    })
+                }
+                _ => (),
+            }
+        }
+
+        for (param, value) in desired.config.iter() {
+            let actual = self.get_config_para_write(param.clone())?;
+            if actual != *value {
+                found.push(Discrepancy::Config {
+                    param: param.clone(),
+                    desired: value.clone(),
+                    actual,
+                });
+            }
+        }
+
+        if policy == ReconcilePolicy::Reapply {
+            for d in found.iter() {
+                match d {
+                    Discrepancy::Mode { desired, .. } => self.mode_switch(desired.clone())?,
+                    Discrepancy::Channels { desired, .. } => {
+                        let state = BusChannelState::from_masks(desired.bits(), !desired.bits())?;
+                        self.set_group_state(BusGroup::BusGroupState, state)?
+                    }
+                    // The group commands don't reach them, switched one by one
+                    Discrepancy::ExtChannels { desired, actual } => {
+                        for bit in 0..16 {
+                            let mask = ExtChannelOnState::from(1 << bit);
+                            if desired.contains(mask) == actual.contains(mask) {
+                                continue;
+                            }
+                            let on = match desired.contains(mask) {
+                                true => BusChannel::On,
+                                false => BusChannel::Off,
+                            };
+                            self.set_single_output(on, Channel::try_from(16 + bit)?)?;
+                        }
+                    }
+                    Discrepancy::Config { param, desired, .. } => {
+                        self.set_config_para_output(param, desired)?;
+                    }
+                }
+            }
+        }
+
+        Ok(found)
+    }
+}
@@ -0,0 +1,142 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Internal board supply voltage monitor
+// A sagging internal supply (VOLT_BRDSUP) is an early failure signature, the
+// monitor checks it against limits and tracks its trend across HK frames.
+
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::collections::VecDeque;
+
+// HK frames carrying the internal board supply voltage in mV
+pub trait BoardSupply {
+    fn board_supply_mv(&self) -> i16;
+}
+impl BoardSupply for PDUHk {
+    fn board_supply_mv(&self) -> i16 {
+        self.volt_brdsup
+    }
+}
+impl BoardSupply for PBUHk {
+    fn board_supply_mv(&self) -> i16 {
+        self.volt_brdsup
+    }
+}
+impl BoardSupply for PCUHk {
+    fn board_supply_mv(&self) -> i16 {
+        self.volt_brdsup
+    }
+}
+impl BoardSupply for PIUHk {
+    fn board_supply_mv(&self) -> i16 {
+        self.volt_brdsup
+    }
+}
+impl BoardSupply for HkFrameRaw {
+    fn board_supply_mv(&self) -> i16 {
+        self.volt_brdsup()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoardSupplyLimits {
+    pub lo_mv: i16,
+    pub hi_mv: i16,
+    // Falling trend (in mV per second) from which BoardSupplyAlert::Sagging is raised
+    pub max_fall_rate: f64,
+    // Number of samples the trend is estimated over
+    pub window: usize,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BoardSupplyAlert {
+    BelowLimit { mv: i16 },
+    AboveLimit { mv: i16 },
+    // Fitted slope over the window in mV per second
+    Sagging { rate: f64 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoardSupplyMonitor {
+    pub limits: BoardSupplyLimits,
+    samples: VecDeque<(f64, i16)>,
+}
+impl BoardSupplyMonitor {
+    pub fn new(limits: BoardSupplyLimits) -> Self {
+        BoardSupplyMonitor {
+            limits,
+            samples: VecDeque::new(),
+        }
+    }
+
+    // Record the supply of a HK frame taken at time t (seconds)
+    pub fn record_frame<T: BoardSupply>(&mut self, t: f64, hk: &T) -> Vec<BoardSupplyAlert> {
+        self.record(t, hk.board_supply_mv())
+    }
+
+    // Record a supply voltage sample taken at time t (seconds)
+    pub fn record(&mut self, t: f64, mv: i16) -> Vec<BoardSupplyAlert> {
+        if self.samples.len() >= self.limits.window.max(2) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((t, mv));
+
+        let mut alerts = Vec::new();
+        if mv < self.limits.lo_mv {
+            alerts.push(BoardSupplyAlert::BelowLimit { mv });
+        }
+        if mv > self.limits.hi_mv {
+            alerts.push(BoardSupplyAlert::AboveLimit { mv });
+        }
+        if let Some(rate) = self.trend() {
+            if rate < -self.limits.max_fall_rate {
+                alerts.push(BoardSupplyAlert::Sagging { rate });
+            }
+        }
+        alerts
+    }
+
+    // Least-squares slope of the samples in the window in mV per second,
+    // None until the window is full
+    pub fn trend(&self) -> Option<f64> {
+        if self.samples.len() < self.limits.window.max(2) {
+            return None;
+        }
+        let n = self.samples.len() as f64;
+        let t_mean = self.samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+        let v_mean = self.samples.iter().map(|(_, v)| *v as f64).sum::<f64>() / n;
+        let mut stv = 0.0;
+        let mut stt = 0.0;
+        for (t, v) in self.samples.iter() {
+            stv += (t - t_mean) * (*v as f64 - v_mean);
+            stt += (t - t_mean) * (t - t_mean);
+        }
+        if stt == 0.0 {
+            None
+        } else {
+            Some(stv / stt)
+        }
+    }
+
+    pub fn latest(&self) -> Option<i16> {
+        self.samples.back().map(|(_, v)| *v)
+    }
+}
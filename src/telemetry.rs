@@ -0,0 +1,144 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-memory housekeeping history
+// TelemetryLog keeps the last N time-stamped samples of a housekeeping struct
+// and gives min/max/mean of selected fields over them, e.g. for trend reports
+// in the downlink. Unlike the AVG HK of the EPS, the window and the fields are
+// chosen by the caller. The time base is up to the caller as in resample.rs,
+// history() can be passed on to resample_fields().
+
+use crate::resample::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+impl FieldStats {
+    // None for an empty series
+    pub fn of<I: IntoIterator<Item = f64>>(values: I) -> Option<Self> {
+        let mut count = 0;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        let mut sum = 0.0;
+        for v in values {
+            count += 1;
+            min = min.min(v);
+            max = max.max(v);
+            sum += v;
+        }
+        if count == 0 {
+            None
+        } else {
+            Some(FieldStats {
+                count,
+                min,
+                max,
+                mean: sum / count as f64,
+            })
+        }
+    }
+}
+
+pub struct TelemetryLog<T> {
+    capacity: usize,
+    fields: Vec<FieldFn<T>>,
+    samples: VecDeque<(f64, T)>,
+}
+impl<T: Clone> TelemetryLog<T> {
+    // Log of the last capacity samples, with statistics on the given fields
+    pub fn new(capacity: usize, fields: Vec<FieldFn<T>>) -> Self {
+        TelemetryLog {
+            capacity: capacity.max(1),
+            fields,
+            samples: VecDeque::with_capacity(capacity.max(1)),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    // Add a sample taken at time t, dropping the oldest one when full
+    pub fn push(&mut self, t: f64, sample: T) {
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((t, sample));
+    }
+
+    pub fn latest(&self) -> Option<&(f64, T)> {
+        self.samples.back()
+    }
+
+    // Samples from the oldest to the newest
+    pub fn iter(&self) -> impl Iterator<Item = &(f64, T)> {
+        self.samples.iter()
+    }
+
+    pub fn history(&self) -> Vec<(f64, T)> {
+        self.samples.iter().cloned().collect()
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.fields.iter().map(|(name, _)| *name)
+    }
+
+    // Statistics of every selected field over all samples, empty if there are none
+    pub fn stats(&self) -> Vec<(String, FieldStats)> {
+        self.stats_since(f64::NEG_INFINITY)
+    }
+
+    // Statistics of every selected field over the samples taken at or after t
+    pub fn stats_since(&self, t: f64) -> Vec<(String, FieldStats)> {
+        self.fields
+            .iter()
+            .filter_map(|(name, field)| {
+                let stats = FieldStats::of(
+                    self.samples
+                        .iter()
+                        .filter(|(ts, _)| *ts >= t)
+                        .map(|(_, x)| field(x)),
+                )?;
+                Some((name.to_string(), stats))
+            })
+            .collect()
+    }
+
+    // Statistics of one selected field, None if it isn't selected or there are no samples
+    pub fn field_stats(&self, name: &str) -> Option<FieldStats> {
+        let (_, field) = self.fields.iter().find(|(n, _)| *n == name)?;
+        FieldStats::of(self.samples.iter().map(|(_, x)| field(x)))
+    }
+}
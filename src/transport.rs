@@ -0,0 +1,109 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transport to the EPS
+// Eps talks to the unit through an EpsTransport, I2C (i2c_rs) by default.
+// Other buses or test doubles implement the trait and are passed to
+// Eps::with_transport(). A transfer writes the command frame and reads rx_len
+// response bytes after delay, like an I2C write-read. Read() fetches the
+// response again without a command, transports that can't leave the default.
+// For I2C masters without a combined write-read TransferMode::Split sends the
+// command with write(), waits the delay and reads the response with read().
+
+use crate::eps::*;
+pub use i2c_rs::Command;
+use i2c_rs::Connection as I2c;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TransferMode {
+    // Write the command and read the response in one transfer() (default)
+    #[default]
+    Combined,
+    // Separate write() and read(), the delay is waited for in between
+    Split,
+}
+
+pub trait EpsTransport {
+    // command.cmd is the STID, command.data the rest of the frame [IVID, CC, BID, payload..]
+    fn transfer(
+        &self,
+        command: Command,
+        rx_len: usize,
+        delay: Duration,
+    ) -> std::io::Result<Vec<u8>>;
+
+    // Read the pending response again without sending a command, for ResponseMode::WaitForFresh
+    // and TransferMode::Split
+    fn read(&self, _stid: u8, _rx_len: usize) -> std::io::Result<Vec<u8>> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
+    // Send a command without reading the response, for TransferMode::Split
+    fn write(&self, _command: Command) -> std::io::Result<()> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
+
+impl EpsTransport for I2c {
+    fn transfer(
+        &self,
+        command: Command,
+        rx_len: usize,
+        delay: Duration,
+    ) -> std::io::Result<Vec<u8>> {
+        I2c::transfer(self, command, rx_len, delay)
+    }
+
+    fn read(&self, stid: u8, rx_len: usize) -> std::io::Result<Vec<u8>> {
+        I2c::read(self, stid, rx_len)
+    }
+
+    fn write(&self, command: Command) -> std::io::Result<()> {
+        I2c::write(self, command)
+    }
+}
+
+impl Eps {
+    pub fn transfer_mode(&self) -> TransferMode {
+        self.transfer_mode
+    }
+
+    pub fn set_transfer_mode(&mut self, mode: TransferMode) {
+        self.transfer_mode = mode;
+    }
+
+    // One command/response exchange in the configured transfer mode
+    pub(crate) fn exchange(
+        &self,
+        command: Command,
+        rx_len: usize,
+        delay: Duration,
+        deadline: Option<Instant>,
+    ) -> std::io::Result<Vec<u8>> {
+        match self.transfer_mode {
+            TransferMode::Combined => self.transport.transfer(command, rx_len, delay),
+            TransferMode::Split => {
+                let stid = command.cmd;
+                self.transport.write(command)?;
+                sleep_within(delay, deadline)?;
+                self.transport.read(stid, rx_len)
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Engineering units of the decoded housekeeping data
+// The decoded HK structs keep the integers of the ICD: voltages in mV,
+// currents in mA, power in mW (scaled from the 10 mW LSB on decoding) and
+// temperatures in 0.01 degC. The *Eng structs hold the same fields as f32
+// in V, A, W and degC, so consumers don't repeat the scaling factors.
+// Status bitflags are passed through unchanged.
+
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+// ICD scaling of the raw integers
+const MILLI: f32 = 1000.0;
+const CENTI_DEGC: f32 = 100.0;
+
+fn volts(mv: i16) -> f32 {
+    mv as f32 / MILLI
+}
+fn amps(ma: i16) -> f32 {
+    ma as f32 / MILLI
+}
+fn degc(centi: i16) -> f32 {
+    centi as f32 / CENTI_DEGC
+}
+
+// Conversion of decoded telemetry into engineering units
+pub trait EngUnits {
+    type Eng;
+    fn eng(&self) -> Self::Eng;
+}
+
+// Voltage in V, current in A and power in W
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VIPDataEng {
+    pub volt: f32,
+    pub curr: f32,
+    pub pwr: f32,
+}
+impl EngUnits for VIPData {
+    type Eng = VIPDataEng;
+    fn eng(&self) -> VIPDataEng {
+        VIPDataEng {
+            volt: self.volts(),
+            curr: self.amps(),
+            pwr: self.watts(),
+        }
+    }
+}
+
+// MPPT input and output in V and A
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CondChnShortDataEng {
+    pub volt_in_mppt: f32,
+    pub curr_in_mppt: f32,
+    pub volt_out_mppt: f32,
+    pub curr_out_mppt: f32,
+}
+impl EngUnits for CondChnShortData {
+    type Eng = CondChnShortDataEng;
+    fn eng(&self) -> CondChnShortDataEng {
+        CondChnShortDataEng {
+            volt_in_mppt: volts(self.volt_in_mppt),
+            curr_in_mppt: amps(self.curr_in_mppt),
+            volt_out_mppt: volts(self.volt_out_mppt),
+            curr_out_mppt: amps(self.curr_out_mppt),
+        }
+    }
+}
+
+// Battery pack data, cell voltages in V and temperatures in degC
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BattPackDataEng {
+    pub vip_bp_output: VIPDataEng,
+    pub stat_bp: BattPackStatus,
+    pub volt_cell1: f32,
+    pub volt_cell2: f32,
+    pub volt_cell3: f32,
+    pub volt_cell4: f32,
+    pub bat_temp1: f32,
+    pub bat_temp2: f32,
+    pub bat_temp3: f32,
+}
+impl EngUnits for BattPackData {
+    type Eng = BattPackDataEng;
+    fn eng(&self) -> BattPackDataEng {
+        BattPackDataEng {
+            vip_bp_output: self.vip_bp_output.eng(),
+            stat_bp: self.stat_bp,
+            volt_cell1: volts(self.volt_cell1),
+            volt_cell2: volts(self.volt_cell2),
+            volt_cell3: volts(self.volt_cell3),
+            volt_cell4: volts(self.volt_cell4),
+            bat_temp1: degc(self.bat_temp1),
+            bat_temp2: degc(self.bat_temp2),
+            bat_temp3: degc(self.bat_temp3),
+        }
+    }
+}
+
+// PIU housekeeping, field names as in PIUHk
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PIUHkEng {
+    pub volt_brdsup: f32,
+    pub temp: f32,
+    pub vip_dist_input: VIPDataEng,
+    pub vip_batt_input: VIPDataEng,
+    pub stat_ch_on: ChannelOnState,
+    pub stat_ch_ocf: ChannelOverCurrentState,
+    pub batt_stat: BattPackStatus,
+    pub batt_temp2: f32,
+    pub batt_temp3: f32,
+    pub volt_vd0: f32,
+    pub volt_vd1: f32,
+    pub volt_vd2: f32,
+    pub vip_cnt_ch00: VIPDataEng,
+    pub vip_cnt_ch01: VIPDataEng,
+    pub vip_cnt_ch02: VIPDataEng,
+    pub vip_cnt_ch03: VIPDataEng,
+    pub vip_cnt_ch04: VIPDataEng,
+    pub vip_cnt_ch05: VIPDataEng,
+    pub vip_cnt_ch06: VIPDataEng,
+    pub vip_cnt_ch07: VIPDataEng,
+    pub vip_cnt_ch08: VIPDataEng,
+    pub ccd1: CondChnShortDataEng,
+    pub ccd2: CondChnShortDataEng,
+    pub ccd3: CondChnShortDataEng,
+    pub vip_cnt_ch09: VIPDataEng,
+    pub vip_cnt_ch10: VIPDataEng,
+    pub vip_cnt_ch11: VIPDataEng,
+    pub vip_cnt_ch12: VIPDataEng,
+    pub vip_cnt_ch13: VIPDataEng,
+    pub vip_cnt_ch14: VIPDataEng,
+    pub vip_cnt_ch15: VIPDataEng,
+    pub ccd4: CondChnShortDataEng,
+    pub ccd5: CondChnShortDataEng,
+    pub stat_ch_ext_on: ExtChannelOnState,
+    pub stat_ch_ext_ocf: ExtChannelOverCurrentState,
+    pub vip_cnt_ch16: VIPDataEng,
+}
+impl EngUnits for PIUHk {
+    type Eng = PIUHkEng;
+    fn eng(&self) -> PIUHkEng {
+        PIUHkEng {
+            volt_brdsup: volts(self.volt_brdsup),
+            temp: degc(self.temp),
+            vip_dist_input: self.vip_dist_input.eng(),
+            vip_batt_input: self.vip_batt_input.eng(),
+            stat_ch_on: self.stat_ch_on,
+            stat_ch_ocf: self.stat_ch_ocf,
+            batt_stat: self.batt_stat,
+            batt_temp2: degc(self.batt_temp2),
+            batt_temp3: degc(self.batt_temp3),
+            volt_vd0: volts(self.volt_vd0),
+            volt_vd1: volts(self.volt_vd1),
+            volt_vd2: volts(self.volt_vd2),
+            vip_cnt_ch00: self.vip_cnt_ch00.eng(),
+            vip_cnt_ch01: self.vip_cnt_ch01.eng(),
+            vip_cnt_ch02: self.vip_cnt_ch02.eng(),
+            vip_cnt_ch03: self.vip_cnt_ch03.eng(),
+            vip_cnt_ch04: self.vip_cnt_ch04.eng(),
+            vip_cnt_ch05: self.vip_cnt_ch05.eng(),
+            vip_cnt_ch06: self.vip_cnt_ch06.eng(),
+            vip_cnt_ch07: self.vip_cnt_ch07.eng(),
+            vip_cnt_ch08: self.vip_cnt_ch08.eng(),
+            ccd1: self.ccd1.eng(),
+            ccd2: self.ccd2.eng(),
+            ccd3: self.ccd3.eng(),
+            vip_cnt_ch09: self.vip_cnt_ch09.eng(),
+            vip_cnt_ch10: self.vip_cnt_ch10.eng(),
+            vip_cnt_ch11: self.vip_cnt_ch11.eng(),
+            vip_cnt_ch12: self.vip_cnt_ch12.eng(),
+            vip_cnt_ch13: self.vip_cnt_ch13.eng(),
+            vip_cnt_ch14: self.vip_cnt_ch14.eng(),
+            vip_cnt_ch15: self.vip_cnt_ch15.eng(),
+            ccd4: self.ccd4.eng(),
+            ccd5: self.ccd5.eng(),
+            stat_ch_ext_on: self.stat_ch_ext_on,
+            stat_ch_ext_ocf: self.stat_ch_ext_ocf,
+            vip_cnt_ch16: self.vip_cnt_ch16.eng(),
+        }
+    }
+}
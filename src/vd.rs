@@ -0,0 +1,142 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Voltage domain (VD) to output channel mapping
+// The default mapping is the one of the ICEPSv2 used by CUAVA (see Channel
+// in objects.rs), the mapping of a unit can be read back
+// from the Vd1AllocChBf - Vd6AllocChBf parameters with Eps::vd_map().
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+#[cfg(feature = "serde")]
+use serde::*;
+
+// Output channel and the rail feeding it
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChannelRail {
+    pub channel: ChannelId,
+    // Voltage domain 0 - 6
+    pub domain: u8,
+    // Position of the channel in the domain, e.g. 1 for VD2_1
+    pub index: u8,
+    // Nominal voltage in mV, None if unknown
+    pub nominal_mv: Option<u16>,
+}
+
+const VD_ALLOC_PARAMS: [ConfigParamRead; 6] = [
+    ConfigParamRead::Vd1AllocChBf,
+    ConfigParamRead::Vd2AllocChBf,
+    ConfigParamRead::Vd3AllocChBf,
+    ConfigParamRead::Vd4AllocChBf,
+    ConfigParamRead::Vd5AllocChBf,
+    ConfigParamRead::Vd6AllocChBf,
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VdMap {
+    pub rails: Vec<ChannelRail>,
+}
+impl Default for VdMap {
+    fn default() -> Self {
+        VdMap {
+            rails: Channel::ALL
+                .iter()
+                .map(|c| ChannelRail {
+                    channel: c.index(),
+                    domain: c.domain(),
+                    index: c.domain_index(),
+                    nominal_mv: Some(c.nominal_mv()),
+                })
+                .collect(),
+        }
+    }
+}
+impl VdMap {
+    // Build from the channel allocation bitfields of VD1 - VD6, bit n = channel n.
+    // Channels not allocated to any of them are on VD0.
+    pub fn from_alloc(alloc: [u32; 6]) -> Self {
+        let mut count = [0u8; 7];
+        let rails = (0..Channel::ALL.len())
+            .map(|ch| {
+                let domain = alloc
+                    .iter()
+                    .position(|bf| bf & (1 << ch) != 0)
+                    .map(|vd| vd as u8 + 1)
+                    .unwrap_or(0);
+                let index = count[domain as usize];
+                count[domain as usize] += 1;
+                ChannelRail {
+                    channel: ch as ChannelId,
+                    domain,
+                    index,
+                    nominal_mv: nominal_mv(ch, domain),
+                }
+            })
+            .collect();
+        VdMap { rails }
+    }
+
+    // Rail feeding a channel
+    pub fn rail(&self, channel: ChannelId) -> Option<&ChannelRail> {
+        self.rails.iter().find(|r| r.channel == channel)
+    }
+
+    // Channels on a voltage domain
+    pub fn channels_on_domain(&self, domain: u8) -> Vec<ChannelId> {
+        self.rails
+            .iter()
+            .filter(|r| r.domain == domain)
+            .map(|r| r.channel)
+            .collect()
+    }
+
+    // Channels with the given nominal voltage, e.g. 12000 for the 12 V channels
+    pub fn channels_at(&self, nominal_mv: u16) -> Vec<ChannelId> {
+        self.rails
+            .iter()
+            .filter(|r| r.nominal_mv == Some(nominal_mv))
+            .map(|r| r.channel)
+            .collect()
+    }
+}
+
+// Nominal voltage of a channel on a domain, taken from the default mapping
+fn nominal_mv(ch: usize, domain: u8) -> Option<u16> {
+    match Channel::ALL.get(ch) {
+        Some(c) if c.domain() == domain => Some(c.nominal_mv()),
+        _ => Channel::ALL
+            .iter()
+            .find(|c| c.domain() == domain && c.domain_index() == 0)
+            .map(|c| c.nominal_mv()),
+    }
+}
+
+impl Eps {
+    // Read the voltage domain allocation of the unit
+    pub fn vd_map(&self) -> EpsResult<VdMap> {
+        let mut alloc = [0u32; 6];
+        for (a, param) in alloc.iter_mut().zip(VD_ALLOC_PARAMS.iter()) {
+            *a = match self.get_config_para_read(param.clone())? {
+                Output::U32(x) => x,
+                _ => return Err(EpsError::InvalidInput),
+            };
+        }
+        Ok(VdMap::from_alloc(alloc))
+    }
+}
@@ -0,0 +1,98 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Telecommand verification
+// With verification enabled, every state changing command (channel on/off,
+// group outputs, config sets, mode switch) is followed by a read-back and
+// EpsError::VerificationFailed is returned if the EPS doesn't show the
+// commanded state. The command itself has been executed in that case.
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+use crate::objects::*;
+
+impl Eps {
+    pub fn verify(&self) -> bool {
+        self.verify
+    }
+
+    // Enable or disable read-back verification (disabled by default)
+    pub fn set_verify(&mut self, verify: bool) {
+        self.verify = verify;
+    }
+
+    // Check the written config parameter reads back as expected
+    pub(crate) fn verify_config(
+        &self,
+        param: ConfigParamWrite,
+        result: &EpsResult<Output>,
+        expected: Output,
+    ) -> EpsResult<()> {
+        if !self.verify || result.is_err() {
+            return Ok(());
+        }
+        let actual = self.get_config_para_write(param)?;
+        if actual != expected {
+            #[cfg(feature = "debug")]
+            eps_log! {"Verification Failed: expected {:?}, read {:?}",expected,actual};
+            return Err(EpsError::VerificationFailed { expected, actual });
+        }
+        Ok(())
+    }
+
+    // Check the channels in on are on and the channels in off are off,
+    // bit n = channel n for channel 0 - 31
+    pub(crate) fn verify_channels(&self, on: u32, off: u32) -> EpsResult<()> {
+        if !self.verify {
+            return Ok(());
+        }
+        let states = self.channel_states()?;
+        let actual = states.on.on() as u32 | (states.ext_on.bits() as u32) << 16;
+        if actual & on != on || actual & off != 0 {
+            #[cfg(feature = "debug")]
+            eps_log! {"Verification Failed: on {:#x}, off {:#x}, read {:#x}",on,off,actual};
+            return Err(EpsError::VerificationFailed {
+                expected: Output::U32((actual | on) & !off),
+                actual: Output::U32(actual),
+            });
+        }
+        Ok(())
+    }
+
+    // Check the EPS is in the commanded mode
+    pub(crate) fn verify_mode(&self, mode: &ModeSwitch) -> EpsResult<()> {
+        if !self.verify {
+            return Ok(());
+        }
+        let actual = self.system_status()?.mode();
+        let matches = matches!(
+            (mode, &actual),
+            (ModeSwitch::Nominal, EpsMode::Nominal) | (ModeSwitch::Safety, EpsMode::Safety)
+        );
+        if !matches {
+            // Reported as EpsMode codes (Startup 0, Nominal 1, Safety 2, Contingency 3)
+            let expected = match mode {
+                ModeSwitch::Nominal => EpsMode::Nominal,
+                ModeSwitch::Safety => EpsMode::Safety,
+            };
+            return Err(EpsError::VerificationFailed {
+                expected: Output::U8(expected as u8),
+                actual: Output::U8(actual as u8),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Latest-value watch channels
+// A Watch holds only the newest value, receivers read it without queueing and
+// without touching the bus, like tokio::sync::watch but on std primitives.
+// HkPoller::spawn_watched publishes every successful read on a Watch per product.
+
+use crate::objects::*;
+use crate::poller::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+struct Shared<T> {
+    // Latest value and its version, counting up from 1 with every send
+    value: Mutex<(Option<T>, u64)>,
+    changed: Condvar,
+}
+
+// Sending side, replaces the value
+pub struct Watch<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Clone> Watch<T> {
+    pub fn new() -> Self {
+        Watch {
+            shared: Arc::new(Shared {
+                value: Mutex::new((None, 0)),
+                changed: Condvar::new(),
+            }),
+        }
+    }
+
+    pub fn send(&self, value: T) {
+        if let Ok(mut v) = self.shared.value.lock() {
+            v.0 = Some(value);
+            v.1 += 1;
+            self.shared.changed.notify_all();
+        }
+    }
+
+    pub fn subscribe(&self) -> WatchReceiver<T> {
+        WatchReceiver {
+            shared: self.shared.clone(),
+            seen: 0,
+        }
+    }
+}
+impl<T: Clone> Default for Watch<T> {
+    fn default() -> Self {
+        Watch::new()
+    }
+}
+
+pub struct WatchReceiver<T> {
+    shared: Arc<Shared<T>>,
+    // Version last returned by changed()
+    seen: u64,
+}
+impl<T> Clone for WatchReceiver<T> {
+    fn clone(&self) -> Self {
+        WatchReceiver {
+            shared: self.shared.clone(),
+            seen: self.seen,
+        }
+    }
+}
+
+impl<T: Clone> WatchReceiver<T> {
+    // Newest value, None if nothing was sent yet
+    pub fn latest(&self) -> Option<T> {
+        match self.shared.value.lock() {
+            Ok(v) => v.0.clone(),
+            Err(_) => None,
+        }
+    }
+
+    // True if a value was sent since the last changed()
+    pub fn has_changed(&self) -> bool {
+        match self.shared.value.lock() {
+            Ok(v) => v.1 != self.seen,
+            Err(_) => false,
+        }
+    }
+
+    // Wait up to timeout for a value newer than the last one returned here
+    pub fn changed(&mut self, timeout: Duration) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        let mut v = match self.shared.value.lock() {
+            Ok(v) => v,
+            Err(_) => return None,
+        };
+        while v.1 == self.seen {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            if wait.is_zero() {
+                return None;
+            }
+            v = match self.shared.changed.wait_timeout(v, wait) {
+                Ok((v, _)) => v,
+                Err(_) => return None,
+            };
+        }
+        self.seen = v.1;
+        v.0.clone()
+    }
+}
+
+// Latest poll results per product, cheap to clone and share between consumers
+#[derive(Clone, Default)]
+pub struct HkWatch {
+    receivers: HashMap<PollProduct, WatchReceiver<PollData>>,
+}
+
+impl HkWatch {
+    pub(crate) fn new(receivers: HashMap<PollProduct, WatchReceiver<PollData>>) -> Self {
+        HkWatch { receivers }
+    }
+
+    pub fn latest(&self, product: &PollProduct) -> Option<PollData> {
+        self.receivers.get(product).and_then(|r| r.latest())
+    }
+
+    // Receiver for one product, None if the product isn't polled
+    pub fn subscribe(&self, product: &PollProduct) -> Option<WatchReceiver<PollData>> {
+        self.receivers.get(product).cloned()
+    }
+
+    // Latest PIU housekeeping of any selection
+    pub fn piu_hk(&self) -> Option<PIUHk> {
+        self.receivers.values().find_map(|r| match r.latest() {
+            Some(PollData::Hk(HkData::Piu(hk))) => Some(hk),
+            _ => None,
+        })
+    }
+
+    // Latest PBU housekeeping (battery packs) of any selection
+    pub fn pbu_hk(&self) -> Option<PBUHk> {
+        self.receivers.values().find_map(|r| match r.latest() {
+            Some(PollData::Hk(HkData::Pbu(hk))) => Some(hk),
+            _ => None,
+        })
+    }
+}
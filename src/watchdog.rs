@@ -0,0 +1,135 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Background watchdog kicker
+// The EPS resets itself when the TTC watchdog isn't reset within
+// TtcWdgTimeout. WatchdogGuard resets it from a background thread at a fixed
+// interval below the timeout, until it is stopped or dropped. Pausing keeps
+// the thread but stops the kicks, e.g. to test the watchdog reset on ground.
+
+use crate::config::*;
+use crate::eps::*;
+use crate::error::*;
+#[cfg(feature = "serde")]
+use serde::*;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// Watchdog resets sent by a guard
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WatchdogStats {
+    pub kicks: u64,
+    pub failures: u64,
+}
+
+enum Control {
+    Pause,
+    Resume,
+}
+
+// Running watchdog kicker, the thread is stopped when the guard is dropped
+pub struct WatchdogGuard {
+    control: Sender<Control>,
+    paused: bool,
+    thread: Option<JoinHandle<WatchdogStats>>,
+}
+
+impl WatchdogGuard {
+    // Start resetting the watchdog every interval, the first reset is sent immediately.
+    // The interval must be below the TtcWdgTimeoutUsed of the unit.
+    pub fn spawn(eps: Arc<Mutex<Eps>>, interval: Duration) -> EpsResult<WatchdogGuard> {
+        let timeout = match eps.lock() {
+            Ok(eps) => eps.get_config_para_read(ConfigParamRead::TtcWdgTimeoutUsed)?,
+            Err(_) => return Err(EpsError::Err),
+        };
+        // A timeout of 0 disables the watchdog
+        let timeout = Duration::from_secs(timeout.as_i64().max(0) as u64);
+        if interval.is_zero() || (!timeout.is_zero() && interval >= timeout) {
+            return Err(EpsError::InvalidInput);
+        }
+        Ok(WatchdogGuard::spawn_unchecked(eps, interval))
+    }
+
+    // Start without checking the interval against the timeout of the unit
+    pub fn spawn_unchecked(eps: Arc<Mutex<Eps>>, interval: Duration) -> WatchdogGuard {
+        let (control, commands) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            let mut stats = WatchdogStats::default();
+            let mut paused = false;
+            loop {
+                if !paused {
+                    // A panic elsewhere mustn't stop the kicks, see SharedEps::lock()
+                    let eps = eps.lock().unwrap_or_else(PoisonError::into_inner);
+                    match eps.watchdog_reset() {
+                        Ok(()) => stats.kicks += 1,
+                        Err(_e) => {
+                            stats.failures += 1;
+                            #[cfg(feature = "debug")]
+                            eps_log! {"Watchdog Reset failed {:?}",_e};
+                        }
+                    }
+                }
+                match commands.recv_timeout(interval) {
+                    Ok(Control::Pause) => paused = true,
+                    Ok(Control::Resume) => paused = false,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            stats
+        });
+        WatchdogGuard {
+            control,
+            paused: false,
+            thread: Some(thread),
+        }
+    }
+
+    // Stop the resets, the watchdog will expire unless resumed in time
+    pub fn pause(&mut self) {
+        let _ = self.control.send(Control::Pause);
+        self.paused = true;
+    }
+
+    // Resume the resets, one is sent immediately
+    pub fn resume(&mut self) {
+        let _ = self.control.send(Control::Resume);
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Stop the thread, returns the resets sent while running
+    pub fn stop(mut self) -> WatchdogStats {
+        self.shutdown().unwrap_or_default()
+    }
+
+    fn shutdown(&mut self) -> Option<WatchdogStats> {
+        // Replacing the sender disconnects the channel, which ends the thread
+        let (control, _) = mpsc::channel();
+        drop(std::mem::replace(&mut self.control, control));
+        self.thread.take().and_then(|t| t.join().ok())
+    }
+}
+impl Drop for WatchdogGuard {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
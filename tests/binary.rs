@@ -0,0 +1,114 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Round trips of the fixed-size HK records (feature "bincode")
+#![cfg(feature = "bincode")]
+
+use isis_eps_api::*;
+use std::fmt::Debug;
+
+// Bytes with a varying pattern, so the fields don't all hold the same value
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + 3) as u8).collect()
+}
+
+fn round_trip<T: HkRecord + PartialEq + Debug>(hk: &T) {
+    let record = hk.to_record().unwrap();
+    assert_eq!(record.len(), T::record_len() + 2);
+    assert_eq!(&T::from_record(&record).unwrap(), hk);
+}
+
+fn system_status() -> SystemStatus {
+    let mut frame = pattern(36);
+    frame[5] = 1; // nominal mode
+    frame[7] = 2; // commanded reset
+    SystemStatus::try_from(frame.as_slice()).unwrap()
+}
+
+#[test]
+fn record_lengths() {
+    assert_eq!(SystemStatus::record_len() + 2, 39);
+    assert_eq!(PDUHk::record_len() + 2, 206);
+}
+
+#[test]
+fn system_status_round_trip() {
+    round_trip(&system_status());
+}
+
+#[test]
+fn channel_states_round_trip() {
+    round_trip(&OverCurrentFaultState::try_from(pattern(78).as_slice()).unwrap());
+    round_trip(&ChannelStates::try_from(pattern(10).as_slice()).unwrap());
+}
+
+#[test]
+fn abf_state_round_trip() {
+    let mut frame = pattern(8);
+    frame[6] = 0xAB;
+    round_trip(&ABFState::try_from(frame.as_slice()).unwrap());
+}
+
+#[test]
+fn pdu_pcu_round_trip() {
+    round_trip(&PDUHk::try_from(pattern(156).as_slice()).unwrap());
+    round_trip(&PCUHk::try_from(pattern(66).as_slice()).unwrap());
+}
+
+#[test]
+fn piu_round_trip() {
+    round_trip(&PIUHk::try_from(pattern(274).as_slice()).unwrap());
+    round_trip(&PIUHkShort::try_from(pattern(116).as_slice()).unwrap());
+}
+
+#[test]
+fn pbu_round_trip() {
+    // One and three battery packs
+    let one = PBUHk::try_from(pattern(34).as_slice()).unwrap();
+    assert_eq!(one.bp2, None);
+    let three = PBUHk::try_from(pattern(78).as_slice()).unwrap();
+    assert!(three.bp3.is_some());
+    // Records of all pack counts have the same length
+    assert_eq!(
+        one.to_record().unwrap().len(),
+        three.to_record().unwrap().len()
+    );
+    round_trip(&one);
+    round_trip(&three);
+    // bp3 without bp2
+    round_trip(&PBUHk { bp2: None, ..three });
+}
+
+#[test]
+fn record_errors() {
+    let record = system_status().to_record().unwrap();
+    let len = record.len();
+    assert_eq!(
+        SystemStatus::from_record(&record[..len - 1]),
+        Err(EpsError::ResponseTooShort(len, len - 1))
+    );
+    let mut long = record.clone();
+    long.push(0);
+    assert_eq!(
+        SystemStatus::from_record(&long),
+        Err(EpsError::InvalidInput)
+    );
+    let mut corrupt = record.clone();
+    corrupt[3] ^= 0x01;
+    assert_eq!(
+        SystemStatus::from_record(&corrupt),
+        Err(EpsError::CrcMismatch)
+    );
+}
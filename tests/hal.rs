@@ -0,0 +1,81 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Eps over HalTransport (feature "embedded")
+#![cfg(feature = "embedded")]
+
+use embedded_hal::i2c::{ErrorType, I2c, Operation, SevenBitAddress};
+use isis_eps_api::*;
+
+const ADDR: u8 = 0x20;
+
+// I2C bus with the emulated unit at ADDR
+struct MockBus(MockEps);
+
+impl ErrorType for MockBus {
+    type Error = embedded_hal::i2c::ErrorKind;
+}
+
+impl I2c<SevenBitAddress> for MockBus {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let nack = embedded_hal::i2c::ErrorKind::NoAcknowledge(
+            embedded_hal::i2c::NoAcknowledgeSource::Address,
+        );
+        if address != ADDR {
+            return Err(nack);
+        }
+        for op in operations {
+            match op {
+                Operation::Write(frame) => {
+                    let command = Command {
+                        cmd: frame[0],
+                        data: frame[1..].to_vec(),
+                    };
+                    self.0.write(command).map_err(|_| nack)?;
+                }
+                Operation::Read(buf) => {
+                    let response = self.0.read(0x1A, buf.len()).map_err(|_| nack)?;
+                    buf.copy_from_slice(&response);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn commands_over_embedded_hal() {
+    let mock = MockEps::new();
+    let eps = Eps::with_transport(HalTransport::new(MockBus(mock.clone()), ADDR)).unwrap();
+    eps.eps_ping().unwrap();
+    eps.set_single_output(BusChannel::On, Channel::try_from(3u8).unwrap())
+        .unwrap();
+    assert_eq!(mock.channels(), 1 << 3);
+    let states = eps.channel_states().unwrap();
+    assert_eq!(u16::from(states.on), 1 << 3);
+    assert!(eps.system_status().is_ok());
+}
+
+#[test]
+fn bus_errors() {
+    let mock = MockEps::new();
+    let eps = Eps::with_transport(HalTransport::new(MockBus(mock.clone()), 0x21)).unwrap();
+    assert_eq!(eps.eps_ping(), Err(EpsError::TransferError));
+    assert_eq!(mock.commands(), 0);
+}
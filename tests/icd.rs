@@ -0,0 +1,80 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Strict ICD checks of the responses
+use isis_eps_api::*;
+use std::time::Duration;
+
+// MockEps with the responses cut or padded to len bytes
+struct Resized {
+    mock: MockEps,
+    len: Option<usize>,
+}
+
+impl EpsTransport for Resized {
+    fn transfer(
+        &self,
+        command: Command,
+        rx_len: usize,
+        delay: Duration,
+    ) -> std::io::Result<Vec<u8>> {
+        let mut x = self.mock.transfer(command, rx_len, delay)?;
+        if let Some(len) = self.len {
+            x.resize(len, 0);
+        }
+        Ok(x)
+    }
+}
+
+fn strict_eps(len: Option<usize>) -> Eps {
+    let mut eps = Eps::with_transport(Resized {
+        mock: MockEps::new(),
+        len,
+    })
+    .unwrap();
+    eps.set_strict(true);
+    eps
+}
+
+#[test]
+fn icd_lengths_pass() {
+    let eps = strict_eps(None);
+    eps.eps_ping().unwrap();
+    eps.system_status().unwrap();
+    // Deliberate short read of the overcurrent fault state
+    eps.channel_states().unwrap();
+    eps.overcurrent_state().unwrap();
+    eps.piu_hk(PIUHkSel::PIUEngHK).unwrap();
+    eps.pbu_hk(PBUHkSel::PBUEngHK).unwrap();
+    eps.get_config_para_write(ConfigParamWrite::TtcWdgTimeout)
+        .unwrap();
+}
+
+#[test]
+fn length_deviation() {
+    let mut eps = strict_eps(Some(30));
+    assert_eq!(
+        eps.system_status(),
+        Err(EpsError::IcdDeviation(
+            "length 30 expected one of [36]".to_string()
+        ))
+    );
+    // Not checked without strict mode
+    eps.set_strict(false);
+    assert!(matches!(
+        eps.system_status(),
+        Err(EpsError::ResponseTooShort(36, 30))
+    ));
+}
@@ -0,0 +1,157 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Eps driven over the MockEps emulator
+use isis_eps_api::*;
+use std::time::Duration;
+
+fn channel(ch: u8) -> Channel {
+    Channel::try_from(ch).unwrap()
+}
+
+fn mock_eps() -> (MockEps, Eps) {
+    let mock = MockEps::new();
+    let eps = Eps::with_transport(mock.clone()).unwrap();
+    (mock, eps)
+}
+
+// Model with a 500 mA load on channel 3 that is latched off above 400 mA
+fn overloaded_model() -> PowerModel {
+    let mut model = PowerModel::new(BatteryModel::default(), SolarModel::default(), 0.8);
+    model.set_load(
+        3,
+        ChannelLoad {
+            current_ma: 500.0,
+            limit_ma: Some(400.0),
+        },
+    );
+    model
+}
+
+#[test]
+fn switching() {
+    let (mock, eps) = mock_eps();
+    eps.set_single_output(BusChannel::On, channel(3)).unwrap();
+    eps.set_single_output(BusChannel::On, channel(16)).unwrap();
+    eps.set_group_outputs(BusGroup::BusGroupOn, vec![channel(0), channel(1)])
+        .unwrap();
+    assert_eq!(mock.channels(), 1 << 16 | 0b1011);
+
+    eps.set_single_output(BusChannel::Off, channel(1)).unwrap();
+    assert_eq!(mock.channels(), 1 << 16 | 0b1001);
+    let hk = eps.piu_hk(PIUHkSel::PIUEngHK).unwrap();
+    assert_eq!(hk.stat_ch_on, ChannelOnState::from(0b1001));
+    assert_eq!(hk.stat_ch_ext_on, ExtChannelOnState::from(1));
+
+    // Safety mode refuses switching
+    eps.mode_switch(ModeSwitch::Safety).unwrap();
+    assert!(eps.set_single_output(BusChannel::On, channel(2)).is_err());
+    assert_eq!(mock.channels(), 1 << 16 | 0b1001);
+}
+
+#[test]
+fn hk_without_model() {
+    let (_mock, eps) = mock_eps();
+    assert_eq!(eps.pbu_hk(PBUHkSel::PBUEngHK).unwrap(), PBUHk::default());
+    assert_eq!(eps.pcu_hk(PCUHkSel::PCUEngHK).unwrap(), PCUHk::default());
+    assert_eq!(eps.pdu_hk(PDUHkSel::PDUEngHK).unwrap(), PDUHk::default());
+}
+
+#[test]
+fn hk_from_model() {
+    let (mock, eps) = mock_eps();
+    let mut model = overloaded_model();
+    model.switch(5, true);
+    // Into the sunlit part of the orbit
+    model.step(Duration::from_secs(600));
+    mock.set_power_model(model);
+    assert_eq!(mock.channels(), 1 << 5);
+
+    let model = mock.power_model().unwrap();
+    let pcu = eps.pcu_hk(PCUHkSel::PCUEngHK).unwrap();
+    assert!(pcu.ccd1.curr_in_mppt > 0);
+    assert_eq!(pcu.ccd1.curr_in_mppt, model.pcu_hk().ccd1.curr_in_mppt);
+    assert_eq!(pcu.ccd4.volt_out_mppt, model.pcu_hk().ccd4.volt_out_mppt);
+    // Power is sent in 10 mW steps
+    assert_eq!(pcu.vip_output.pwr, model.pcu_hk().vip_output.pwr / 10 * 10);
+    let pbu = eps.pbu_hk(PBUHkSel::PBUAvgHK).unwrap();
+    assert_eq!(pbu.bp1.cell_voltages(), model.pbu_hk().bp1.cell_voltages());
+    assert_eq!(pbu.vip_input.volt, model.pbu_hk().vip_input.volt);
+    // Raw HK isn't modelled
+    assert_eq!(eps.pcu_hk(PCUHkSel::PCURawHK).unwrap(), PCUHk::default());
+}
+
+#[test]
+fn model_follows_commands() {
+    let (mock, eps) = mock_eps();
+    mock.set_power_model(overloaded_model());
+    eps.set_single_output(BusChannel::On, channel(3)).unwrap();
+    assert_eq!(mock.power_model().unwrap().channels_on(), 1 << 3);
+    let idle = mock.power_model().unwrap().load_mw();
+
+    eps.set_single_output(BusChannel::Off, channel(3)).unwrap();
+    assert_eq!(mock.power_model().unwrap().channels_on(), 0);
+    assert!(mock.power_model().unwrap().load_mw() < idle);
+}
+
+#[test]
+fn step_keeps_overcurrent_latch() {
+    let (mock, eps) = mock_eps();
+    mock.set_power_model(overloaded_model());
+    eps.set_single_output(BusChannel::On, channel(3)).unwrap();
+    mock.step(Duration::from_secs(1));
+    assert_eq!(mock.channels(), 0);
+    // Further steps and commands on other channels don't switch it back on
+    eps.set_single_output(BusChannel::On, channel(5)).unwrap();
+    mock.step(Duration::from_secs(1));
+    assert_eq!(mock.channels(), 1 << 5);
+    assert_eq!(mock.power_model().unwrap().channels_on(), 1 << 5);
+}
+
+#[test]
+fn overcurrent_reported() {
+    let (mock, eps) = mock_eps();
+    mock.set_power_model(overloaded_model());
+    eps.set_single_output(BusChannel::On, channel(3)).unwrap();
+    eps.set_single_output(BusChannel::On, channel(5)).unwrap();
+    let oc = eps.overcurrent_state().unwrap();
+    assert_eq!(oc.stat_ch_ocf, ChannelOverCurrentState::from(0));
+
+    mock.step(Duration::from_secs(1));
+    let oc = eps.overcurrent_state().unwrap();
+    assert_eq!(oc.stat_ch_on, ChannelOnState::from(1 << 5));
+    assert_eq!(oc.stat_ch_ocf, ChannelOverCurrentState::from(1 << 3));
+    assert_eq!(oc.count(channel(3)), 1);
+    assert_eq!(oc.count(channel(5)), 0);
+
+    // Switching it on again clears the latch, the counter stays
+    eps.set_single_output(BusChannel::On, channel(3)).unwrap();
+    let oc = eps.overcurrent_state().unwrap();
+    assert_eq!(oc.stat_ch_ocf, ChannelOverCurrentState::from(0));
+    assert_eq!(oc.count(channel(3)), 1);
+}
+
+#[test]
+fn model_without_cells() {
+    let battery = BatteryModel {
+        cells_series: 0,
+        ..BatteryModel::default()
+    };
+    let mut model = PowerModel::new(battery, SolarModel::default(), 0.5);
+    model.step(Duration::from_secs(600));
+    assert!(model.battery_ma().is_finite());
+    assert!(model.cell_mv().is_finite());
+    assert!(model.pcu_hk().ccd1.curr_out_mppt > 0);
+}
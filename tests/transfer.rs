@@ -0,0 +1,83 @@
+//
+// Copyright (C) 2022 CUAVA, The University of Sydney
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transaction timeout of Eps::transfer
+use isis_eps_api::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// MockEps answering only after a fixed time, or failing after it
+struct SlowTransport {
+    mock: MockEps,
+    latency: Duration,
+    fail: bool,
+    attempts: Arc<AtomicUsize>,
+}
+
+impl EpsTransport for SlowTransport {
+    fn transfer(
+        &self,
+        command: Command,
+        rx_len: usize,
+        delay: Duration,
+    ) -> std::io::Result<Vec<u8>> {
+        self.attempts.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(self.latency);
+        if self.fail {
+            return Err(std::io::ErrorKind::BrokenPipe.into());
+        }
+        self.mock.transfer(command, rx_len, delay)
+    }
+}
+
+// Eps with a 20 ms transaction timeout over a transport taking 50 ms per attempt
+fn slow_eps(mock: &MockEps, fail: bool, attempts: &Arc<AtomicUsize>) -> Eps {
+    let mut eps = Eps::with_transport(SlowTransport {
+        mock: mock.clone(),
+        latency: Duration::from_millis(50),
+        fail,
+        attempts: attempts.clone(),
+    })
+    .unwrap();
+    eps.set_pacing(PacingProfile {
+        timeout: Some(Duration::from_millis(20)),
+        ..PacingProfile::default()
+    })
+    .unwrap();
+    eps.set_retry_policy(Some(RetryPolicy::default()));
+    eps
+}
+
+#[test]
+fn late_success_is_returned() {
+    let mock = MockEps::new();
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let eps = slow_eps(&mock, false, &attempts);
+    let channel = Channel::try_from(3u8).unwrap();
+    assert_eq!(eps.set_single_output(BusChannel::On, channel), Ok(()));
+    assert_eq!(mock.channels(), 1 << 3);
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn late_failure_is_not_retried() {
+    let mock = MockEps::new();
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let eps = slow_eps(&mock, true, &attempts);
+    let channel = Channel::try_from(3u8).unwrap();
+    assert!(eps.set_single_output(BusChannel::On, channel).is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}